@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+
+use alloy_primitives::B256;
+use auction_interface::headers::{subscribe_headers, HeaderClient, HeaderSource, L1Header};
+use color_eyre::eyre::{self, Context};
+use futures_util::StreamExt;
+use tokio::{
+    sync::mpsc::UnboundedSender,
+    time::{sleep, timeout, Duration},
+};
+use url::Url;
+
+use crate::strategy::BlockHeader;
+
+/// Hashes of the latest blocks kept to find where a reorg forked off.
+const TRACKED_BLOCKS: usize = 128;
+
+/// How the listener keeps its `newHeads` subscription alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Wait before the first reconnect, doubled after every failed attempt
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// The subscription is reopened after this long without a new head
+    pub idle_timeout: Duration,
+    /// Most missed or reorged blocks read when a head doesn't follow the last one, older
+    /// ones are skipped
+    pub max_backfill: u64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            idle_timeout: Duration::from_secs(60),
+            max_backfill: 32,
+        }
+    }
+}
+
+/// A new L1 block for the listener to act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewHead {
+    pub header: BlockHeader,
+    pub hash: B256,
+    pub parent_hash: B256,
+    /// Read over http after the subscription missed it or a reorg replaced it, its auction
+    /// round has already passed
+    pub backfilled: bool,
+}
+
+/// Blocks the listener followed that are no longer canonical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reorg {
+    /// Number of orphaned blocks
+    pub depth: u64,
+    /// Last block the old and new chain share
+    pub common_ancestor: u64,
+    pub old_tip: u64,
+    pub new_tip: u64,
+}
+
+/// What the head follower sends the listener.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeadEvent {
+    /// The chain reorged, sent before the new chain's heads
+    Reorg(Reorg),
+    Head(NewHead),
+}
+
+/// Follows `newHeads` on `ws_url`, sending each new block to `heads` in order.
+///
+/// Heads are only sent once the node has confirmed the subscription, so every one is a
+/// complete header. The subscription is reopened with exponential backoff whenever the
+/// connection drops, errors, or goes without a new head for longer than the idle timeout.
+/// Blocks produced while it was down are read from `backfill` and sent before the first new
+/// head.
+///
+/// Each head's parent hash is checked against the block followed at its parent's height. On
+/// a mismatch, or a head at or below the last one, the new chain is read back from `backfill`
+/// until it joins the followed one and a `Reorg` is sent before the new chain's heads.
+/// Returns once `heads` is closed.
+pub(crate) async fn follow_heads(
+    ws_url: Url,
+    policy: ReconnectPolicy,
+    backfill: HeaderClient,
+    heads: UnboundedSender<HeadEvent>,
+) {
+    let mut follower = HeadFollower::new(policy, Box::new(backfill), heads);
+    loop {
+        match follower.follow(&ws_url).await {
+            Ok(()) => println!("--> !!! L1 new heads subscription ended"),
+            Err(e) => println!("--> !!! L1 new heads subscription failed: {:#}", e),
+        }
+        if follower.heads.is_closed() {
+            return;
+        }
+        println!(
+            "--| resubscribing to L1 new heads in {:?}",
+            follower.backoff
+        );
+        sleep(follower.backoff).await;
+        follower.backoff = (follower.backoff * 2).min(follower.policy.max_backoff);
+    }
+}
+
+struct HeadFollower {
+    policy: ReconnectPolicy,
+    backfill: Box<dyn HeaderSource>,
+    heads: UnboundedSender<HeadEvent>,
+    /// Hashes of the latest blocks sent, by number
+    followed: BTreeMap<u64, B256>,
+    backoff: Duration,
+}
+
+impl HeadFollower {
+    fn new(
+        policy: ReconnectPolicy,
+        backfill: Box<dyn HeaderSource>,
+        heads: UnboundedSender<HeadEvent>,
+    ) -> Self {
+        Self {
+            policy,
+            backfill,
+            heads,
+            followed: BTreeMap::new(),
+            backoff: policy.initial_backoff,
+        }
+    }
+
+    /// Subscribes and reads heads until the subscription ends.
+    async fn follow(&mut self, ws_url: &Url) -> eyre::Result<()> {
+        let mut headers = timeout(self.policy.idle_timeout, subscribe_headers(ws_url.as_str()))
+            .await
+            .context("timed out subscribing to L1 new heads")??;
+        // the subscription is confirmed, the listener is ready for heads
+        println!("--| subscribed to L1 new heads");
+        self.backoff = self.policy.initial_backoff;
+
+        loop {
+            let header = timeout(self.policy.idle_timeout, headers.next())
+                .await
+                .map_err(|_| eyre::eyre!("no new head for {:?}", self.policy.idle_timeout))?;
+            let Some(header) = header else {
+                return Ok(());
+            };
+            match header {
+                Ok(header) => self.on_head(header).await,
+                Err(e) => println!("--> !!! ignoring head: {:#}", e),
+            }
+            eyre::ensure!(!self.heads.is_closed(), "listener stopped");
+        }
+    }
+
+    /// Sends `header` after the blocks between it and the followed chain, reporting the
+    /// followed blocks it orphans.
+    async fn on_head(&mut self, header: L1Header) {
+        if self.followed.get(&header.number) == Some(&header.hash) {
+            println!("--| ignoring head {}, already seen", header.number);
+            return;
+        }
+        let Some((&last_number, _)) = self.followed.last_key_value() else {
+            self.send(&header, false);
+            return;
+        };
+
+        // read back from the new head until it joins the followed chain, through any blocks
+        // missed while disconnected and any followed blocks it replaced
+        let mut chain = vec![header];
+        loop {
+            let oldest = chain[chain.len() - 1];
+            let Some(parent_number) = oldest.number.checked_sub(1) else {
+                break;
+            };
+            match self.followed.get(&parent_number) {
+                Some(hash) if *hash == oldest.parent_hash => break,
+                // older than the blocks tracked, assume it joins
+                None if parent_number <= last_number => break,
+                _ => (),
+            }
+            if chain.len() as u64 > self.policy.max_backfill {
+                println!(
+                    "--> !!! skipping blocks before {}, too far back to fill in",
+                    oldest.number
+                );
+                break;
+            }
+            match self.backfill.header(parent_number).await {
+                Ok(Some(parent)) => chain.push(parent),
+                Ok(None) => {
+                    println!("--> !!! block {} not found", parent_number);
+                    break;
+                }
+                Err(e) => {
+                    println!("--> !!! failed to read block {}: {:#}", parent_number, e);
+                    break;
+                }
+            }
+        }
+        chain.reverse();
+
+        // followed blocks past where the new chain joins were orphaned
+        let common_ancestor = chain[0].number.saturating_sub(1);
+        let orphaned = self.followed.split_off(&(common_ancestor + 1));
+        if !orphaned.is_empty() {
+            let reorg = Reorg {
+                depth: orphaned.len() as u64,
+                common_ancestor,
+                old_tip: last_number,
+                new_tip: header.number,
+            };
+            println!(
+                "--> !!! reorg of depth {}: blocks {}..={} orphaned, new tip {}",
+                reorg.depth,
+                common_ancestor + 1,
+                last_number,
+                header.number
+            );
+            let _ = self.heads.send(HeadEvent::Reorg(reorg));
+        }
+
+        let tip = chain.len() - 1;
+        for (index, block) in chain.iter().enumerate() {
+            if index != tip {
+                println!("--| filled in block {}", block.number);
+            }
+            self.send(block, index != tip);
+        }
+    }
+
+    fn send(&mut self, header: &L1Header, backfilled: bool) {
+        self.followed.insert(header.number, header.hash);
+        while self.followed.len() > TRACKED_BLOCKS {
+            self.followed.pop_first();
+        }
+        let head = NewHead {
+            header: BlockHeader {
+                number: header.number,
+                timestamp: header.timestamp,
+            },
+            hash: header.hash,
+            parent_hash: header.parent_hash,
+            backfilled,
+        };
+        // a closed channel ends the subscription on the next head
+        let _ = self.heads.send(HeadEvent::Head(head));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::*;
+
+    /// Headers of a fork, by number.
+    struct Chain(HashMap<u64, L1Header>);
+
+    #[async_trait]
+    impl HeaderSource for Chain {
+        async fn header(&self, number: u64) -> eyre::Result<Option<L1Header>> {
+            Ok(self.0.get(&number).copied())
+        }
+    }
+
+    /// Block `number` of fork `fork`, whose blocks up to `forked_after` are fork 0's.
+    fn block(fork: u8, forked_after: u64, number: u64) -> L1Header {
+        let hash = |number: u64| {
+            let fork = if number <= forked_after { 0 } else { fork };
+            B256::left_padding_from(&[fork, number as u8])
+        };
+        L1Header {
+            number,
+            hash: hash(number),
+            parent_hash: hash(number - 1),
+            timestamp: number * 12,
+        }
+    }
+
+    /// A follower that followed fork 0 through `followed` and backfills from fork `fork`.
+    fn follower(
+        followed: u64,
+        fork: u8,
+        forked_after: u64,
+    ) -> (HeadFollower, UnboundedReceiver<HeadEvent>) {
+        let chain = (1..=followed + 8)
+            .map(|number| (number, block(fork, forked_after, number)))
+            .collect();
+        let (heads, events) = unbounded_channel();
+        let mut follower =
+            HeadFollower::new(ReconnectPolicy::default(), Box::new(Chain(chain)), heads);
+        for number in 1..=followed {
+            follower.send(&block(0, 0, number), false);
+        }
+        (follower, events)
+    }
+
+    /// The events sent since the followed blocks, as (number, backfilled) for heads.
+    fn events(events: &mut UnboundedReceiver<HeadEvent>) -> Vec<Result<(u64, bool), Reorg>> {
+        let mut sent = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event {
+                HeadEvent::Reorg(reorg) => sent.push(Err(reorg)),
+                HeadEvent::Head(head) => sent.push(Ok((head.header.number, head.backfilled))),
+            }
+        }
+        sent
+    }
+
+    #[tokio::test]
+    async fn reorg_reports_depth_and_common_ancestor() {
+        // fork 1 replaced blocks 4 and 5 and announces 6
+        let (mut follower, mut sent) = follower(5, 1, 3);
+        events(&mut sent);
+        follower.on_head(block(1, 3, 6)).await;
+        assert_eq!(
+            events(&mut sent),
+            vec![
+                Err(Reorg {
+                    depth: 2,
+                    common_ancestor: 3,
+                    old_tip: 5,
+                    new_tip: 6,
+                }),
+                Ok((4, true)),
+                Ok((5, true)),
+                Ok((6, false)),
+            ]
+        );
+        assert_eq!(follower.followed[&5], block(1, 3, 5).hash);
+    }
+
+    #[tokio::test]
+    async fn lower_head_is_a_reorg() {
+        // fork 1's block 4 is announced after fork 0's block 5
+        let (mut follower, mut sent) = follower(5, 1, 3);
+        events(&mut sent);
+        follower.on_head(block(1, 3, 4)).await;
+        assert_eq!(
+            events(&mut sent),
+            vec![
+                Err(Reorg {
+                    depth: 2,
+                    common_ancestor: 3,
+                    old_tip: 5,
+                    new_tip: 4,
+                }),
+                Ok((4, false)),
+            ]
+        );
+        assert_eq!(follower.followed.keys().last(), Some(&4));
+    }
+
+    #[tokio::test]
+    async fn missed_blocks_are_filled_in() {
+        let (mut follower, mut sent) = follower(3, 0, 0);
+        events(&mut sent);
+        follower.on_head(block(0, 0, 6)).await;
+        assert_eq!(
+            events(&mut sent),
+            vec![Ok((4, true)), Ok((5, true)), Ok((6, false))]
+        );
+
+        // the next head follows on without reading anything
+        follower.on_head(block(0, 0, 7)).await;
+        assert_eq!(events(&mut sent), vec![Ok((7, false))]);
+        // and a head seen twice is ignored
+        follower.on_head(block(0, 0, 7)).await;
+        assert!(events(&mut sent).is_empty());
+    }
+}
@@ -1,66 +1,183 @@
-use auction_interface::amm_auction::AuctionSuapp;
+use std::{collections::BTreeMap, sync::Arc};
+
+use auction_interface::{
+    amm_auction::AuctionSuapp,
+    amm_auction_config::AmmAuctionConfig,
+    headers::HeaderClient,
+    landing::BundleVerifier,
+    pool::PoolClient,
+    pool_lock::{PoolLockDetector, PoolLockTracker},
+};
 use color_eyre::eyre::{self, Context};
-use futures_util::{stream::StreamExt, SinkExt};
-use serde_json::Value;
-use std::time::{SystemTime, UNIX_EPOCH};
+use eyre::ContextCompat;
 use tokio::{
+    sync::{mpsc, Mutex},
     task::JoinHandle,
-    time::{sleep, Duration},
 };
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 use url::Url;
 
+pub mod heads;
+mod scheduler;
+pub mod strategy;
+
+/// Blocks a verified auction is remembered for, to verify it again if a reorg replaces its
+/// block.
+const KEPT_VERIFIED_BLOCKS: u64 = 64;
+
+use heads::{follow_heads, HeadEvent, ReconnectPolicy, Reorg};
+use scheduler::{RoundContext, RoundScheduler};
+use strategy::{strategy_from_config, BidStrategy};
+
 /// `BlockServer` is a service responsible for listening for new block produced on the given RCP
 /// websocket endpoint and performing an action.
 pub struct BlockServer {
     // L1 websocket url
     l1_websocket_url: Url,
+    // how the websocket subscription is kept alive
+    reconnect_policy: ReconnectPolicy,
+    // reads the blocks missed while the websocket was down
+    header_client: Option<HeaderClient>,
+    // reads the pool's price each block if set
+    pool_client: Option<PoolClient>,
+    // decides each bidder's bid per block
+    bid_strategy: Option<Box<dyn BidStrategy>>,
+    // checks each auctioned block for the suapp's bundle if set
+    bundle_verifier: Option<BundleVerifier>,
+    // checks each block for the pool being locked if set
+    lock_detector: Option<PoolLockDetector>,
+    config: AmmAuctionConfig,
 }
 
 impl BlockServer {
-    pub async fn new(l1_websocket: String) -> eyre::Result<Self> {
+    /// Bids with the strategy selected in `config` unless overridden with
+    /// `with_bid_strategy()`.
+    pub async fn new(l1_websocket: String, config: AmmAuctionConfig) -> eyre::Result<Self> {
         // Setup the WebSocket server URL
         let url = Url::parse(&l1_websocket).context("failed to parse URL")?;
+        let bid_strategy = strategy_from_config(&config).context("failed to build bid strategy")?;
+        let header_client =
+            HeaderClient::from_config(&config).context("failed to build L1 header client")?;
 
         Ok(Self {
             l1_websocket_url: url,
+            reconnect_policy: ReconnectPolicy::default(),
+            header_client: Some(header_client),
+            pool_client: None,
+            bid_strategy: Some(bid_strategy),
+            bundle_verifier: None,
+            lock_detector: None,
+            config,
         })
     }
 
+    /// Prints the auctioned pool's state before each block's auction.
+    pub fn with_pool_client(mut self, pool_client: PoolClient) -> Self {
+        self.pool_client = Some(pool_client);
+        self
+    }
+
+    /// Replaces the strategy deciding each bidder's bids.
+    pub fn with_bid_strategy(mut self, bid_strategy: Box<dyn BidStrategy>) -> Self {
+        self.bid_strategy = Some(bid_strategy);
+        self
+    }
+
+    /// Reports if each auctioned block's bundle landed once the block is produced.
+    pub fn with_bundle_verifier(mut self, bundle_verifier: BundleVerifier) -> Self {
+        self.bundle_verifier = Some(bundle_verifier);
+        self
+    }
+
+    /// Reports blocks the pool was locked in because no auction results landed.
+    pub fn with_lock_detector(mut self, lock_detector: PoolLockDetector) -> Self {
+        self.lock_detector = Some(lock_detector);
+        self
+    }
+
+    /// Overrides how the websocket is reconnected and checked for liveness.
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Follows the L1's new heads, reconnecting whenever the websocket drops, and runs an
+    /// auction round for each.
     pub async fn run_until_stopped(
         &mut self,
         amm_auction_suapp: AuctionSuapp,
     ) -> eyre::Result<JoinHandle<()>> {
-        // Connect to the server
-        let (ws_stream, _) = connect_async(self.l1_websocket_url.clone())
-            .await
-            .wrap_err("failed to connect to L1 websocket")?;
+        let bid_strategy = self
+            .bid_strategy
+            .take()
+            .context("block server was already started")?;
+        let header_client = self
+            .header_client
+            .take()
+            .context("block server was already started")?;
+        let monitor = Arc::new(Mutex::new(Monitor {
+            bundle_verifier: self.bundle_verifier.take(),
+            unverified_auctions: BTreeMap::new(),
+            verified_auctions: BTreeMap::new(),
+            lock_detector: self.lock_detector.take(),
+            lock_tracker: PoolLockTracker::new(),
+        }));
+        let mut scheduler = RoundScheduler::new(Arc::new(RoundContext {
+            suapp: amm_auction_suapp,
+            pool_client: self.pool_client.take(),
+            bid_strategy,
+            config: self.config.clone(),
+            monitor: monitor.clone(),
+            auction_lock: Mutex::new(()),
+            sent_bids: Mutex::new(BTreeMap::new()),
+        }));
 
-        // Split the stream into a sender and receiver
-        let (mut write, mut read) = ws_stream.split();
+        // follow the chain in its own task so the subscription is read while auctions run
+        let (heads_sender, mut heads) = mpsc::unbounded_channel();
+        tokio::spawn(follow_heads(
+            self.l1_websocket_url.clone(),
+            self.reconnect_policy,
+            header_client,
+            heads_sender,
+        ));
 
-        // Send a message to start subscription
-        let msg = Message::Text(
-            "{\"jsonrpc\":\"2.0\",\"id\": 2, \"method\": \"eth_subscribe\", \"params\": \
-             [\"newHeads\"]}"
-                .into(),
-        );
-        write
-            .send(msg)
-            .await
-            .wrap_err("failed to send subscription method to websocket")?;
+        // produced blocks are checked one at a time, in order, beside the rounds
+        let (produced_sender, mut produced) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(event) = produced.recv().await {
+                let mut monitor = monitor.lock().await;
+                match event {
+                    MonitorEvent::Produced(block_number) => {
+                        verify_bundle(&mut monitor, block_number).await;
+                        check_pool_lock(&mut monitor, block_number).await;
+                    }
+                    MonitorEvent::Reorg(reorg) => {
+                        // the new chain's blocks are checked again as they come
+                        monitor.lock_tracker.rollback(reorg.common_ancestor + 1);
+                        reverify_bundles(&mut monitor, reorg);
+                    }
+                }
+            }
+        });
 
-        // Spawn a task to handle incoming messages
         let api_task = tokio::spawn(async move {
-            let mut amm_auction_suapp = amm_auction_suapp;
-            while let Some(message) = read.next().await {
-                match message {
-                    Ok(msg) => match msg {
-                        Message::Text(text) => process_header(&mut amm_auction_suapp, text).await,
-                        _ => (),
-                    },
-                    Err(e) => println!("error receiving message: {}", e),
+            while let Some(event) = heads.recv().await {
+                let head = match event {
+                    HeadEvent::Reorg(reorg) => {
+                        let _ = produced_sender.send(MonitorEvent::Reorg(reorg));
+                        scheduler.on_reorg(reorg).await;
+                        continue;
+                    }
+                    HeadEvent::Head(head) => head,
+                };
+                let _ = produced_sender.send(MonitorEvent::Produced(head.header.number));
+                if head.backfilled {
+                    println!(
+                        "--| block {} was missed, its auction round has passed",
+                        head.header.number
+                    );
+                    continue;
                 }
+                scheduler.on_head(head).await;
             }
         });
 
@@ -68,80 +185,105 @@ impl BlockServer {
     }
 }
 
-fn get_random_amount() -> u128 {
-    let seed = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_micros();
-
-    (seed % 100) + 1
+/// What the produced block checks are told, in chain order.
+enum MonitorEvent {
+    Produced(u64),
+    Reorg(Reorg),
 }
 
-async fn trigger_auction(amm_auction_suapp: &mut AuctionSuapp, block_number: u128) {
-    println!("[~~~~  running auction for block: {} ~~~~]", block_number);
-
-    let mut bid_amount = get_random_amount();
-    // send bids
-    if let Err(e) = amm_auction_suapp
-        .new_bid(&"bidder_0".to_string(), block_number, bid_amount, 10, true)
-        .await
-    {
-        println!("--> !!! failed to send bid for bidder_0: {}", e);
-    } else {
-        println!("--> sent bid for bidder_0 for: {}", bid_amount);
-    }
-    bid_amount = get_random_amount();
-    if let Err(e) = amm_auction_suapp
-        .new_bid(&"bidder_1".to_string(), block_number, bid_amount, 10, true)
-        .await
-    {
-        println!("--> !!! failed to send bid for bidder_1: {}", e);
-    } else {
-        println!("--> sent bid for bidder_1 for: {}", bid_amount);
-    }
-    bid_amount = get_random_amount();
-    if let Err(e) = amm_auction_suapp
-        .new_bid(&"bidder_2".to_string(), block_number, bid_amount, 10, true)
-        .await
-    {
-        println!("--> !!! failed to send bid for bidder_2: {}", e);
-    } else {
-        println!("--> sent bid for bidder_2 for: {}", bid_amount);
-    }
+/// State of the checks made on each produced block.
+pub(crate) struct Monitor {
+    bundle_verifier: Option<BundleVerifier>,
+    /// Non-bid swaps in the bundle of each auctioned block, verified once it's produced
+    pub(crate) unverified_auctions: BTreeMap<u64, u64>,
+    /// Non-bid swaps of the auctions already verified, kept to verify them again after a reorg
+    verified_auctions: BTreeMap<u64, u64>,
+    lock_detector: Option<PoolLockDetector>,
+    /// Locked blocks seen since the listener started
+    lock_tracker: PoolLockTracker,
+}
 
-    // sleep a few seconds to let auction time pass
-    sleep(Duration::from_secs(4)).await;
+async fn verify_bundle(monitor: &mut Monitor, block_number: u64) {
+    // rounds overlap, so several auctions can be waiting on their block
+    let pending = monitor.unverified_auctions.split_off(&(block_number + 1));
+    let produced = std::mem::replace(&mut monitor.unverified_auctions, pending);
+    let Some(bundle_verifier) = &monitor.bundle_verifier else {
+        return;
+    };
 
-    if let Err(e) = amm_auction_suapp.trigger_auction().await {
-        println!("--> !!! failed to trigger auction: {}", e);
-    } else {
-        println!("--| triggered auction");
+    for (auctioned_block, non_bid_txns) in produced {
+        match bundle_verifier
+            .verify(auctioned_block, Some(non_bid_txns))
+            .await
+        {
+            Ok(report) => println!("{}", report),
+            Err(e) => println!(
+                "--> !!! failed to verify bundle for block {}: {}",
+                auctioned_block, e
+            ),
+        }
+        monitor
+            .verified_auctions
+            .insert(auctioned_block, non_bid_txns);
     }
+    let kept = monitor
+        .verified_auctions
+        .split_off(&block_number.saturating_sub(KEPT_VERIFIED_BLOCKS));
+    monitor.verified_auctions = kept;
+}
 
-    sleep(Duration::from_secs(5)).await;
-    if let Err(e) = amm_auction_suapp.print_auction_stats().await {
-        println!("!! {} !!", e);
+/// Queues the auctions whose block the reorg replaced to be verified again once the new
+/// chain's block is produced, the suapp won't send another bundle for them.
+fn reverify_bundles(monitor: &mut Monitor, reorg: Reorg) {
+    let orphaned = monitor
+        .verified_auctions
+        .split_off(&(reorg.common_ancestor + 1));
+    for (auctioned_block, non_bid_txns) in orphaned {
+        println!(
+            "--| block {} was reorged out, its bundle is verified again on the new chain",
+            auctioned_block
+        );
+        monitor
+            .unverified_auctions
+            .insert(auctioned_block, non_bid_txns);
     }
 }
 
-async fn process_header(amm_auction_suapp: &mut AuctionSuapp, text: String) {
-    // TODO add better error handling around this
-    let v: Value = serde_json::from_str(&text).unwrap();
-
-    let mut block_number: u128 = 0;
-    if let Value::String(result) = &v["params"]["result"]["number"] {
-        block_number =
-            u128::from_str_radix(&result[2..], 16).expect("hex parsing failed for block number");
-    }
+async fn check_pool_lock(monitor: &mut Monitor, block_number: u64) {
+    let Some(lock_detector) = &monitor.lock_detector else {
+        return;
+    };
+    let status = match lock_detector.check_block(block_number).await {
+        Ok(status) => status,
+        Err(e) => {
+            println!(
+                "--> !!! failed to check pool lock for block {}: {}",
+                block_number, e
+            );
+            return;
+        }
+    };
 
-    let mut timestamp: u128 = 0;
-    if let Value::String(result) = &v["params"]["result"]["timestamp"] {
-        timestamp =
-            u128::from_str_radix(&result[2..], 16).expect("hex parsing failed for timestamp");
+    if let Some(period) = monitor.lock_tracker.push(&status) {
+        println!(
+            "--| pool unlocked after {} blocks ({}s)",
+            period.blocks(),
+            period.duration_secs
+        );
     }
-
-    if timestamp != 0 {
-        // don't run on first message
-        trigger_auction(amm_auction_suapp, block_number + 1).await;
+    if let Some(reason) = status.lock {
+        println!(
+            "--> !!! pool locked in block {} ({:?}), {} of {} swaps reverted, locked {} blocks in \
+             a row, {} total",
+            block_number,
+            reason,
+            status.reverted_swaps.len(),
+            status.swap_attempts,
+            monitor.lock_tracker.current_streak(),
+            monitor.lock_tracker.locked_block_count()
+        );
+        for swap in &status.reverted_swaps {
+            println!("--| reverted {}", swap);
+        }
     }
 }
@@ -1,5 +1,8 @@
 use auction_block_listener::BlockServer;
-use auction_interface::{amm_auction::AuctionSuapp, amm_auction_config::AmmAuctionConfig};
+use auction_interface::{
+    amm_auction::AuctionSuapp, amm_auction_config::AmmAuctionConfig, landing::BundleVerifier,
+    pool::PoolClient, pool_lock::PoolLockDetector,
+};
 use color_eyre::eyre::Context;
 
 #[tokio::main]
@@ -14,9 +17,22 @@ async fn main() {
         .expect("failed to build amm auction suapp wrapper");
 
     // setup block server
-    let block_server = BlockServer::new(config.wss_l1)
+    let mut block_server = BlockServer::new(config.wss_l1.clone(), config.clone())
         .await
-        .expect("failed to create new block server")
+        .expect("failed to create new block server");
+    match PoolClient::from_config(&config).await {
+        Ok(pool_client) => block_server = block_server.with_pool_client(pool_client),
+        Err(e) => println!("not reading pool state: {:#}", e),
+    }
+    match BundleVerifier::from_config(&config) {
+        Ok(bundle_verifier) => block_server = block_server.with_bundle_verifier(bundle_verifier),
+        Err(e) => println!("not verifying bundles: {:#}", e),
+    }
+    match PoolLockDetector::from_config(&config).await {
+        Ok(lock_detector) => block_server = block_server.with_lock_detector(lock_detector),
+        Err(e) => println!("not detecting pool locks: {:#}", e),
+    }
+    let block_server = block_server
         .run_until_stopped(amm_auction_wrapper)
         .await
         .expect("failed to start block server");
@@ -0,0 +1,516 @@
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use alloy_primitives::B256;
+use auction_interface::{
+    accounts::AccountRole,
+    amm_auction::{AuctionSuapp, BidSubmission},
+    amm_auction_config::AmmAuctionConfig,
+    ccr::SuappRevert,
+    pool::PoolClient,
+    swap::Swap,
+};
+use color_eyre::eyre;
+use futures_util::future::join_all;
+use tokio::{
+    sync::{watch, Mutex},
+    task::JoinHandle,
+    time::{sleep, Duration},
+};
+
+use crate::{
+    heads::{NewHead, Reorg},
+    strategy::{BidDecision, BidStrategy, BlockHeader, PoolSnapshot},
+    Monitor,
+};
+
+/// Tick bitmap words read on each side of the current one to size swaps with, swaps are
+/// capped where they end.
+const TICK_WORD_RADIUS: i16 = 1;
+
+/// Wait after the bids are sent for the auction's duration to pass.
+const AUCTION_WAIT: Duration = Duration::from_secs(4);
+/// Wait after `runAuction` before reading the auction's stats.
+const STATS_WAIT: Duration = Duration::from_secs(5);
+
+/// Blocks a bid is remembered for after the block it was sent for is produced.
+const KEPT_BID_BLOCKS: u64 = 64;
+
+/// What the round tasks share.
+pub(crate) struct RoundContext {
+    pub(crate) suapp: AuctionSuapp,
+    pub(crate) pool_client: Option<PoolClient>,
+    pub(crate) bid_strategy: Box<dyn BidStrategy>,
+    pub(crate) config: AmmAuctionConfig,
+    pub(crate) monitor: Arc<Mutex<Monitor>>,
+    /// Held while `runAuction` is in flight, so only one is sent at a time
+    pub(crate) auction_lock: Mutex<()>,
+    /// Bids sent by the rounds, by the block they were sent for. The suapp keeps a bid for its
+    /// block whatever head it was planned on, so they outlive reorgs
+    pub(crate) sent_bids: Mutex<BTreeMap<u64, Vec<SentBid>>>,
+}
+
+/// A bid a round sent, and the head it was planned on.
+#[derive(Debug, Clone)]
+pub(crate) struct SentBid {
+    pub(crate) bidder: String,
+    pub(crate) amount: u128,
+    /// If the bid's swap sells token 0 for token 1
+    pub(crate) token_0_in: bool,
+    /// The bid's CCR, its callback isn't waited for
+    pub(crate) ccr_hash: B256,
+    /// Hash of the head the round was started on
+    pub(crate) head_hash: B256,
+    /// Set once a reorg orphans `head_hash`, the bid was sized on a chain that's gone
+    pub(crate) orphaned: bool,
+}
+
+/// Why a round was stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cancel {
+    /// The block it auctions was produced
+    Produced(u64),
+    /// The head it was started on was orphaned by a reorg back to this block
+    Reorged(u64),
+}
+
+impl fmt::Display for Cancel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cancel::Produced(block_number) => write!(f, "block {} was produced", block_number),
+            Cancel::Reorged(common_ancestor) => {
+                write!(f, "its head was reorged out past block {}", common_ancestor)
+            }
+        }
+    }
+}
+
+/// Reorgs seen since the listener started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ReorgMetrics {
+    reorgs: u64,
+    deepest: u64,
+    orphaned_blocks: u64,
+    cancelled_rounds: u64,
+    /// Bids sent on orphaned heads, still live in the suapp
+    orphaned_bids: u64,
+}
+
+impl fmt::Display for ReorgMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "reorgs: {}, deepest: {}, orphaned blocks: {}, cancelled rounds: {}, orphaned bids: {}",
+            self.reorgs,
+            self.deepest,
+            self.orphaned_blocks,
+            self.cancelled_rounds,
+            self.orphaned_bids
+        )
+    }
+}
+
+/// Runs an auction round per block, each in its own task so a round's bids go out while
+/// earlier rounds are still collecting their stats.
+///
+/// A round for block `n` is cancelled once block `n` is produced, or once a reorg orphans
+/// the head it was started on. It stops at its next step (before bidding, while waiting, or
+/// before `runAuction`), a `runAuction` already in flight is seen through. Rounds for
+/// reorged blocks are planned again on the new chain's head. Bids sent on orphaned heads are
+/// marked orphaned, but the suapp can't withdraw them: a re-planned round doesn't bid again
+/// for their bidders, it checks them against what the strategy would bid on the new chain.
+pub(crate) struct RoundScheduler {
+    context: Arc<RoundContext>,
+    /// Round tasks not yet known to be finished, by the block they auction
+    rounds: BTreeMap<u64, RoundHandle>,
+    /// Highest block a round was started for
+    highest_planned: u64,
+    metrics: ReorgMetrics,
+}
+
+struct RoundHandle {
+    task: JoinHandle<()>,
+    cancel: watch::Sender<Option<Cancel>>,
+}
+
+impl RoundScheduler {
+    pub(crate) fn new(context: Arc<RoundContext>) -> Self {
+        Self {
+            context,
+            rounds: BTreeMap::new(),
+            highest_planned: 0,
+            metrics: ReorgMetrics::default(),
+        }
+    }
+
+    /// Starts the round for the block after `head`, cancelling rounds for `head`'s block and
+    /// earlier.
+    pub(crate) async fn on_head(&mut self, head: NewHead) {
+        let header = head.header;
+        self.rounds.retain(|_, round| !round.task.is_finished());
+        let pending = self.rounds.split_off(&(header.number + 1));
+        for (_, round) in std::mem::replace(&mut self.rounds, pending) {
+            round
+                .cancel
+                .send_replace(Some(Cancel::Produced(header.number)));
+        }
+
+        // bids for long produced blocks can't be orphaned anymore
+        let mut sent_bids = self.context.sent_bids.lock().await;
+        let kept = sent_bids.split_off(&header.number.saturating_sub(KEPT_BID_BLOCKS));
+        *sent_bids = kept;
+        drop(sent_bids);
+
+        let block_number = header.number + 1;
+        if self.rounds.contains_key(&block_number) {
+            return;
+        }
+        let replanned = block_number <= self.highest_planned;
+        self.highest_planned = self.highest_planned.max(block_number);
+        let (cancel, cancelled) = watch::channel(None);
+        let round = Round {
+            context: self.context.clone(),
+            header,
+            head_hash: head.hash,
+            block_number,
+            replanned,
+            cancelled,
+        };
+        self.rounds.insert(
+            block_number,
+            RoundHandle {
+                task: tokio::spawn(round.run()),
+                cancel,
+            },
+        );
+    }
+
+    /// Cancels the rounds started on orphaned heads and marks their bids orphaned, the new
+    /// chain's heads that follow plan them again.
+    pub(crate) async fn on_reorg(&mut self, reorg: Reorg) {
+        // a round for block `n` was started on head `n - 1`
+        let first_orphaned_round = reorg.common_ancestor + 2;
+
+        let orphaned_rounds = self.rounds.split_off(&first_orphaned_round);
+        let mut cancelled_rounds = 0;
+        for (_, round) in orphaned_rounds {
+            if !round.task.is_finished() {
+                cancelled_rounds += 1;
+            }
+            round
+                .cancel
+                .send_replace(Some(Cancel::Reorged(reorg.common_ancestor)));
+        }
+
+        // the suapp has no way to withdraw a bid, it still takes part in its block's auction
+        let mut sent_bids = self.context.sent_bids.lock().await;
+        let mut orphaned_bids = 0;
+        for (block_number, bids) in sent_bids.range_mut(first_orphaned_round..) {
+            for bid in bids.iter_mut().filter(|bid| !bid.orphaned) {
+                println!(
+                    "--> !!! bid of {} for {} on block {} was planned on orphaned head {}, it stays live in the suapp (ccr {})",
+                    bid.bidder, bid.amount, block_number, bid.head_hash, bid.ccr_hash
+                );
+                bid.orphaned = true;
+                orphaned_bids += 1;
+            }
+        }
+        drop(sent_bids);
+
+        self.metrics.reorgs += 1;
+        self.metrics.deepest = self.metrics.deepest.max(reorg.depth);
+        self.metrics.orphaned_blocks += reorg.depth;
+        self.metrics.cancelled_rounds += cancelled_rounds;
+        self.metrics.orphaned_bids += orphaned_bids;
+        println!(
+            "--| reorg of depth {} back to block {}: {} rounds cancelled, {} bids orphaned",
+            reorg.depth, reorg.common_ancestor, cancelled_rounds, orphaned_bids
+        );
+        println!("--| {}", self.metrics);
+    }
+}
+
+/// The auction round for a single block.
+struct Round {
+    context: Arc<RoundContext>,
+    /// Head the round was started on
+    header: BlockHeader,
+    head_hash: B256,
+    /// Block being auctioned, the one after `header`
+    block_number: u64,
+    /// An earlier round for the same block was cancelled by a reorg
+    replanned: bool,
+    cancelled: watch::Receiver<Option<Cancel>>,
+}
+
+impl Round {
+    fn cancel_reason(&self) -> Option<Cancel> {
+        *self.cancelled.borrow()
+    }
+
+    /// Sleeps for `duration`, returning early with `false` if the round is cancelled.
+    async fn wait(&mut self, duration: Duration) -> bool {
+        let deadline = sleep(duration);
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                _ = &mut deadline => return self.cancel_reason().is_none(),
+                changed = self.cancelled.changed() => {
+                    // a dropped sender means the scheduler is gone
+                    if changed.is_err() || self.cancel_reason().is_some() {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    fn cancelled(&self, step: &str) {
+        match self.cancel_reason() {
+            Some(reason) => println!(
+                "--| round for block {} cancelled {}, {}",
+                self.block_number, step, reason
+            ),
+            None => println!(
+                "--| round for block {} cancelled {}, the listener stopped",
+                self.block_number, step
+            ),
+        }
+    }
+
+    async fn run(mut self) {
+        println!(
+            "[~~~~  running auction for block: {} ~~~~]",
+            self.block_number
+        );
+
+        let pool = self.read_pool().await;
+        if self.cancel_reason().is_some() {
+            self.cancelled("before bidding");
+            return;
+        }
+
+        // send bids
+        let bidders = self
+            .context
+            .suapp
+            .accounts()
+            .names_with_role(AccountRole::Bidder);
+        join_all(
+            bidders
+                .iter()
+                .map(|bidder| self.send_bid(bidder, pool.as_ref())),
+        )
+        .await;
+
+        // wait for the auction time to pass
+        if !self.wait(AUCTION_WAIT).await {
+            self.cancelled("while waiting for the auction");
+            return;
+        }
+
+        {
+            let _auction = self.context.auction_lock.lock().await;
+            if self.cancel_reason().is_some() {
+                self.cancelled("before running the auction");
+                return;
+            }
+            if self.replanned && self.auction_already_ran().await {
+                return;
+            }
+            // the callback is waited for under the lock, the next round's auction can only run
+            // once this one's is recorded on Suave
+            let triggered = match self.context.suapp.trigger_auction().await {
+                Ok(tx_hash) => self.context.suapp.wait_for_ccr(tx_hash).await,
+                Err(e) => Err(e),
+            };
+            match triggered {
+                Ok(outcome) => println!(
+                    "--| triggered auction for block {}: {}",
+                    self.block_number, outcome
+                ),
+                Err(e) => match e.downcast_ref::<SuappRevert>() {
+                    Some(revert) => println!(
+                        "--> !!! auction for block {} not run: {}",
+                        self.block_number, revert
+                    ),
+                    None => println!(
+                        "--> !!! failed to trigger auction for block {}: {}",
+                        self.block_number, e
+                    ),
+                },
+            }
+        }
+
+        // the stats are read while the next round bids
+        sleep(STATS_WAIT).await;
+        match self.context.suapp.get_auction_stats().await {
+            Ok(stats) => {
+                println!("{}", stats);
+                if stats.last_auction_processed_l1_block == self.block_number {
+                    self.context
+                        .monitor
+                        .lock()
+                        .await
+                        .unverified_auctions
+                        .insert(stats.last_auction_processed_l1_block, stats.included_txns);
+                }
+            }
+            Err(e) => println!("!! {} !!", e),
+        }
+    }
+
+    /// Whether the auction for the round's block ran before a reorg replaced its head, the
+    /// suapp only runs one per block.
+    async fn auction_already_ran(&self) -> bool {
+        match self.context.suapp.get_auction_stats().await {
+            Ok(stats) if stats.last_auction_processed_l1_block >= self.block_number => {
+                println!(
+                    "--| auction for block {} already ran before the reorg, not running it again",
+                    self.block_number
+                );
+                true
+            }
+            Ok(_) => false,
+            Err(e) => {
+                println!("--> !!! failed to read auction stats: {}", e);
+                false
+            }
+        }
+    }
+
+    async fn read_pool(&self) -> Option<PoolSnapshot> {
+        let pool_client = self.context.pool_client.as_ref()?;
+        match read_pool(pool_client).await {
+            Ok(snapshot) => {
+                let state = &snapshot.state;
+                println!(
+                    "--| pool price: {} {} per {} (tick {})",
+                    state.price_0_in_1, state.token_1.symbol, state.token_0.symbol, state.tick
+                );
+                Some(snapshot)
+            }
+            Err(e) => {
+                println!("--> !!! failed to read pool state: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn send_bid(&self, bidder: &String, pool: Option<&PoolSnapshot>) {
+        let live_bid = self.live_bid(bidder).await;
+        if let Some(bid) = live_bid.as_ref().filter(|bid| !bid.orphaned) {
+            println!(
+                "--| {} already bid {} for block {} on head {} (ccr {})",
+                bidder, bid.amount, self.block_number, bid.head_hash, bid.ccr_hash
+            );
+            return;
+        }
+        let decision =
+            self.context
+                .bid_strategy
+                .bid(bidder, &self.header, pool, &self.context.config);
+        if let Some(bid) = live_bid {
+            self.check_orphaned_bid(&bid, decision.as_ref());
+            return;
+        }
+        let Some(decision) = decision else {
+            println!("--| {} not bidding", bidder);
+            return;
+        };
+        let bid_amount = decision.amount;
+        let (token_in, token_out) = self.context.suapp.pool_tokens(decision.token_0_in);
+        let swap = Swap::exact_input_single(
+            token_in,
+            token_out,
+            decision.swap_amount,
+            decision.min_amount_out,
+        )
+        .with_sqrt_price_limit_x96(decision.sqrt_price_limit_x96);
+        match self
+            .context
+            .suapp
+            .new_bid(
+                bidder,
+                u128::from(self.block_number),
+                bid_amount,
+                swap,
+                false,
+            )
+            .await
+        {
+            Ok(BidSubmission { ccr_hash, .. }) => {
+                println!(
+                    "--> sent bid for {} for: {} (ccr {})",
+                    bidder, bid_amount, ccr_hash
+                );
+                self.context
+                    .sent_bids
+                    .lock()
+                    .await
+                    .entry(self.block_number)
+                    .or_default()
+                    .push(SentBid {
+                        bidder: bidder.clone(),
+                        amount: bid_amount,
+                        token_0_in: decision.token_0_in,
+                        ccr_hash,
+                        head_hash: self.head_hash,
+                        orphaned: false,
+                    });
+            }
+            Err(e) => println!("--> !!! failed to send bid for {}: {}", bidder, e),
+        }
+    }
+
+    /// Reports an orphaned bid the suapp still holds that the strategy wouldn't make on the
+    /// new chain, given what it bids now. Bidding again would put a second bid in the suapp.
+    fn check_orphaned_bid(&self, bid: &SentBid, decision: Option<&BidDecision>) {
+        let problem = match decision {
+            None => "the strategy doesn't bid on the new chain".to_string(),
+            Some(decision) if decision.token_0_in != bid.token_0_in => {
+                "the strategy swaps the other way on the new chain".to_string()
+            }
+            Some(decision) if decision.amount < bid.amount => format!(
+                "the strategy only bids {} on the new chain",
+                decision.amount
+            ),
+            Some(decision) => {
+                println!(
+                    "--| {}'s orphaned bid of {} for block {} still holds on the new chain, \
+                     the strategy bids {} (ccr {})",
+                    bid.bidder, bid.amount, self.block_number, decision.amount, bid.ccr_hash
+                );
+                return;
+            }
+        };
+        println!(
+            "--> !!! {}'s bid of {} for block {} was sized on orphaned head {} and is no longer \
+             profitable, {}, it stays live in the suapp (ccr {})",
+            bid.bidder, bid.amount, self.block_number, bid.head_hash, problem, bid.ccr_hash
+        );
+    }
+
+    /// `bidder`'s bid for the round's block, on any head, a bid planned on an orphaned head
+    /// is still live in the suapp.
+    async fn live_bid(&self, bidder: &String) -> Option<SentBid> {
+        self.context
+            .sent_bids
+            .lock()
+            .await
+            .get(&self.block_number)?
+            .iter()
+            .find(|bid| &bid.bidder == bidder)
+            .cloned()
+    }
+}
+
+async fn read_pool(pool_client: &PoolClient) -> eyre::Result<PoolSnapshot> {
+    // the price and the ticks around it have to come from the same block
+    let block_number = pool_client.latest_block().await?;
+    Ok(PoolSnapshot {
+        state: pool_client.state_at(block_number).await?,
+        ticks: pool_client
+            .tick_liquidity_at(TICK_WORD_RADIUS, block_number)
+            .await?,
+    })
+}
@@ -0,0 +1,214 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::U256;
+use auction_interface::{
+    amm_auction_config::{AmmAuctionConfig, BidStrategyKind},
+    arbitrage::optimal_arbitrage,
+    pool::PoolState,
+    price_feed::{price_feed_from_config, PriceFeed},
+    v3_math::TickLiquidity,
+};
+use color_eyre::eyre::{self, Context};
+use eyre::ContextCompat;
+
+/// Default share of the arbitrage value the `arbitrage` strategy bids.
+const DEFAULT_BID_FRACTION: f64 = 0.5;
+
+/// Default bid token units per token 1 unit the `arbitrage` strategy values profit at.
+const DEFAULT_BID_PER_TOKEN_1: f64 = 1.0;
+
+/// Fields of a new L1 header the strategies look at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub number: u64,
+    pub timestamp: u64,
+}
+
+/// What a bidder bids for the next block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BidDecision {
+    /// Bid in wei
+    pub amount: u128,
+    /// If the swap sells token 0 for token 1
+    pub token_0_in: bool,
+    /// Amount of the input token swapped
+    pub swap_amount: U256,
+    pub min_amount_out: U256,
+    /// Price limit of the swap, 0 for no limit
+    pub sqrt_price_limit_x96: U256,
+}
+
+/// The pool as read for a block.
+#[derive(Debug, Clone)]
+pub struct PoolSnapshot {
+    pub state: PoolState,
+    /// Liquidity around the current price, for sizing swaps
+    pub ticks: TickLiquidity,
+}
+
+/// Decides, per bidder and block, whether and what to bid.
+pub trait BidStrategy: Send + Sync {
+    /// Returns `None` to skip bidding for `bidder` on the block after `header`.
+    ///
+    /// `pool` is `None` if the listener isn't reading pool state or the read failed.
+    fn bid(
+        &self,
+        bidder: &str,
+        header: &BlockHeader,
+        pool: Option<&PoolSnapshot>,
+        config: &AmmAuctionConfig,
+    ) -> Option<BidDecision>;
+}
+
+/// Builds the strategy selected by the config's `BID_STRATEGY`, and for `arbitrage` the
+/// `PRICE_FEED` it prices against.
+pub fn strategy_from_config(config: &AmmAuctionConfig) -> eyre::Result<Box<dyn BidStrategy>> {
+    Ok(match config.bid_strategy {
+        BidStrategyKind::Random => Box::new(RandomBidStrategy),
+        BidStrategyKind::Fixed => Box::new(FixedBidStrategy {
+            amount: config
+                .bid_strategy_amount
+                .context("BID_STRATEGY_AMOUNT not set for fixed strategy")?
+                .into(),
+            token_0_in: config.bid_strategy_token_0_in.unwrap_or(true),
+            swap_amount: U256::from(
+                config
+                    .bid_strategy_swap_amount
+                    .context("BID_STRATEGY_SWAP_AMOUNT not set for fixed strategy")?,
+            ),
+        }),
+        BidStrategyKind::Arbitrage => {
+            let bid_fraction = config
+                .bid_strategy_bid_fraction
+                .unwrap_or(DEFAULT_BID_FRACTION);
+            eyre::ensure!(
+                (0.0..=1.0).contains(&bid_fraction),
+                "BID_STRATEGY_BID_FRACTION must be between 0 and 1"
+            );
+            let price_feed =
+                price_feed_from_config(config).context("failed to build price feed")?;
+            println!("arbitrage reference price from {}", price_feed.source());
+            Box::new(ArbitrageValueStrategy {
+                price_feed,
+                bid_per_token_1: config
+                    .bid_strategy_bid_per_token_1
+                    .unwrap_or(DEFAULT_BID_PER_TOKEN_1),
+                bid_fraction,
+            })
+        }
+    })
+}
+
+/// Bids 1-100 wei with a swap of 10 token 0 in, every bidder every block.
+pub struct RandomBidStrategy;
+
+impl RandomBidStrategy {
+    fn random_amount() -> u128 {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_micros();
+
+        (seed % 100) + 1
+    }
+}
+
+impl BidStrategy for RandomBidStrategy {
+    fn bid(
+        &self,
+        _bidder: &str,
+        _header: &BlockHeader,
+        _pool: Option<&PoolSnapshot>,
+        _config: &AmmAuctionConfig,
+    ) -> Option<BidDecision> {
+        Some(BidDecision {
+            amount: Self::random_amount(),
+            token_0_in: true,
+            swap_amount: U256::from(10),
+            min_amount_out: U256::from(1),
+            sqrt_price_limit_x96: U256::ZERO,
+        })
+    }
+}
+
+/// Bids the same amount with the same swap, every bidder every block.
+pub struct FixedBidStrategy {
+    pub amount: u128,
+    pub token_0_in: bool,
+    pub swap_amount: U256,
+}
+
+impl BidStrategy for FixedBidStrategy {
+    fn bid(
+        &self,
+        _bidder: &str,
+        _header: &BlockHeader,
+        _pool: Option<&PoolSnapshot>,
+        _config: &AmmAuctionConfig,
+    ) -> Option<BidDecision> {
+        Some(BidDecision {
+            amount: self.amount,
+            token_0_in: self.token_0_in,
+            swap_amount: self.swap_amount,
+            min_amount_out: U256::from(1),
+            sqrt_price_limit_x96: U256::ZERO,
+        })
+    }
+}
+
+/// Trades the pool to a price feed's reference price and bids a share of the profit.
+///
+/// The swap is sized with the pool's swap math to stop where the marginal unit breaks even,
+/// crossing initialized ticks on the way, and its profit is the truthful second price bid.
+/// No bid is made without pool state or a fresh reference price, or without an opportunity.
+pub struct ArbitrageValueStrategy {
+    pub price_feed: Arc<dyn PriceFeed>,
+    /// Bid token units per token 1 unit of profit
+    pub bid_per_token_1: f64,
+    /// Share of the profit bid
+    pub bid_fraction: f64,
+}
+
+impl BidStrategy for ArbitrageValueStrategy {
+    fn bid(
+        &self,
+        _bidder: &str,
+        _header: &BlockHeader,
+        pool: Option<&PoolSnapshot>,
+        _config: &AmmAuctionConfig,
+    ) -> Option<BidDecision> {
+        let pool = pool?;
+        let reference = self.price_feed.reading()?;
+        if reference.stale {
+            println!("--| reference price {} is stale, not bidding", reference);
+            return None;
+        }
+        let opportunity = match optimal_arbitrage(
+            &pool.ticks,
+            reference.quote.price,
+            pool.state.token_0.decimals,
+            pool.state.token_1.decimals,
+        ) {
+            Ok(opportunity) => opportunity?,
+            Err(e) => {
+                println!("--> !!! failed to size arbitrage: {}", e);
+                return None;
+            }
+        };
+        let max_bid = opportunity.max_profitable_bid(self.bid_per_token_1);
+        let amount = (max_bid as f64 * self.bid_fraction) as u128;
+        if amount == 0 {
+            return None;
+        }
+        Some(BidDecision {
+            amount,
+            token_0_in: opportunity.token_0_in,
+            swap_amount: opportunity.amount_in,
+            min_amount_out: opportunity.amount_out,
+            sqrt_price_limit_x96: opportunity.sqrt_price_limit_x96,
+        })
+    }
+}
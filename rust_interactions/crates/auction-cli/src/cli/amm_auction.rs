@@ -1,3 +1,4 @@
+use alloy_primitives::{Address, U256};
 use clap::{Args, Subcommand};
 
 #[derive(Debug, Subcommand)]
@@ -8,6 +9,13 @@ pub enum Command {
     InitializeSuapp(InitializeSuappArgs),
     AddSwaps(AddSwapsArgs),
     AuctionStats(AuctionStatsArgs),
+    VerifyBundle(VerifyBundleArgs),
+    PoolState(PoolStateArgs),
+    Arbitrage(ArbitrageArgs),
+    Events(EventsArgs),
+    Deposit(DepositArgs),
+    DepositBalance(DepositBalanceArgs),
+    Withdraw(WithdrawArgs),
 }
 
 #[derive(Args, Debug)]
@@ -18,26 +26,52 @@ pub struct AuctionArgs {
 
 #[derive(Args, Debug)]
 pub struct BidArgs {
-    #[clap(long, default_value = "10")]
+    /// L1 block the bid is for, must not be produced yet
+    #[clap(long)]
     pub(crate) target_block: u128,
     #[clap(long)]
     pub(crate) bidder: String,
     #[clap(long, default_value = "10")]
     pub(crate) bid_amount: u128,
-    #[clap(long, default_value = "10")]
-    pub(crate) swap_amount: u128,
-    #[clap(long, default_value = "true")]
-    pub(crate) token_0_in: bool,
+    #[clap(flatten)]
+    pub(crate) swap: SwapRouteArgs,
+    /// Send the bid even if it fails validation
+    #[clap(long)]
+    pub(crate) force: bool,
 }
 
 #[derive(Args, Debug)]
 pub struct SwapArgs {
     #[clap(long)]
     pub(crate) swapper: String,
+    #[clap(flatten)]
+    pub(crate) swap: SwapRouteArgs,
+}
+
+/// Shape of the swap sent with a bid or as a non-bid swap
+#[derive(Args, Debug)]
+pub struct SwapRouteArgs {
+    /// Amount going in, or coming out with `--exact-output`
     #[clap(long, default_value = "10")]
     pub(crate) swap_amount: u128,
+    /// Swap the pool's token 0 for token 1, ignored if `--path` is given
     #[clap(long, default_value = "true")]
     pub(crate) token_0_in: bool,
+    /// Fix the output amount instead of the input amount
+    #[clap(long)]
+    pub(crate) exact_output: bool,
+    /// Minimum amount out, or maximum amount in with `--exact-output`
+    #[clap(long)]
+    pub(crate) amount_limit: Option<u128>,
+    /// Comma separated tokens to swap through, starting with the token going in
+    #[clap(long, value_delimiter = ',')]
+    pub(crate) path: Vec<Address>,
+    /// Comma separated fee tier of each hop, defaults to the configured fee tier
+    #[clap(long, value_delimiter = ',')]
+    pub(crate) fees: Vec<u32>,
+    /// Price limit for single pool swaps, 0 for no limit
+    #[clap(long, default_value = "0")]
+    pub(crate) sqrt_price_limit_x96: U256,
 }
 
 #[derive(Args, Debug)]
@@ -47,4 +81,84 @@ pub struct InitializeSuappArgs {}
 pub struct AddSwapsArgs {}
 
 #[derive(Args, Debug)]
-pub struct AuctionStatsArgs {}
+pub struct AuctionStatsArgs {
+    /// Print the stats as JSON
+    #[clap(long)]
+    pub(crate) json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyBundleArgs {
+    /// Auctioned L1 block to check, defaults to the suapp's last auctioned block
+    #[clap(long)]
+    pub(crate) block: Option<u64>,
+    /// Non-bid swaps expected in the bundle, defaults to the suapp's stats for its last block
+    #[clap(long)]
+    pub(crate) non_bid_txns: Option<u64>,
+    /// Print the report as JSON
+    #[clap(long)]
+    pub(crate) json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PoolStateArgs {
+    /// Print the state as JSON
+    #[clap(long)]
+    pub(crate) json: bool,
+    /// Also print the tick bitmap words within this many words of the current tick
+    #[clap(long)]
+    pub(crate) bitmap_radius: Option<i16>,
+}
+
+#[derive(Args, Debug)]
+pub struct ArbitrageArgs {
+    /// Token 1 per token 0 to arbitrage the pool against, adjusted for decimals
+    #[clap(long)]
+    pub(crate) reference_price: f64,
+    /// Tick bitmap words on each side of the current one the swap may cross into
+    #[clap(long, default_value = "2")]
+    pub(crate) bitmap_radius: i16,
+    /// Bid token units per token 1 unit of profit, for the max bid
+    #[clap(long, default_value = "1")]
+    pub(crate) bid_per_token_1: f64,
+}
+
+#[derive(Args, Debug)]
+pub struct EventsArgs {
+    /// Poll L1 logs over http instead of subscribing over the websocket
+    #[clap(long)]
+    pub(crate) poll: bool,
+    /// L1 block to start reading events from, implies `--poll`
+    #[clap(long)]
+    pub(crate) from_block_l1: Option<u64>,
+    /// Suave block to start reading events from, defaults to the latest block
+    #[clap(long)]
+    pub(crate) from_block_suave: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+pub struct DepositArgs {
+    #[clap(long)]
+    pub(crate) bidder: String,
+    /// Amount to deposit in wei
+    #[clap(long)]
+    pub(crate) amount: U256,
+}
+
+#[derive(Args, Debug)]
+pub struct DepositBalanceArgs {
+    #[clap(long)]
+    pub(crate) bidder: String,
+}
+
+#[derive(Args, Debug)]
+pub struct WithdrawArgs {
+    #[clap(long)]
+    pub(crate) bidder: String,
+    /// Address to send the funds to, defaults to the bidder
+    #[clap(long)]
+    pub(crate) to: Option<Address>,
+    /// Amount to withdraw in wei
+    #[clap(long)]
+    pub(crate) amount: U256,
+}
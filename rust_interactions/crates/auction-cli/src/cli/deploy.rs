@@ -0,0 +1,30 @@
+use alloy_primitives::U256;
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct DeployArgs {
+    /// Name of the admin account that deploys the L1 contracts, defaults to the first one
+    #[clap(long)]
+    pub(crate) admin: Option<String>,
+    /// Env file the deployed addresses are written to
+    #[clap(long, default_value = "../solidity_code/.env")]
+    pub(crate) env_file: String,
+    /// Forge's build output directory
+    #[clap(long, default_value = "../solidity_code/out")]
+    pub(crate) out_dir: String,
+    /// File the deployment's progress is saved to, a failed deployment resumes from it
+    #[clap(long, default_value = "../solidity_code/deployment.json")]
+    pub(crate) state_file: String,
+    /// Discard any saved progress and deploy everything again
+    #[clap(long)]
+    pub(crate) fresh: bool,
+    /// Fee tier of the auctioned pool, defaults to SWAP_FEE_TIER or 3000
+    #[clap(long)]
+    pub(crate) pool_fee: Option<u32>,
+    /// Add liquidity to the pool and give every bidder and swapper tokens to swap with
+    #[clap(long)]
+    pub(crate) init_pool_state: bool,
+    /// Amount in wei each bidder deposits into the deposits contract
+    #[clap(long)]
+    pub(crate) bid_deposit: Option<U256>,
+}
@@ -0,0 +1,55 @@
+use alloy_primitives::Address;
+use clap::{ArgAction, Args, Subcommand};
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    Status(StatusArgs),
+    SetAdmin(SetAddressArgs),
+    SetSuappKey(SetAddressArgs),
+    SetFeeAddress(SetAddressArgs),
+    EnableAuction(EnableAuctionArgs),
+    PoolLocks(PoolLocksArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct StatusArgs {
+    /// Print the state as JSON
+    #[clap(long)]
+    pub(crate) json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SetAddressArgs {
+    /// Name of the admin account to send from, defaults to the first one
+    #[clap(long)]
+    pub(crate) admin: Option<String>,
+    #[clap(long)]
+    pub(crate) address: Address,
+}
+
+#[derive(Args, Debug)]
+pub struct EnableAuctionArgs {
+    /// Name of the admin account to send from, defaults to the first one
+    #[clap(long)]
+    pub(crate) admin: Option<String>,
+    /// Whether swaps through the pool should be guarded by the auction, `--enabled false`
+    /// turns it off
+    #[clap(long, action = ArgAction::Set, default_value = "true")]
+    pub(crate) enabled: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PoolLocksArgs {
+    /// First block to check, defaults to `--blocks` before the last one
+    #[clap(long)]
+    pub(crate) from: Option<u64>,
+    /// Last block to check, defaults to the latest block
+    #[clap(long)]
+    pub(crate) to: Option<u64>,
+    /// Number of blocks to check when `--from` isn't given
+    #[clap(long, default_value = "50")]
+    pub(crate) blocks: u64,
+    /// Print the report as JSON
+    #[clap(long)]
+    pub(crate) json: bool,
+}
@@ -1,8 +1,12 @@
 pub(crate) mod amm_auction;
+pub(crate) mod deploy;
+pub(crate) mod guard;
 use clap::{Parser, Subcommand};
 use color_eyre::eyre;
 
-use crate::cli::amm_auction::Command as AmmAuctionCommand;
+use crate::cli::{
+    amm_auction::Command as AmmAuctionCommand, deploy::DeployArgs, guard::Command as GuardCommand,
+};
 
 /// A CLI for interacting with AuctionSuapp Proof of Concept
 #[derive(Debug, Parser)]
@@ -31,4 +35,11 @@ pub enum Command {
         #[clap(subcommand)]
         command: AmmAuctionCommand,
     },
+    /// Read and manage the L1 AuctionGuard
+    Guard {
+        #[clap(subcommand)]
+        command: GuardCommand,
+    },
+    /// Deploy and wire together the L1 and Suave contracts from forge's build output
+    Deploy(DeployArgs),
 }
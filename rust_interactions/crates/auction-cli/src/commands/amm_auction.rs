@@ -1,41 +1,93 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use auction_interface::amm_auction::AuctionSuapp;
+use alloy_primitives::{B256, U256};
+use auction_interface::{
+    accounts::AccountRole,
+    amm_auction::AuctionSuapp,
+    amm_auction_config::AmmAuctionConfig,
+    arbitrage::optimal_arbitrage,
+    deposits::DepositsClient,
+    events::{auction_event_stream, AuctionContracts, LogSource},
+    l1_client::L1Client,
+    landing::BundleVerifier,
+    pool::PoolClient,
+    swap::Swap,
+};
 use color_eyre::{eyre, eyre::Context};
+use futures_util::StreamExt;
 
 use crate::cli::amm_auction::{
-    AddSwapsArgs, AuctionArgs, AuctionStatsArgs, BidArgs, InitializeSuappArgs, SwapArgs,
+    AddSwapsArgs, ArbitrageArgs, AuctionArgs, AuctionStatsArgs, BidArgs, DepositArgs,
+    DepositBalanceArgs, EventsArgs, InitializeSuappArgs, PoolStateArgs, SwapArgs, SwapRouteArgs,
+    VerifyBundleArgs, WithdrawArgs,
 };
 
+/// Builds the swap described by the command line arguments.
+fn swap_from_args(args: &SwapRouteArgs, amm_auction: &AuctionSuapp) -> Swap {
+    let path = if args.path.is_empty() {
+        let (token_in, token_out) = amm_auction.pool_tokens(args.token_0_in);
+        vec![token_in, token_out]
+    } else {
+        args.path.clone()
+    };
+    let amount = U256::from(args.swap_amount);
+
+    let swap = if args.exact_output {
+        let amount_in_maximum = args.amount_limit.map(U256::from).unwrap_or(U256::MAX);
+        Swap::exact_output(path, amount, amount_in_maximum)
+    } else {
+        let amount_out_minimum = U256::from(args.amount_limit.unwrap_or(1));
+        Swap::exact_input(path, amount, amount_out_minimum)
+    };
+    swap.with_fees(args.fees.clone())
+        .with_sqrt_price_limit_x96(args.sqrt_price_limit_x96)
+}
+
+/// Waits for a sent CCR's callback and prints its outcome.
+async fn wait_for_ccr(amm_auction: &AuctionSuapp, tx_hash: B256) -> eyre::Result<()> {
+    let outcome = amm_auction
+        .wait_for_ccr(tx_hash)
+        .await
+        .wrap_err("failed waiting for ccr callback")?;
+    println!("{}", outcome);
+    Ok(())
+}
+
 pub(crate) async fn trigger_auction(
     _args: &AuctionArgs,
     amm_auction: &mut AuctionSuapp,
 ) -> eyre::Result<()> {
     println!("auction cli logic");
-    amm_auction
+    let tx_hash = amm_auction
         .trigger_auction()
         .await
         .wrap_err("failed to send bid ccr")?;
+    wait_for_ccr(amm_auction, tx_hash).await?;
     println!("fin");
-    amm_auction
-        .print_auction_stats()
+    let stats = amm_auction
+        .get_auction_stats()
         .await
-        .wrap_err("failed to print auction stats")?;
+        .wrap_err("failed to get auction stats")?;
+    println!("{}", stats);
     Ok(())
 }
 
 pub(crate) async fn send_bid(args: &BidArgs, amm_auction: &mut AuctionSuapp) -> eyre::Result<()> {
     println!("new bid logic");
-    amm_auction
+    let submission = amm_auction
         .new_bid(
             &args.bidder,
-            10000000000, // TODO figure out what a good automation is
+            args.target_block,
             args.bid_amount,
-            args.swap_amount,
-            args.token_0_in,
+            swap_from_args(&args.swap, amm_auction),
+            args.force,
         )
         .await
         .wrap_err("failed to send bid ccr")?;
+    if !submission.report.is_empty() {
+        println!("--> !!! forced through: {}", submission.report);
+    }
+    wait_for_ccr(amm_auction, submission.ccr_hash).await?;
     println!("fin");
     Ok(())
 }
@@ -45,10 +97,11 @@ pub(crate) async fn send_swap_tx(
     amm_auction: &mut AuctionSuapp,
 ) -> eyre::Result<()> {
     println!("new pending transaction logic");
-    amm_auction
-        .new_pending_txn(&args.swapper, args.swap_amount, args.token_0_in)
+    let tx_hash = amm_auction
+        .new_pending_txn(&args.swapper, swap_from_args(&args.swap, amm_auction))
         .await
         .wrap_err("failed to send swap tx ccr")?;
+    wait_for_ccr(amm_auction, tx_hash).await?;
     println!("fin");
     Ok(())
 }
@@ -60,30 +113,35 @@ pub(crate) async fn initialize_suapp(
     println!("new suapp initialization logic");
     // initialize the suapp's Confidential Store logic for the L1 Block, L1's URL, and signing
     // key
-    amm_auction
+    let tx_hash = amm_auction
         .clear_swaps()
         .await
         .wrap_err("failed to send clear swaps ccr")?;
+    wait_for_ccr(amm_auction, tx_hash).await?;
     println!("cleared pending swaps");
-    amm_auction
+    let tx_hash = amm_auction
         .initialize_l1_block()
         .await
         .wrap_err("failed to send l1 block initialize ccr")?;
+    wait_for_ccr(amm_auction, tx_hash).await?;
     println!("initialized l1 block");
-    amm_auction
+    let tx_hash = amm_auction
         .set_l1_url()
         .await
         .wrap_err("failed to send L1 init ccr")?;
+    wait_for_ccr(amm_auction, tx_hash).await?;
     println!("set l1 url");
-    amm_auction
+    let tx_hash = amm_auction
         .set_bundle_url()
         .await
         .wrap_err("failed to send L1 init ccr")?;
+    wait_for_ccr(amm_auction, tx_hash).await?;
     println!("set bundle url");
-    amm_auction
+    let tx_hash = amm_auction
         .set_signing_key()
         .await
         .wrap_err("failed to send signing key init ccr")?;
+    wait_for_ccr(amm_auction, tx_hash).await?;
     println!("set suave signing key");
     send_swaps(amm_auction)
         .await
@@ -105,58 +163,256 @@ fn get_random_amount() -> u128 {
 async fn send_swaps(amm_auction: &mut AuctionSuapp) -> eyre::Result<()> {
     println!("adding swaps");
 
-    let mut swap_amount = get_random_amount();
-    let mut token_0_in = swap_amount % 2 == 0;
-    if let Err(e) = amm_auction
-        .new_pending_txn(&"swapper_0".to_string(), swap_amount, token_0_in)
+    let swappers = amm_auction.accounts().names_with_role(AccountRole::Swapper);
+    for swapper in swappers {
+        let swap_amount = get_random_amount();
+        let token_0_in = swap_amount % 2 == 0;
+        let (token_in, token_out) = amm_auction.pool_tokens(token_0_in);
+        let swap =
+            Swap::exact_input_single(token_in, token_out, U256::from(swap_amount), U256::from(1));
+        // the swaps' callbacks land on their own, there's no need to wait on each
+        match amm_auction.new_pending_txn(&swapper, swap).await {
+            Ok(tx_hash) => println!(
+                "--> sent swap for {} for: {} (ccr {})",
+                swapper, swap_amount, tx_hash
+            ),
+            Err(e) => println!("--> !!! failed to send swap for {}: {:?}", swapper, e),
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn add_swaps(
+    _args: &AddSwapsArgs,
+    amm_auction: &mut AuctionSuapp,
+) -> eyre::Result<()> {
+    send_swaps(amm_auction)
+        .await
+        .context("failed to send swaps")?;
+
+    Ok(())
+}
+
+pub(crate) async fn auction_stats(
+    args: &AuctionStatsArgs,
+    amm_auction: &mut AuctionSuapp,
+) -> eyre::Result<()> {
+    let stats = amm_auction
+        .get_auction_stats()
         .await
-    {
-        println!("--> !!! failed to send swap for swapper_0: {:?}", e);
+        .wrap_err("failed to get auction stats")?;
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).wrap_err("failed to serialize auction stats")?
+        );
     } else {
-        println!("--> sent swap for swapper_0 for: {}", swap_amount);
+        println!("{}", stats);
     }
-    swap_amount = get_random_amount();
-    token_0_in = swap_amount % 2 == 0;
-    if let Err(e) = amm_auction
-        .new_pending_txn(&"swapper_1".to_string(), swap_amount, token_0_in)
+    Ok(())
+}
+
+pub(crate) async fn verify_bundle(
+    args: &VerifyBundleArgs,
+    amm_auction: &mut AuctionSuapp,
+    config: &AmmAuctionConfig,
+) -> eyre::Result<()> {
+    let stats = amm_auction
+        .get_auction_stats()
+        .await
+        .wrap_err("failed to get auction stats")?;
+    let block = args.block.unwrap_or(stats.last_auction_processed_l1_block);
+    // the stats only describe the last auctioned block's bundle
+    let non_bid_txns = args
+        .non_bid_txns
+        .or((block == stats.last_auction_processed_l1_block).then_some(stats.included_txns));
+
+    let verifier = BundleVerifier::from_config(config).wrap_err("failed to build verifier")?;
+    let report = verifier
+        .verify(block, non_bid_txns)
         .await
-    {
-        println!("--> !!! failed to send swap for swapper_1: {:#?}", e);
+        .wrap_err("failed to verify bundle")?;
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).wrap_err("failed to serialize report")?
+        );
     } else {
-        println!("--> sent swap for swapper_1 for: {}", swap_amount);
+        println!("{}", report);
     }
-    swap_amount = get_random_amount();
-    token_0_in = swap_amount % 2 == 0;
-    if let Err(e) = amm_auction
-        .new_pending_txn(&"swapper_2".to_string(), swap_amount, token_0_in)
+    Ok(())
+}
+
+pub(crate) async fn pool_state(
+    args: &PoolStateArgs,
+    config: &AmmAuctionConfig,
+) -> eyre::Result<()> {
+    let pool = PoolClient::from_config(config)
         .await
-    {
-        println!("--> !!! failed to send swap for swapper_2: {:?}", e);
+        .wrap_err("failed to build pool client")?;
+    let state = pool.state().await.wrap_err("failed to get pool state")?;
+    let words = match args.bitmap_radius {
+        Some(radius) => pool
+            .tick_bitmap_words_around(state.tick, radius, state.block_number)
+            .await
+            .wrap_err("failed to get tick bitmap")?,
+        None => Vec::new(),
+    };
+
+    if args.json {
+        let output = serde_json::json!({ "state": state, "tick_bitmap": words });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).wrap_err("failed to serialize pool state")?
+        );
     } else {
-        println!("--> sent swap for swapper_2 for: {}", swap_amount);
+        println!("{}", state);
+        for word in words {
+            println!(
+                "  bitmap word {:<8} : {:?}",
+                word.word_position, word.initialized_ticks
+            );
+        }
     }
+    Ok(())
+}
+
+pub(crate) async fn arbitrage(args: &ArbitrageArgs, config: &AmmAuctionConfig) -> eyre::Result<()> {
+    let pool = PoolClient::from_config(config)
+        .await
+        .wrap_err("failed to build pool client")?;
+    let liquidity = pool
+        .tick_liquidity(args.bitmap_radius)
+        .await
+        .wrap_err("failed to get pool liquidity")?;
+    let opportunity = optimal_arbitrage(
+        &liquidity,
+        args.reference_price,
+        pool.token_0().decimals,
+        pool.token_1().decimals,
+    )
+    .wrap_err("failed to size arbitrage")?;
 
+    match opportunity {
+        Some(opportunity) => {
+            let (token_in, token_out) = if opportunity.token_0_in {
+                (pool.token_0(), pool.token_1())
+            } else {
+                (pool.token_1(), pool.token_0())
+            };
+            println!("Arbitrage");
+            println!(
+                "  swap                 : {} {} for {} {}",
+                opportunity.amount_in, token_in.symbol, opportunity.amount_out, token_out.symbol
+            );
+            println!("  token 0 in           : {}", opportunity.token_0_in);
+            println!(
+                "  sqrt price limit x96 : {}",
+                opportunity.sqrt_price_limit_x96
+            );
+            println!(
+                "  tick                 : {} -> {}",
+                liquidity.tick, opportunity.tick_after
+            );
+            println!("  ticks crossed        : {:?}", opportunity.ticks_crossed);
+            println!(
+                "  profit               : {} {}",
+                opportunity.profit,
+                pool.token_1().symbol
+            );
+            println!(
+                "  max profitable bid   : {}",
+                opportunity.max_profitable_bid(args.bid_per_token_1)
+            );
+        }
+        None => println!("no profitable arbitrage at the reference price"),
+    }
     Ok(())
 }
 
-pub(crate) async fn add_swaps(
-    _args: &AddSwapsArgs,
+pub(crate) async fn watch_events(args: &EventsArgs, config: &AmmAuctionConfig) -> eyre::Result<()> {
+    let contracts = AuctionContracts::from_config(config).context("missing contract address")?;
+    let mut events = auction_event_stream(
+        LogSource::suave_from_config(config, args.from_block_suave),
+        LogSource::l1_from_config(config, args.poll, args.from_block_l1),
+        contracts,
+    )
+    .await
+    .wrap_err("failed to start event stream")?;
+
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) => println!(
+                "block {:?} tx {:?}: {:?}",
+                event.block_number, event.tx_hash, event.event
+            ),
+            Err(e) => println!("!! {:#} !!", e),
+        }
+    }
+    Ok(())
+}
+
+/// Builds a deposits client sending from the named bidder's wallet.
+fn deposits_client(
+    bidder: &str,
+    config: &AmmAuctionConfig,
+    amm_auction: &AuctionSuapp,
+) -> eyre::Result<DepositsClient> {
+    let wallet = amm_auction
+        .accounts()
+        .wallet(bidder, AccountRole::Bidder)
+        .context("failed to get bidder's wallet")?
+        .clone();
+    let l1 = L1Client::new(&config.rpc_url_l1, wallet)
+        .context("failed to build L1 client")?
+        .with_nonce_manager(amm_auction.nonce_manager());
+    DepositsClient::from_config(config, l1)
+}
+
+pub(crate) async fn deposit(
+    args: &DepositArgs,
+    config: &AmmAuctionConfig,
     amm_auction: &mut AuctionSuapp,
 ) -> eyre::Result<()> {
-    send_swaps(amm_auction)
+    let deposits = deposits_client(&args.bidder, config, amm_auction)?;
+    let receipt = deposits
+        .deposit(args.amount)
         .await
-        .context("failed to send swaps")?;
+        .wrap_err("failed to deposit")?;
+    println!(
+        "deposited {} for {} in {}",
+        args.amount, args.bidder, receipt.tx_hash
+    );
+    Ok(())
+}
 
+pub(crate) async fn deposit_balance(
+    args: &DepositBalanceArgs,
+    config: &AmmAuctionConfig,
+    amm_auction: &mut AuctionSuapp,
+) -> eyre::Result<()> {
+    let deposits = deposits_client(&args.bidder, config, amm_auction)?;
+    let bidder = deposits.address();
+    let balance = deposits
+        .balance_of(bidder)
+        .await
+        .wrap_err("failed to get deposit balance")?;
+    println!("{} ({}) has {} deposited", args.bidder, bidder, balance);
     Ok(())
 }
 
-pub(crate) async fn auction_stats(
-    _args: &AuctionStatsArgs,
+pub(crate) async fn withdraw(
+    args: &WithdrawArgs,
+    config: &AmmAuctionConfig,
     amm_auction: &mut AuctionSuapp,
 ) -> eyre::Result<()> {
-    amm_auction
-        .print_auction_stats()
+    let deposits = deposits_client(&args.bidder, config, amm_auction)?;
+    let to = args.to.unwrap_or(deposits.address());
+    let receipt = deposits
+        .withdraw(to, args.amount)
         .await
-        .wrap_err("failed to print auction stats")?;
+        .wrap_err("failed to withdraw")?;
+    println!("withdrew {} to {} in {}", args.amount, to, receipt.tx_hash);
     Ok(())
 }
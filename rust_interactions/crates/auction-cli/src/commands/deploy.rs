@@ -0,0 +1,52 @@
+use auction_interface::{
+    amm_auction_config::AmmAuctionConfig,
+    deploy::{DeployOptions, Deployer, DeploymentState, ForgeArtifacts},
+    swap::DEFAULT_FEE_TIER,
+};
+use color_eyre::{eyre, eyre::Context};
+
+use crate::cli::deploy::DeployArgs;
+
+pub(crate) async fn deploy(args: &DeployArgs, config: &AmmAuctionConfig) -> eyre::Result<()> {
+    let state = if args.fresh {
+        DeploymentState::fresh(&args.state_file)
+    } else {
+        DeploymentState::load(&args.state_file)?
+    };
+    let options = DeployOptions {
+        pool_fee: args
+            .pool_fee
+            .or(config.swap_fee_tier)
+            .unwrap_or(DEFAULT_FEE_TIER),
+        init_pool_state: args.init_pool_state,
+        bid_deposit: args.bid_deposit,
+        ..Default::default()
+    };
+
+    let state = Deployer::from_config(
+        config,
+        ForgeArtifacts::new(&args.out_dir),
+        args.admin.as_deref(),
+        state,
+    )
+    .wrap_err("failed to build deployer")?
+    .with_options(options)
+    .run()
+    .await
+    .wrap_err(format!(
+        "deployment failed, rerun to resume from {}",
+        args.state_file
+    ))?;
+
+    let addresses = state.env_addresses();
+    AmmAuctionConfig::write_addresses(&args.env_file, &addresses)
+        .wrap_err("failed to write deployed addresses")?;
+    for (key, address) in &addresses {
+        println!("{}={}", key, address);
+    }
+    println!(
+        "wrote deployed addresses to {}, run `amm-auction initialize-suapp` next",
+        args.env_file
+    );
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use auction_interface::{
+    accounts::{AccountRegistry, AccountRole},
+    amm_auction_config::AmmAuctionConfig,
+    guard::GuardClient,
+    l1_client::L1Client,
+    pool_lock::PoolLockDetector,
+};
+use color_eyre::{eyre, eyre::Context};
+
+use crate::cli::guard::{EnableAuctionArgs, PoolLocksArgs, SetAddressArgs, StatusArgs};
+
+/// Builds a guard client sending admin calls from the named admin account, the first one if
+/// no name is given.
+fn admin_client(admin: Option<&str>, config: &AmmAuctionConfig) -> eyre::Result<GuardClient> {
+    let accounts = AccountRegistry::from_config(config)?;
+    let wallet = accounts
+        .resolve(admin, AccountRole::Admin)
+        .context("failed to resolve admin")?
+        .clone();
+    let l1 = L1Client::new(&config.rpc_url_l1, wallet).context("failed to build L1 client")?;
+    Ok(GuardClient::from_config(config)?.with_admin(l1))
+}
+
+pub(crate) async fn status(args: &StatusArgs, config: &AmmAuctionConfig) -> eyre::Result<()> {
+    let state = GuardClient::from_config(config)?
+        .state()
+        .await
+        .wrap_err("failed to get guard state")?;
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&state).wrap_err("failed to serialize guard state")?
+        );
+    } else {
+        println!("{}", state);
+    }
+    Ok(())
+}
+
+pub(crate) async fn set_admin(
+    args: &SetAddressArgs,
+    config: &AmmAuctionConfig,
+) -> eyre::Result<()> {
+    let event = admin_client(args.admin.as_deref(), config)?
+        .set_admin(args.address)
+        .await
+        .wrap_err("failed to set admin")?;
+    println!("{:?}", event);
+    Ok(())
+}
+
+pub(crate) async fn set_suapp_key(
+    args: &SetAddressArgs,
+    config: &AmmAuctionConfig,
+) -> eyre::Result<()> {
+    let event = admin_client(args.admin.as_deref(), config)?
+        .set_suapp_key(args.address)
+        .await
+        .wrap_err("failed to set suapp key")?;
+    println!("{:?}", event);
+    Ok(())
+}
+
+pub(crate) async fn set_fee_address(
+    args: &SetAddressArgs,
+    config: &AmmAuctionConfig,
+) -> eyre::Result<()> {
+    let event = admin_client(args.admin.as_deref(), config)?
+        .set_fee_address(args.address)
+        .await
+        .wrap_err("failed to set fee address")?;
+    println!("{:?}", event);
+    Ok(())
+}
+
+pub(crate) async fn enable_auction(
+    args: &EnableAuctionArgs,
+    config: &AmmAuctionConfig,
+) -> eyre::Result<()> {
+    let event = admin_client(args.admin.as_deref(), config)?
+        .enable_auction(args.enabled)
+        .await
+        .wrap_err("failed to enable auction")?;
+    println!("{:?}", event);
+    Ok(())
+}
+
+pub(crate) async fn pool_locks(
+    args: &PoolLocksArgs,
+    config: &AmmAuctionConfig,
+) -> eyre::Result<()> {
+    let detector = PoolLockDetector::from_config(config)
+        .await
+        .wrap_err("failed to build pool lock detector")?;
+    let to = match args.to {
+        Some(to) => to,
+        None => detector.latest_block().await?,
+    };
+    let from = args
+        .from
+        .unwrap_or_else(|| to.saturating_sub(args.blocks.saturating_sub(1)));
+    let report = detector
+        .scan(from, to)
+        .await
+        .wrap_err("failed to scan for pool locks")?;
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).wrap_err("failed to serialize report")?
+        );
+    } else {
+        println!("{}", report);
+    }
+    Ok(())
+}
@@ -1,15 +1,23 @@
 mod amm_auction;
+mod deploy;
+mod guard;
 
-use auction_interface::amm_auction::AuctionSuapp;
-use color_eyre::{eyre, eyre::eyre};
+use auction_interface::{amm_auction::AuctionSuapp, amm_auction_config::AmmAuctionConfig};
+use color_eyre::{
+    eyre,
+    eyre::{eyre, Context},
+};
 
-use crate::cli::{amm_auction::Command as AmmAuctionCommand, Cli, Command};
+use crate::cli::{
+    amm_auction::Command as AmmAuctionCommand, guard::Command as GuardCommand, Cli, Command,
+};
 
 /// Checks what function needs to be run and calls it with the appropriate arguments
 ///
 /// # Arguments
 ///
 /// * `cli` - The arguments passed to the command
+/// * `config` - The .env config, the wrapper to send CCRs with is built from it
 ///
 /// # Errors
 ///
@@ -18,28 +26,63 @@ use crate::cli::{amm_auction::Command as AmmAuctionCommand, Cli, Command};
 /// # Panics
 ///
 /// * If the command is not recognized
-pub async fn run(cli: Cli, mut amm_auction: AuctionSuapp) -> eyre::Result<()> {
+pub async fn run(cli: Cli, config: AmmAuctionConfig) -> eyre::Result<()> {
     if let Some(command) = cli.command {
         match command {
-            Command::AmmAuction { command } => match command {
-                AmmAuctionCommand::Auction(args) => {
-                    amm_auction::trigger_auction(&args, &mut amm_auction).await?
-                }
-                AmmAuctionCommand::Bid(args) => {
-                    amm_auction::send_bid(&args, &mut amm_auction).await?
-                }
-                AmmAuctionCommand::SwapTx(args) => {
-                    amm_auction::send_swap_tx(&args, &mut amm_auction).await?
-                }
-                AmmAuctionCommand::InitializeSuapp(args) => {
-                    amm_auction::initialize_suapp(&args, &mut amm_auction).await?
-                }
-                AmmAuctionCommand::AddSwaps(args) => {
-                    amm_auction::add_swaps(&args, &mut amm_auction).await?
-                }
-                AmmAuctionCommand::AuctionStats(args) => {
-                    amm_auction::auction_stats(&args, &mut amm_auction).await?
+            // deploying happens before the contract addresses the wrapper needs exist
+            Command::Deploy(args) => deploy::deploy(&args, &config).await?,
+            Command::AmmAuction { command } => {
+                let mut amm_auction = amm_auction_wrapper(&config).await?;
+                match command {
+                    AmmAuctionCommand::Auction(args) => {
+                        amm_auction::trigger_auction(&args, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::Bid(args) => {
+                        amm_auction::send_bid(&args, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::SwapTx(args) => {
+                        amm_auction::send_swap_tx(&args, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::InitializeSuapp(args) => {
+                        amm_auction::initialize_suapp(&args, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::AddSwaps(args) => {
+                        amm_auction::add_swaps(&args, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::AuctionStats(args) => {
+                        amm_auction::auction_stats(&args, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::VerifyBundle(args) => {
+                        amm_auction::verify_bundle(&args, &mut amm_auction, &config).await?
+                    }
+                    AmmAuctionCommand::PoolState(args) => {
+                        amm_auction::pool_state(&args, &config).await?
+                    }
+                    AmmAuctionCommand::Arbitrage(args) => {
+                        amm_auction::arbitrage(&args, &config).await?
+                    }
+                    AmmAuctionCommand::Events(args) => {
+                        amm_auction::watch_events(&args, &config).await?
+                    }
+                    AmmAuctionCommand::Deposit(args) => {
+                        amm_auction::deposit(&args, &config, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::DepositBalance(args) => {
+                        amm_auction::deposit_balance(&args, &config, &mut amm_auction).await?
+                    }
+                    AmmAuctionCommand::Withdraw(args) => {
+                        amm_auction::withdraw(&args, &config, &mut amm_auction).await?
+                    }
                 }
+            }
+            // admin calls only need the admin's key, not the whole wrapper
+            Command::Guard { command } => match command {
+                GuardCommand::Status(args) => guard::status(&args, &config).await?,
+                GuardCommand::SetAdmin(args) => guard::set_admin(&args, &config).await?,
+                GuardCommand::SetSuappKey(args) => guard::set_suapp_key(&args, &config).await?,
+                GuardCommand::SetFeeAddress(args) => guard::set_fee_address(&args, &config).await?,
+                GuardCommand::EnableAuction(args) => guard::enable_auction(&args, &config).await?,
+                GuardCommand::PoolLocks(args) => guard::pool_locks(&args, &config).await?,
             },
         }
     } else {
@@ -47,3 +90,9 @@ pub async fn run(cli: Cli, mut amm_auction: AuctionSuapp) -> eyre::Result<()> {
     }
     Ok(())
 }
+
+async fn amm_auction_wrapper(config: &AmmAuctionConfig) -> eyre::Result<AuctionSuapp> {
+    AuctionSuapp::new_from_config(config.clone())
+        .await
+        .wrap_err("failed to build amm auction suapp wrapper")
+}
@@ -1,7 +1,7 @@
 use std::process::ExitCode;
 
 use auction_cli::{cli::Cli, commands};
-use auction_interface::{amm_auction::AuctionSuapp, amm_auction_config::AmmAuctionConfig};
+use auction_interface::amm_auction_config::AmmAuctionConfig;
 use color_eyre::eyre::{self, Context};
 
 fn main() -> ExitCode {
@@ -23,13 +23,9 @@ fn run() -> eyre::Result<()> {
 async fn async_main() -> eyre::Result<()> {
     let config = AmmAuctionConfig::new("../solidity_code/.env")
         .await
-        .expect("failed to build auction amm config");
-
-    let amm_auction_wrapper = AuctionSuapp::new_from_config(config)
-        .await
-        .wrap_err("failed to build amm auction suapp wrapper")?;
+        .wrap_err("failed to build auction amm config")?;
 
     let args = Cli::get_args()?;
-    commands::run(args, amm_auction_wrapper).await?;
+    commands::run(args, config).await?;
     Ok(())
 }
@@ -0,0 +1,386 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use alloy::signers::{
+    wallet::{coins_bip39::English, LocalWallet, MnemonicBuilder},
+    Signer,
+};
+use alloy_primitives::Address;
+use color_eyre::{eyre, eyre::Context};
+use serde::Deserialize;
+
+use crate::amm_auction_config::AmmAuctionConfig;
+
+/// What an account is used for in the auction flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountRole {
+    /// Sends bids and the bid's inner swap on L1, needs funds in `AuctionDeposits`
+    Bidder,
+    /// Sends non-bid swaps on L1
+    Swapper,
+    /// Pays for CCRs on Suave
+    SuaveSigner,
+    /// Key stored in the suapp to sign `postAuctionResults()` on L1
+    SuappSigner,
+    /// Deploys the L1 contracts and sends the guard's admin calls
+    Admin,
+}
+
+impl fmt::Display for AccountRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = match self {
+            AccountRole::Bidder => "bidder",
+            AccountRole::Swapper => "swapper",
+            AccountRole::SuaveSigner => "suave_signer",
+            AccountRole::SuappSigner => "suapp_signer",
+            AccountRole::Admin => "admin",
+        };
+        write!(f, "{}", role)
+    }
+}
+
+/// Errors returned when resolving accounts from the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountError {
+    /// No account with this name is registered
+    UnknownAccount(String),
+    /// The account exists but is registered for a different role
+    WrongRole {
+        name: String,
+        expected: AccountRole,
+        actual: AccountRole,
+    },
+    /// No account is registered for a role that needs one
+    MissingRole(AccountRole),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::UnknownAccount(name) => write!(f, "unknown account: {}", name),
+            AccountError::WrongRole {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "account {} has role {} but {} is required",
+                name, actual, expected
+            ),
+            AccountError::MissingRole(role) => write!(f, "no account registered as {}", role),
+        }
+    }
+}
+
+impl std::error::Error for AccountError {}
+
+/// A named wallet and the role it plays.
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub role: AccountRole,
+    pub wallet: LocalWallet,
+}
+
+/// Where an account's signing key is loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// Encrypted JSON keystore, the password is read from `password_file` or prompted for
+    Keystore {
+        path: PathBuf,
+        password_file: Option<PathBuf>,
+    },
+    /// BIP-39 mnemonic read from a file, derived at `m/44'/60'/0'/0/{index}`
+    Mnemonic { path: PathBuf, index: u32 },
+    /// Plaintext hex private key, only allowed when plaintext keys are opted into
+    PlaintextHex(String),
+}
+
+impl KeySource {
+    /// Decrypts/derives the wallet for the named account.
+    pub fn load(&self, name: &str, allow_plaintext_keys: bool) -> eyre::Result<LocalWallet> {
+        match self {
+            KeySource::Keystore {
+                path,
+                password_file,
+            } => {
+                let password = match password_file {
+                    Some(password_file) => std::fs::read_to_string(password_file)
+                        .context(format!(
+                            "failed to read password file {}",
+                            password_file.display()
+                        ))?
+                        .trim_end_matches(['\r', '\n'])
+                        .to_string(),
+                    None => rpassword::prompt_password(format!("keystore password for {}: ", name))
+                        .context("failed to read keystore password")?,
+                };
+                LocalWallet::decrypt_keystore(path, password)
+                    .context(format!("failed to decrypt {}'s keystore", name))
+            }
+            KeySource::Mnemonic { path, index } => {
+                let phrase = std::fs::read_to_string(path)
+                    .context(format!("failed to read mnemonic file {}", path.display()))?;
+                MnemonicBuilder::<English>::default()
+                    .phrase(phrase.trim())
+                    .index(*index)
+                    .context("invalid derivation index")?
+                    .build()
+                    .context(format!("failed to derive {}'s key from mnemonic", name))
+            }
+            KeySource::PlaintextHex(private_key) => {
+                eyre::ensure!(
+                    allow_plaintext_keys,
+                    "account {} uses a plaintext private key, set ALLOW_PLAINTEXT_KEYS=true to \
+                     allow this in dev mode",
+                    name
+                );
+                private_key
+                    .parse()
+                    .context(format!("failed to parse {}'s pk", name))
+            }
+        }
+    }
+}
+
+/// On-disk representation of a single account in the keyfile.
+#[derive(Deserialize)]
+struct AccountEntry {
+    name: String,
+    role: AccountRole,
+    /// Optional sanity check against the address derived from the key
+    address: Option<Address>,
+    keystore: Option<PathBuf>,
+    password_file: Option<PathBuf>,
+    mnemonic_file: Option<PathBuf>,
+    derivation_index: Option<u32>,
+    private_key: Option<String>,
+}
+
+impl AccountEntry {
+    /// Picks the entry's key source, relative paths are resolved against `base_dir`.
+    fn key_source(&self, base_dir: &Path) -> eyre::Result<KeySource> {
+        match (&self.keystore, &self.mnemonic_file, &self.private_key) {
+            (Some(keystore), None, None) => Ok(KeySource::Keystore {
+                path: base_dir.join(keystore),
+                password_file: self
+                    .password_file
+                    .as_ref()
+                    .map(|password_file| base_dir.join(password_file)),
+            }),
+            (None, Some(mnemonic_file), None) => Ok(KeySource::Mnemonic {
+                path: base_dir.join(mnemonic_file),
+                index: self.derivation_index.unwrap_or(0),
+            }),
+            (None, None, Some(private_key)) => Ok(KeySource::PlaintextHex(private_key.clone())),
+            _ => eyre::bail!(
+                "account {} must set exactly one of keystore, mnemonic_file, or private_key",
+                self.name
+            ),
+        }
+    }
+}
+
+/// On-disk representation of the keyfile.
+#[derive(Deserialize)]
+struct AccountsFile {
+    accounts: Vec<AccountEntry>,
+}
+
+/// Registry of any number of named accounts.
+///
+/// Loaded from a TOML or JSON keyfile of the form:
+/// ```toml
+/// [[accounts]]
+/// name = "bidder_0"
+/// role = "bidder"
+/// address = "0x..." # optional
+/// keystore = "keys/bidder_0.json"
+/// password_file = "keys/bidder_0.pass" # optional, prompted for otherwise
+///
+/// [[accounts]]
+/// name = "swapper_0"
+/// role = "swapper"
+/// mnemonic_file = "keys/swappers.mnemonic"
+/// derivation_index = 0
+///
+/// [[accounts]]
+/// name = "suave_signer"
+/// role = "suave_signer"
+/// private_key = "0x..." # dev mode only
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AccountRegistry {
+    accounts: BTreeMap<String, Account>,
+}
+
+impl AccountRegistry {
+    /// Loads the registry from a keyfile, the format is picked from the file's extension.
+    ///
+    /// Plaintext `private_key` entries are rejected unless `allow_plaintext_keys` is set.
+    pub fn from_file(path: impl AsRef<Path>, allow_plaintext_keys: bool) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .context(format!("failed to read accounts file {}", path.display()))?;
+
+        let file: AccountsFile = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => {
+                serde_json::from_str(&contents).context("failed to parse json accounts file")?
+            }
+            Some("toml") => {
+                toml::from_str(&contents).context("failed to parse toml accounts file")?
+            }
+            _ => eyre::bail!(
+                "accounts file {} must have a .toml or .json extension",
+                path.display()
+            ),
+        };
+
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        let mut registry = AccountRegistry::default();
+        for entry in file.accounts {
+            let wallet = entry
+                .key_source(base_dir)?
+                .load(&entry.name, allow_plaintext_keys)?;
+            if let Some(address) = entry.address {
+                eyre::ensure!(
+                    wallet.address() == address,
+                    "account {}'s key is for {} but address {} was given",
+                    entry.name,
+                    wallet.address(),
+                    address
+                );
+            }
+            registry.insert(entry.name, entry.role, wallet)?;
+        }
+        Ok(registry)
+    }
+
+    /// Loads the registry from `ACCOUNTS_FILE` if set, from the .env's `*_pk` fields otherwise.
+    pub fn from_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        match &config.accounts_file {
+            Some(accounts_file) => {
+                AccountRegistry::from_file(accounts_file, config.allow_plaintext_keys)
+                    .context("failed to load accounts file")
+            }
+            None => AccountRegistry::from_legacy_config(config)
+                .context("failed to load accounts from .env"),
+        }
+    }
+
+    /// Builds the registry from the fixed `*_pk` fields of the .env file.
+    ///
+    /// These are plaintext keys so this is only allowed with `ALLOW_PLAINTEXT_KEYS=true`.
+    pub fn from_legacy_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        eyre::ensure!(
+            config.allow_plaintext_keys,
+            "no ACCOUNTS_FILE given, set ALLOW_PLAINTEXT_KEYS=true to use the plaintext *_PK \
+             keys from the .env in dev mode"
+        );
+
+        let suave_signer_pk = if config.use_local {
+            &config.suave_signer_local_pk
+        } else {
+            &config.suave_signer_pk
+        };
+
+        // the admin is bidder_0 unless it has its own key, as the deploy scripts have it
+        let admin_pk = config.admin_pk.clone().or(config.bidder_0_pk.clone());
+
+        let legacy_accounts = [
+            ("suave_signer", AccountRole::SuaveSigner, suave_signer_pk),
+            (
+                "suapp_signer",
+                AccountRole::SuappSigner,
+                &config.suapp_signer_pk,
+            ),
+            ("admin", AccountRole::Admin, &admin_pk),
+            ("bidder_0", AccountRole::Bidder, &config.bidder_0_pk),
+            ("bidder_1", AccountRole::Bidder, &config.bidder_1_pk),
+            ("bidder_2", AccountRole::Bidder, &config.bidder_2_pk),
+            ("swapper_0", AccountRole::Swapper, &config.swapper_0_pk),
+            ("swapper_1", AccountRole::Swapper, &config.swapper_1_pk),
+            ("swapper_2", AccountRole::Swapper, &config.swapper_2_pk),
+        ];
+
+        let mut registry = AccountRegistry::default();
+        for (name, role, pk) in legacy_accounts {
+            // accounts left out of the .env are skipped
+            let Some(pk) = pk else {
+                continue;
+            };
+            let wallet = KeySource::PlaintextHex(pk.clone()).load(name, true)?;
+            registry.insert(name.to_string(), role, wallet)?;
+        }
+        Ok(registry)
+    }
+
+    /// Adds a named account, names must be unique.
+    pub fn insert(
+        &mut self,
+        name: String,
+        role: AccountRole,
+        wallet: LocalWallet,
+    ) -> eyre::Result<()> {
+        eyre::ensure!(
+            !self.accounts.contains_key(&name),
+            "account {} is defined more than once",
+            name
+        );
+        self.accounts.insert(name, Account { role, wallet });
+        Ok(())
+    }
+
+    /// Looks up an account by name.
+    pub fn get(&self, name: &str) -> Result<&Account, AccountError> {
+        self.accounts
+            .get(name)
+            .ok_or_else(|| AccountError::UnknownAccount(name.to_string()))
+    }
+
+    /// Looks up an account's wallet by name, checking that it has the expected role.
+    pub fn wallet(&self, name: &str, role: AccountRole) -> Result<&LocalWallet, AccountError> {
+        let account = self.get(name)?;
+        if account.role != role {
+            return Err(AccountError::WrongRole {
+                name: name.to_string(),
+                expected: role,
+                actual: account.role,
+            });
+        }
+        Ok(&account.wallet)
+    }
+
+    /// Returns the wallet of the first account with the given role.
+    pub fn first_with_role(&self, role: AccountRole) -> Result<&LocalWallet, AccountError> {
+        self.accounts
+            .values()
+            .find(|account| account.role == role)
+            .map(|account| &account.wallet)
+            .ok_or(AccountError::MissingRole(role))
+    }
+
+    /// Looks up the named account if a name is given, the first with the role otherwise.
+    pub fn resolve(
+        &self,
+        name: Option<&str>,
+        role: AccountRole,
+    ) -> Result<&LocalWallet, AccountError> {
+        match name {
+            Some(name) => self.wallet(name, role),
+            None => self.first_with_role(role),
+        }
+    }
+
+    /// Names of all accounts with the given role, in sorted order.
+    pub fn names_with_role(&self, role: AccountRole) -> Vec<String> {
+        self.accounts
+            .iter()
+            .filter(|(_, account)| account.role == role)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
@@ -1,26 +1,38 @@
-use std::collections::HashMap;
+use std::sync::Arc;
 
 use alloy::{
-    eips::{eip2718::Encodable2718, BlockId},
+    eips::{eip2718::Encodable2718, BlockId, BlockNumberOrTag},
     network::{EthereumSigner, TransactionBuilder},
-    providers::{fillers::FillProvider, Provider, ProviderBuilder, RootProvider, WalletProvider},
-    rpc::types::eth::{TransactionInput, TransactionRequest},
+    providers::{fillers::FillProvider, Provider, ProviderBuilder, RootProvider},
+    rpc::types::eth::TransactionRequest,
     signers::{wallet::LocalWallet, Signer},
     sol,
-    transports::http::Http,
+    transports::{http::Http, TransportError},
 };
 use alloy_primitives::{Address, Bytes, B256, U256};
-use alloy_sol_types::{SolCall, SolStruct, SolValue};
+use alloy_sol_types::{Eip712Domain, SolCall, SolValue};
 use color_eyre::{eyre, eyre::Context};
 use eyre::ContextCompat;
 use reqwest::Client as ReqwestClient;
+use serde::Serialize;
 use suave_alloy::{
     self,
     network::{KettleFiller, SuaveNetwork, SuaveProvider, SuaveSigner},
     types::{ConfidentialComputeRecord, ConfidentialComputeRequest},
 };
-
-use crate::amm_auction_config::AmmAuctionConfig;
+use tokio::time::{sleep, Duration};
+
+use crate::{
+    accounts::{AccountRegistry, AccountRole},
+    amm_auction_config::AmmAuctionConfig,
+    bid_signer::BidSigner,
+    bid_validation::{BidRejection, BidRejectionReport},
+    ccr::{revert_data, CcrOutcome, SuappRevert, SuaveReceipt},
+    gas::GasPolicy,
+    nonce_manager::{NonceErrorKind, NonceManager},
+    raw_tx::RawTransaction,
+    swap::{Swap, SwapBuilder, DEFAULT_DEADLINE_OFFSET_SECS, DEFAULT_FEE_TIER},
+};
 
 sol! {
     #[sol(rpc)]
@@ -42,6 +54,18 @@ sol! {
         #[derive(Debug)]
         function _resetSwaps() external returns (bytes memory);
 
+        error OnlyOwner();
+        error StaleBid();
+        error AuctionNotEnded();
+        error AuctionAlreadyRan();
+
+        #[derive(Debug)]
+        event UpdateKey(bytes16 newKey);
+        #[derive(Debug)]
+        event NonBidTxnId(address sender, bytes16 txnId);
+        #[derive(Debug)]
+        event NewBid(bytes32 saltedReturn, bytes16 bidId);
+
         struct Bid {
             address bidder;
             uint256 blockNumber;
@@ -53,6 +77,69 @@ sol! {
         }
     }
 
+    #[derive(Debug, PartialEq, Eq)]
+    interface IAuctionGuard {
+        function setAdmin(address newAdmin) external;
+        function setSuappKey(address newSuappKey) external;
+        function setFeeAddress(address newFeeAddress) external;
+        function enableAuction(bool setAuction) external;
+        function currentBlockAuctionDone() external view returns (bool);
+        function getFeeAddress() external view returns (address);
+        function lastConcludedBlock() external view returns (uint256);
+        function winnerValidBlock() external view returns (uint256);
+        function winnerTxOrigin() external view returns (address);
+        function auctionsEnabled() external view returns (bool);
+        function suappKey() external view returns (address);
+        function admin() external view returns (address);
+        function postAuctionResults(address bidder, uint256 validBlock, uint256 price, bool auction, uint8 v, bytes32 r, bytes32 s) external;
+
+        error WrongValidWinnerBlock();
+        error WrongWinner();
+        error AuctionsNotRunning();
+        error AuctionAlreadyPosted();
+        error OnlyAdmin();
+        error OnlySuappKey();
+        error ZeroAddress();
+
+        #[derive(Debug)]
+        event AuctionsEnabled(bool enabled);
+        #[derive(Debug)]
+        event AuctionSucceeded();
+        #[derive(Debug)]
+        event SuccessfulPayment();
+        #[derive(Debug)]
+        event AdminChanged(address indexed oldAdmin, address indexed newAdmin);
+        #[derive(Debug)]
+        event SuappKeyChanged(address indexed oldSuappKey, address indexed newSuappKey);
+        #[derive(Debug)]
+        event FeeAddressChanged(address indexed oldFeeAddress, address indexed newFeeAddress);
+    }
+
+    interface IAuctionDeposits {
+        function deposit() external payable;
+        function balanceOf(address bidder) external view returns (uint256);
+        function withdraw(address to, uint256 amount) external;
+        function setAuctionGuard(address auctionGuard_) external;
+        function auctionGuard() external view returns (address);
+
+        error OnlyAuction();
+        error AuctionAlreadySet();
+        error AuctionNotDone();
+        error AuctionAlreadyWithdrawn();
+        error WrongBlockNumber();
+        error WrongSigner();
+        error NotEnoughFunds();
+        error ZeroAddress();
+        error TransferError(bytes error);
+
+        #[derive(Debug)]
+        event Deposit(address depositee, address depositor, uint256 amount);
+        #[derive(Debug)]
+        event Withdraw(address from, address to, uint256 amount);
+        #[derive(Debug)]
+        event EnforceSequencing(bool enabled);
+    }
+
     struct WithdrawBid{
         address bidder;
         uint256 blockNumber;
@@ -65,6 +152,18 @@ sol! {
             ExactInputSingleParams calldata params
         ) external payable returns (uint256 amountOut);
 
+        function exactOutputSingle(
+            ExactOutputSingleParams calldata params
+        ) external payable returns (uint256 amountIn);
+
+        function exactInput(
+            ExactInputParams calldata params
+        ) external payable returns (uint256 amountOut);
+
+        function exactOutput(
+            ExactOutputParams calldata params
+        ) external payable returns (uint256 amountIn);
+
         struct ExactInputSingleParams {
             address tokenIn;
             address tokenOut;
@@ -75,9 +174,118 @@ sol! {
             uint256 amountOutMinimum;
             uint160 sqrtPriceLimitX96;
         }
+
+        struct ExactOutputSingleParams {
+            address tokenIn;
+            address tokenOut;
+            uint24 fee;
+            address recipient;
+            uint256 deadline;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+            uint160 sqrtPriceLimitX96;
+        }
+
+        struct ExactInputParams {
+            bytes path;
+            address recipient;
+            uint256 deadline;
+            uint256 amountIn;
+            uint256 amountOutMinimum;
+        }
+
+        struct ExactOutputParams {
+            bytes path;
+            address recipient;
+            uint256 deadline;
+            uint256 amountOut;
+            uint256 amountInMaximum;
+        }
     }
 }
 
+/// How often and how many times to poll for a CCR's receipt before giving up.
+const CCR_RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const CCR_RECEIPT_POLL_ATTEMPTS: u32 = 60;
+
+/// L1 slot time, used to estimate the timestamp of future blocks.
+const L1_BLOCK_TIME_SECS: u64 = 12;
+
+/// Storage slots of `AuctionSuapp.sol`'s state variables.
+///
+/// The `Suave.DataId` records are `bytes16` and are packed two per slot (10 and 11).
+mod auction_suapp_slots {
+    pub(super) const LAST_AUCTION_PROCESSED_L1_BLOCK: u64 = 0;
+    pub(super) const NONCE_USED: u64 = 1;
+    pub(super) const INCLUDED_TXNS: u64 = 2;
+    pub(super) const NOT_LANDED_BUT_SENT: u64 = 3;
+    pub(super) const LANDED: u64 = 4;
+    pub(super) const WINNING_BID_AMOUNT: u64 = 5;
+    pub(super) const CHAIN_ID: u64 = 12;
+    pub(super) const GAS_NEEDED_POST_AUCTION_RESULTS: u64 = 13;
+    pub(super) const AUCTION_DURATION: u64 = 14;
+}
+
+/// Storage slots of `AuctionGuard.sol`'s state variables.
+pub(crate) mod auction_guard_slots {
+    pub(crate) const LAST_CONCLUDED_BLOCK: u64 = 5;
+}
+
+/// Snapshot of the auction suapp's visibility and functional stats.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuctionStats {
+    /// L1 block that was last auctioned (should be the block right after the last L1 block)
+    pub last_auction_processed_l1_block: u64,
+    /// Nonce of the suapp's signing key used in the last sent bundle
+    pub nonce_used: u64,
+    /// Number of non-bid swap transactions included in the last bundle
+    pub included_txns: u64,
+    /// Index up to which non-bid swap transactions have been sent but not yet seen landing
+    pub not_landed_but_sent: u64,
+    /// Index up to which non-bid swap transactions are known to have landed
+    pub landed: u64,
+    /// Amount the last auction's winner paid (is 2nd price)
+    pub winning_bid_amount: U256,
+    /// L1 chain id the suapp signs auction results for
+    pub chain_id: u64,
+    /// Gas limit used for the `postAuctionResults()` transaction
+    pub gas_needed_post_auction_results: u64,
+    /// Seconds past the last L1 block's timestamp before an auction can run
+    pub auction_duration: u64,
+}
+
+impl std::fmt::Display for AuctionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Auction Stats")?;
+        writeln!(
+            f,
+            "  auctioned block      : {}",
+            self.last_auction_processed_l1_block
+        )?;
+        writeln!(f, "  last nonce used      : {}", self.nonce_used)?;
+        writeln!(f, "  included swap txns   : {}", self.included_txns)?;
+        writeln!(f, "  not landed but sent  : {}", self.not_landed_but_sent)?;
+        writeln!(f, "  total landed         : {}", self.landed)?;
+        writeln!(f, "  winning bid $        : {}", self.winning_bid_amount)?;
+        writeln!(f, "  l1 chain id          : {}", self.chain_id)?;
+        writeln!(
+            f,
+            "  post auction gas     : {}",
+            self.gas_needed_post_auction_results
+        )?;
+        write!(f, "  auction duration     : {}", self.auction_duration)
+    }
+}
+
+/// A bid the kettle accepted.
+#[derive(Debug, Clone)]
+pub struct BidSubmission {
+    /// Hash of the bid's CCR, `wait_for_ccr()` gives the stored bid's id
+    pub ccr_hash: B256,
+    /// What the bid failed validation for, only non-empty if it was forced through
+    pub report: BidRejectionReport,
+}
+
 pub struct AuctionSuapp {
     auction_suapp: Address,
     deposit_contract: Address,
@@ -107,90 +315,78 @@ pub struct AuctionSuapp {
         Http<ReqwestClient>,
         SuaveNetwork,
     >,
-    eoa_wallets: HashMap<String, LocalWallet>,
+    accounts: AccountRegistry,
+    suave_signer: LocalWallet,
+    suapp_signer: LocalWallet,
     l1_rpc: String,
     bundle_rpc: String,
-    last_used_suave_nonce: u64,
+    nonce_manager: Arc<NonceManager>,
+    suave_gas: GasPolicy,
+    l1_gas: GasPolicy,
+    bid_signer: BidSigner,
+    swap_builder: SwapBuilder,
 }
 
 impl AuctionSuapp {
     pub async fn new_from_config(config: AmmAuctionConfig) -> eyre::Result<Self> {
         let execution_node;
         let suave_rpc;
-        let suave_signer_pk;
         if config.use_local {
             execution_node = config.execution_node_suave_local;
-            suave_rpc = config.rpc_url_suave_local;
-            suave_signer_pk = config.suave_signer_local_pk;
+            suave_rpc = config.rpc_url_suave_local.clone();
         } else {
             execution_node = config.execution_node_suave;
-            suave_rpc = config.rpc_url_suave;
-            suave_signer_pk = config.suave_signer_pk;
+            suave_rpc = config.rpc_url_suave.clone();
         }
 
         // construct eoa accounts
-        let mut eoas = HashMap::<String, LocalWallet>::new();
-        eoas.insert(
-            "suave_signer".to_string(),
-            suave_signer_pk
-                .parse()
-                .context("failed to parse suave_signer's pk")?,
-        );
-        eoas.insert(
-            "suapp_signer".to_string(),
-            config
-                .suapp_signer_pk
-                .parse()
-                .context("failed to parse suapp_signer's pk")?,
-        );
-        eoas.insert(
-            "bidder_0".to_string(),
-            config
-                .bidder_0_pk
-                .parse()
-                .context("failed to parse bidder_0's pk")?,
-        );
-        eoas.insert(
-            "bidder_1".to_string(),
-            config
-                .bidder_1_pk
-                .parse()
-                .context("failed to parse bidder_1's pk")?,
-        );
-        eoas.insert(
-            "bidder_2".to_string(),
-            config
-                .bidder_2_pk
-                .parse()
-                .context("failed to parse bidder_2's pk")?,
-        );
-        eoas.insert(
-            "swapper_0".to_string(),
-            config
-                .swapper_0_pk
-                .parse()
-                .context("failed to parse swapper_0's pk")?,
-        );
-        eoas.insert(
-            "swapper_1".to_string(),
-            config
-                .swapper_1_pk
-                .parse()
-                .context("failed to parse swapper_1's pk")?,
-        );
-        eoas.insert(
-            "swapper_2".to_string(),
+        let accounts = AccountRegistry::from_config(&config)?;
+        let suave_signer = accounts
+            .resolve(
+                config.suave_signer_account.as_deref(),
+                AccountRole::SuaveSigner,
+            )
+            .context("failed to resolve suave signer")?
+            .clone();
+        let suapp_signer = accounts
+            .resolve(
+                config.suapp_signer_account.as_deref(),
+                AccountRole::SuappSigner,
+            )
+            .context("failed to resolve suapp signer")?
+            .clone();
+
+        let nonce_manager = match &config.nonce_store {
+            Some(nonce_store) => NonceManager::with_persistence(nonce_store)
+                .await
+                .context("failed to load nonce store")?,
+            None => NonceManager::new(),
+        };
+        let nonce_manager = Arc::new(nonce_manager);
+
+        let mut suave_gas = GasPolicy::suave_default();
+        if let Some(multiplier) = config.suave_gas_safety_multiplier {
+            suave_gas = suave_gas.with_safety_multiplier(multiplier);
+        }
+        let mut l1_gas = GasPolicy::l1_default();
+        if let Some(multiplier) = config.l1_gas_safety_multiplier {
+            l1_gas = l1_gas.with_safety_multiplier(multiplier);
+        }
+
+        let swap_builder = SwapBuilder::new(
+            config.swap_fee_tier.unwrap_or(DEFAULT_FEE_TIER),
             config
-                .swapper_2_pk
-                .parse()
-                .context("failed to parse swapper_2's pk")?,
+                .swap_deadline_offset_secs
+                .unwrap_or(DEFAULT_DEADLINE_OFFSET_SECS),
         );
 
-        AuctionSuapp::new(
+        let deposit_contract = config
+            .auction_deposits
+            .context("auction deposits not set")?;
+
+        let auction_suapp = AuctionSuapp::new(
             config.suapp_amm.context("auction suapp not set")?,
-            config
-                .auction_deposits
-                .context("auction deposits not set")?,
+            deposit_contract,
             config.token_0.context("auction deposits not set")?,
             config.token_1.context("auction deposits not set")?,
             config.swap_router.context("swap router not set")?,
@@ -198,9 +394,52 @@ impl AuctionSuapp {
             suave_rpc,
             config.rpc_url_l1,
             config.rpc_url_bundle,
-            eoas,
+            accounts,
+            suave_signer,
+            suapp_signer,
+            nonce_manager,
         )
-        .await
+        .await?
+        .with_gas_policies(suave_gas, l1_gas)
+        .with_swap_builder(swap_builder);
+
+        // a domain for the wrong chain makes every bid fail `withdrawBid()` with `WrongSigner`
+        let l1_chain_id = auction_suapp
+            .l1_provider
+            .get_chain_id()
+            .await
+            .context("failed to get L1 chain id")?;
+        eyre::ensure!(
+            l1_chain_id == config.chain_id_l1,
+            "CHAIN_ID_L1 is {} but the L1 rpc is on chain {}",
+            config.chain_id_l1,
+            l1_chain_id
+        );
+
+        // every bid is recovered over the CHAIN_ID_L1 domain, and checked against the
+        // contract's own domain if it has one
+        let onchain_domain = match auction_suapp.query_eip712_domain().await {
+            Ok(domain) => Some(domain),
+            Err(e) => {
+                println!(
+                    "deposits contract has no eip712Domain(), checking bid signatures against \
+                     the CHAIN_ID_L1 domain only: {:#}",
+                    e
+                );
+                None
+            }
+        };
+        let bid_signer = match onchain_domain {
+            Some(domain) if config.query_eip712_domain => {
+                BidSigner::from_domain(domain.clone()).with_onchain_domain(domain)
+            }
+            Some(domain) => {
+                BidSigner::new(config.chain_id_l1, deposit_contract).with_onchain_domain(domain)
+            }
+            None => BidSigner::new(config.chain_id_l1, deposit_contract),
+        };
+
+        Ok(auction_suapp.with_bid_signer(bid_signer))
     }
 
     pub async fn new(
@@ -213,23 +452,28 @@ impl AuctionSuapp {
         suave_rpc: String,
         l1_rpc: String,
         bundle_rpc: String,
-        eoa_accounts: HashMap<String, LocalWallet>,
+        accounts: AccountRegistry,
+        suave_signer: LocalWallet,
+        suapp_signer: LocalWallet,
+        nonce_manager: Arc<NonceManager>,
     ) -> eyre::Result<Self> {
         // build L1 provider
-        let l1_rpc_url =
-            url::Url::parse(&l1_rpc).context("failed to build url from suave rpc string")?;
-        let l1_provider = ProviderBuilder::new()
-            .on_http(l1_rpc_url.clone())
-            .context("failed to build provider from given rpc url")?;
+        let l1_provider = http_provider(&l1_rpc)?;
+
+        // sign bids for the L1 the provider is on until told otherwise
+        let l1_chain_id = l1_provider
+            .get_chain_id()
+            .await
+            .context("failed to get L1 chain id")?;
+        let bid_signer = BidSigner::new(l1_chain_id, deposit_contract);
 
         // build suave provider
-        let suave_signer_wallet = eoa_accounts.get("suave_signer").unwrap().clone();
         let suave_rpc_url =
             url::Url::parse(&suave_rpc).context("failed to build url from suave rpc string")?;
         let suave_provider = ProviderBuilder::<_, _, SuaveNetwork>::default()
             .with_recommended_fillers()
             .filler(KettleFiller::default())
-            .signer(SuaveSigner::new(suave_signer_wallet))
+            .signer(SuaveSigner::new(suave_signer.clone()))
             .on_provider(SuaveProvider::from_http(suave_rpc_url));
 
         Ok(AuctionSuapp {
@@ -241,57 +485,296 @@ impl AuctionSuapp {
             execution_node,
             l1_provider,
             suave_provider,
-            eoa_wallets: eoa_accounts,
+            accounts,
+            suave_signer,
+            suapp_signer,
             l1_rpc,
             bundle_rpc,
-            last_used_suave_nonce: 0,
+            nonce_manager,
+            suave_gas: GasPolicy::suave_default(),
+            l1_gas: GasPolicy::l1_default(),
+            bid_signer,
+            swap_builder: SwapBuilder::default(),
         })
     }
 
+    /// Overrides the gas policies used for CCRs and L1 transactions.
+    pub fn with_gas_policies(mut self, suave_gas: GasPolicy, l1_gas: GasPolicy) -> Self {
+        self.suave_gas = suave_gas;
+        self.l1_gas = l1_gas;
+        self
+    }
+
+    /// Overrides the signer used for bids' `WithdrawBid` EIP-712 signatures.
+    pub fn with_bid_signer(mut self, bid_signer: BidSigner) -> Self {
+        self.bid_signer = bid_signer;
+        self
+    }
+
+    /// Overrides the fee tier and deadline used when encoding swaps.
+    pub fn with_swap_builder(mut self, swap_builder: SwapBuilder) -> Self {
+        self.swap_builder = swap_builder;
+        self
+    }
+
+    /// The pool's tokens ordered as (in, out) for a single pool swap.
+    pub fn pool_tokens(&self, token_0_in: bool) -> (Address, Address) {
+        if token_0_in {
+            (self.token_0, self.token_1)
+        } else {
+            (self.token_1, self.token_0)
+        }
+    }
+
+    /// Nonces handed out for the wrapper's signers, for other clients sending from the same
+    /// accounts to share.
+    pub fn nonce_manager(&self) -> Arc<NonceManager> {
+        self.nonce_manager.clone()
+    }
+
+    /// Signer for bids' `WithdrawBid` EIP-712 signatures.
+    pub fn bid_signer(&self) -> &BidSigner {
+        &self.bid_signer
+    }
+
+    /// Reads the deposits contract's ERC-5267 `eip712Domain()`.
+    ///
+    /// Errors if the contract doesn't implement it, as `AuctionDeposits` built on
+    /// OpenZeppelin 4.0's `EIP712` doesn't.
+    pub async fn query_eip712_domain(&self) -> eyre::Result<Eip712Domain> {
+        let tx = TransactionRequest::default()
+            .to(self.deposit_contract)
+            .input(BidSigner::eip712_domain_calldata().into());
+        let return_data = self
+            .l1_provider
+            .call(&tx, BlockId::latest())
+            .await
+            .context("failed to call eip712Domain() on deposits contract")?;
+        BidSigner::decode_eip712_domain(&return_data)
+    }
+
+    /// Builds a bid signer signing over the deposits contract's `eip712Domain()`.
+    pub async fn query_bid_signer(&self) -> eyre::Result<BidSigner> {
+        let domain = self.query_eip712_domain().await?;
+        Ok(BidSigner::from_domain(domain.clone()).with_onchain_domain(domain))
+    }
+
+    /// Checks the preconditions `AuctionSuapp.sol` silently drops bids for: the deposit
+    /// covering the bid, the target block being in the future, and the swap going through
+    /// once the bidder won.
+    pub async fn validate_bid(
+        &self,
+        bidder: Address,
+        target_block: u64,
+        bid_amount: u128,
+        signed_swap_txn: &[u8],
+    ) -> eyre::Result<BidRejectionReport> {
+        let bid_amount = U256::from(bid_amount);
+        let mut report = BidRejectionReport::new(bidder, target_block, bid_amount);
+        let swap_txn =
+            RawTransaction::decode(signed_swap_txn).context("failed to decode bid's swap")?;
+
+        let deposit = view(
+            &self.l1_provider,
+            self.deposit_contract,
+            IAuctionDeposits::balanceOfCall { bidder },
+            BlockId::latest(),
+        )
+        .await?
+        ._0;
+        if deposit < bid_amount {
+            report.rejections.push(BidRejection::InsufficientDeposit {
+                deposit,
+                bid_amount,
+            });
+        }
+
+        let latest_block = self
+            .l1_provider
+            .get_block_number()
+            .await
+            .context("failed to get latest L1 block number")?;
+        if target_block <= latest_block {
+            report.rejections.push(BidRejection::StaleTargetBlock {
+                target_block,
+                latest_block,
+            });
+        }
+
+        // the guard only lets the auction's winner swap first, so the swap is simulated with
+        // the pending block's auction already concluded
+        let auction_guard = view(
+            &self.l1_provider,
+            self.deposit_contract,
+            IAuctionDeposits::auctionGuardCall {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0;
+        let guard_key = auction_guard.to_string();
+        let slot_key =
+            B256::from(U256::from(auction_guard_slots::LAST_CONCLUDED_BLOCK)).to_string();
+        let state_override = serde_json::json!({
+            guard_key: { "stateDiff": { slot_key: B256::from(U256::MAX) } }
+        });
+        let mut tx = TransactionRequest::default()
+            .from(bidder)
+            .with_value(swap_txn.value)
+            .input(swap_txn.input.into());
+        if let Some(to) = swap_txn.to {
+            tx = tx.to(to);
+        }
+        let simulation: Result<Bytes, TransportError> = self
+            .l1_provider
+            .client()
+            .request("eth_call", (tx, BlockId::pending(), state_override))
+            .await;
+        if let Err(e) = simulation {
+            match revert_data(&e) {
+                Some(data) => report.rejections.push(BidRejection::swap_reverts(&data)),
+                None => return Err(e).context("failed to simulate bid's swap"),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Registry of the named bidder/swapper accounts this wrapper can sign for.
+    pub fn accounts(&self) -> &AccountRegistry {
+        &self.accounts
+    }
+
+    /// Hands a CCR to the kettle, returning its transaction hash once accepted. Its callback
+    /// lands on Suave later, `wait_for_ccr()` waits for it.
+    ///
+    /// If the CCR's confidential execution reverts the returned error holds the decoded
+    /// `SuappRevert`.
     pub async fn send_ccr(
         &self,
         confidential_compute_request: ConfidentialComputeRequest,
-    ) -> eyre::Result<()> {
-        // TODO add better error handling, maybe even skipping getting response?
-        let result = self
+    ) -> eyre::Result<B256> {
+        let result = match self
             .suave_provider
             .send_transaction(confidential_compute_request)
             .await
-            .context("failed to send ccr")?;
-        let tx_hash = B256::from_slice(&result.tx_hash().to_vec());
-        let _ = self
+        {
+            Ok(result) => result,
+            Err(e) => match SuappRevert::from_transport_error(&e) {
+                Some(revert) => {
+                    return Err(eyre::Report::new(revert).wrap_err("ccr execution reverted"))
+                }
+                None => return Err(e).context("failed to send ccr"),
+            },
+        };
+        Ok(B256::from_slice(&result.tx_hash().to_vec()))
+    }
+
+    /// Waits for the callback transaction of a sent CCR to land on Suave.
+    pub async fn wait_for_ccr(&self, tx_hash: B256) -> eyre::Result<CcrOutcome> {
+        for _ in 0..CCR_RECEIPT_POLL_ATTEMPTS {
+            let receipt: Option<SuaveReceipt> = self
+                .suave_provider
+                .client()
+                .request("eth_getTransactionReceipt", (tx_hash,))
+                .await
+                .context("failed to get ccr receipt")?;
+            if let Some(receipt) = receipt {
+                return Ok(receipt.into_outcome(tx_hash, self.auction_suapp));
+            }
+            sleep(CCR_RECEIPT_POLL_INTERVAL).await;
+        }
+        eyre::bail!("timed out waiting for receipt of ccr {}", tx_hash)
+    }
+
+    /// Builds a CCR from `signer` to the suapp and hands it to the kettle. If the node
+    /// rejects the CCR with a nonce error the signer's nonce is resynced and the CCR is resent
+    /// once, if it fails otherwise its nonce is released for the next CCR. The signer's CCRs
+    /// are sent one at a time so the resync can't reuse the nonce of one still in flight.
+    async fn send_suapp_ccr(
+        &self,
+        signer: Address,
+        input: Bytes,
+        confidential_inputs: Option<Bytes>,
+    ) -> eyre::Result<B256> {
+        let _send_lock = self.nonce_manager.lock_signer(signer).await;
+        let mut resynced = false;
+        loop {
+            // create generic transaction request and add function specific data
+            let tx = self
+                .build_generic_suave_transaction(signer, input.clone())
+                .await
+                .context("failed to build generic suave transaction")?;
+            let (chain_id, nonce) = (tx.chain_id, tx.nonce);
+
+            let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
+                .context("failed to create ccr")?;
+            let result = self
+                .send_ccr(ConfidentialComputeRequest::new(
+                    cc_record,
+                    confidential_inputs.clone(),
+                ))
+                .await;
+
+            match result {
+                Err(e) if !resynced && NonceErrorKind::classify(&e).is_some() => {
+                    println!("ccr nonce rejected for {}, resyncing: {}", signer, e);
+                    self.resync_suave_nonce(signer)
+                        .await
+                        .context("failed to resync suave nonce")?;
+                    resynced = true;
+                }
+                Err(e) => {
+                    if let (Some(chain_id), Some(nonce)) = (chain_id, nonce) {
+                        self.nonce_manager
+                            .release(chain_id, signer, nonce)
+                            .await
+                            .context("failed to release suave nonce")?;
+                    }
+                    return Err(e);
+                }
+                Ok(tx_hash) => return Ok(tx_hash),
+            }
+        }
+    }
+
+    /// Resets the tracked Suave nonce of `signer` to its pending transaction count.
+    pub async fn resync_suave_nonce(&self, signer: Address) -> eyre::Result<()> {
+        let pending_count = self
             .suave_provider
-            .get_transaction_by_hash(tx_hash)
+            .get_transaction_count(signer, BlockId::pending())
+            .await
+            .context("failed to get transaction count for address")?;
+        let chain_id = self
+            .suave_provider
+            .get_chain_id()
+            .await
+            .context("failed to get chain id")?;
+        self.nonce_manager
+            .resync(chain_id, signer, pending_count)
             .await
-            .context("failed to get transaction hash receipt");
-        Ok(())
     }
 
     pub async fn build_generic_suave_transaction(
-        &mut self,
+        &self,
         signer: Address,
+        input: Bytes,
     ) -> eyre::Result<TransactionRequest> {
         // gather network dependent variables
-        let mut nonce = self
+        let pending_count = self
             .suave_provider
-            .get_transaction_count(signer, BlockId::default())
+            .get_transaction_count(signer, BlockId::pending())
             .await
             .context("failed to get transaction count for address")?;
 
-        // nonce management for sending CCRs without waiting for others to complete
-        if self.last_used_suave_nonce >= nonce {
-            nonce = self.last_used_suave_nonce + 1;
-        }
-        self.last_used_suave_nonce = nonce;
-
-        let gas_price = self
-            .suave_provider
-            .get_gas_price()
-            .await
-            .context("failed to get gas price")?
-            .wrapping_add(1_000_000);
+        let gas_price = self.suave_gas.gas_price(
+            self.suave_provider
+                .get_gas_price()
+                .await
+                .context("failed to get gas price")?,
+        );
 
-        let gas = 0x2f4240; // TODO: figure out what is reasonable, probably should be per function
+        // CCRs need a kettle to execute so they can't be estimated, use the selector's profile
+        let gas = self.suave_gas.gas_limit(&input, None);
 
         let chain_id = self
             .suave_provider
@@ -299,35 +782,86 @@ impl AuctionSuapp {
             .await
             .context("failed to get chain id")?;
 
+        // nonce management for sending CCRs without waiting for others to complete
+        let nonce = self
+            .nonce_manager
+            .next_nonce(chain_id, signer, pending_count)
+            .await
+            .context("failed to reserve suave nonce")?;
+
         let tx = TransactionRequest::default()
             .to(self.auction_suapp)
             .gas_limit(gas)
             .with_gas_price(gas_price)
             .with_chain_id(chain_id)
-            .with_nonce(nonce);
+            .with_nonce(nonce)
+            .input(input.into());
         Ok(tx)
     }
 
-    pub async fn build_generic_l1_transaction(
-        &self,
-        signer: Address,
-        target_contract: Address,
-    ) -> eyre::Result<TransactionRequest> {
-        // gather network dependent variables
-        let nonce = self
+    /// Reserves the next L1 nonce for `signer`, for transactions that are expected to land
+    /// in order after any other reserved ones (e.g. non-bid swaps).
+    pub async fn reserve_l1_nonce(&self, signer: Address) -> eyre::Result<u64> {
+        let pending_count = self.pending_l1_nonce(signer).await?;
+        let chain_id = self
             .l1_provider
-            .get_transaction_count(signer, BlockId::default())
+            .get_chain_id()
             .await
-            .context("failed to get transaction count for address")?;
+            .context("failed to get chain id")?;
+        self.nonce_manager
+            .next_nonce(chain_id, signer, pending_count)
+            .await
+            .context("failed to reserve L1 nonce")
+    }
 
-        let gas_price = self
+    /// Gives back an L1 nonce reserved with `reserve_l1_nonce()` whose transaction never
+    /// reached the suapp.
+    pub async fn release_l1_nonce(&self, signer: Address, nonce: u64) -> eyre::Result<()> {
+        let chain_id = self
             .l1_provider
-            .get_gas_price()
+            .get_chain_id()
             .await
-            .context("failed to get gas price")?
-            .wrapping_add(1_000_000_000); // to account for gas fluction between creation and sending
+            .context("failed to get chain id")?;
+        self.nonce_manager.release(chain_id, signer, nonce).await
+    }
 
-        let gas = 0x0f4240; // TODO: figure out what is reasonable, probably should be per function
+    /// Resets the tracked L1 nonce of `signer` to its pending transaction count, for when
+    /// the transactions it reserved nonces for will never land.
+    pub async fn resync_l1_nonce(&self, signer: Address) -> eyre::Result<()> {
+        let pending_count = self.pending_l1_nonce(signer).await?;
+        let chain_id = self
+            .l1_provider
+            .get_chain_id()
+            .await
+            .context("failed to get chain id")?;
+        self.nonce_manager
+            .resync(chain_id, signer, pending_count)
+            .await
+    }
+
+    /// The L1 pending transaction count of `signer`, for transactions that may never land
+    /// (e.g. a bid's swap, which only lands if the bid wins).
+    pub async fn pending_l1_nonce(&self, signer: Address) -> eyre::Result<u64> {
+        self.l1_provider
+            .get_transaction_count(signer, BlockId::pending())
+            .await
+            .context("failed to get transaction count for address")
+    }
+
+    pub async fn build_generic_l1_transaction(
+        &self,
+        signer: Address,
+        target_contract: Address,
+        nonce: u64,
+        input: Bytes,
+    ) -> eyre::Result<TransactionRequest> {
+        // gather network dependent variables
+        let gas_price = self.l1_gas.gas_price(
+            self.l1_provider
+                .get_gas_price()
+                .await
+                .context("failed to get gas price")?,
+        );
 
         let chain_id = self
             .l1_provider
@@ -336,50 +870,66 @@ impl AuctionSuapp {
             .context("failed to get chain id")?;
 
         let tx = TransactionRequest::default()
+            .from(signer)
             .to(target_contract)
-            .gas_limit(gas)
             .with_gas_price(gas_price)
             .with_chain_id(chain_id)
-            .with_nonce(nonce);
-        Ok(tx)
+            .with_nonce(nonce)
+            .input(input.clone().into());
+
+        // guarded swaps revert outside of the auction winner's block, fall back to the
+        // selector's profile when the estimate fails
+        let estimate = if self.l1_gas.should_estimate() {
+            self.l1_provider
+                .estimate_gas(&tx, BlockId::pending())
+                .await
+                .ok()
+        } else {
+            None
+        };
+        let gas = self.l1_gas.gas_limit(&input, estimate);
+
+        Ok(tx.gas_limit(gas))
+    }
+
+    /// Estimates the timestamp of L1 block `block_number` from the latest block, assuming
+    /// no missed slots in between.
+    pub async fn target_block_timestamp(&self, block_number: u64) -> eyre::Result<u64> {
+        let latest = self
+            .l1_provider
+            .get_block_by_number(BlockNumberOrTag::Latest, false)
+            .await
+            .context("failed to get latest L1 block")?
+            .context("latest L1 block not found")?;
+        let latest_number = latest
+            .header
+            .number
+            .context("latest L1 block has no number")?;
+
+        Ok(latest.header.timestamp
+            + block_number.saturating_sub(latest_number) * L1_BLOCK_TIME_SECS)
     }
 
+    /// Builds and signs `swap` as a router transaction from `swapper`, valid until the
+    /// configured deadline offset past `target_block_timestamp`.
     pub async fn new_pending_swap_txn(
         &self,
         swapper: LocalWallet,
-        in_amount: u128,
-        token_0_in: bool,
+        nonce: u64,
+        swap: &Swap,
+        target_block_timestamp: u64,
     ) -> eyre::Result<Vec<u8>> {
         // create swap router transaction input
-        let (token_in, token_out) = if token_0_in {
-            (self.token_0, self.token_1)
-        } else {
-            (self.token_1, self.token_0)
-        };
-
-        let swap_input_params = ISwapRouter::ExactInputSingleParams {
-            tokenIn: token_in,
-            tokenOut: token_out,
-            fee: 3000u32,
-            recipient: swapper.address(),
-            deadline: U256::from(1776038248), // 4/12/2026
-            amountIn: U256::from(in_amount),
-            amountOutMinimum: U256::from(1),
-            sqrtPriceLimitX96: U256::from(0),
-        };
+        let input = self
+            .swap_builder
+            .calldata(swap, swapper.address(), target_block_timestamp)
+            .context("failed to encode swap")?;
 
         // create and sign over the swap transaction
         let mut rlp_encoded_swap_tx = Vec::new();
-        self.build_generic_l1_transaction(swapper.address(), self.swap_router)
+        self.build_generic_l1_transaction(swapper.address(), self.swap_router, nonce, input.into())
             .await
             .context("failed to build generic L1 transaction")?
-            .input(TransactionInput::new(
-                ISwapRouter::exactInputSingleCall {
-                    params: swap_input_params,
-                }
-                .abi_encode()
-                .into(),
-            ))
             .build(&EthereumSigner::from(swapper))
             .await
             .context("failed to sign transaction")?
@@ -388,104 +938,121 @@ impl AuctionSuapp {
         Ok(rlp_encoded_swap_tx)
     }
 
-    pub async fn trigger_auction(&mut self) -> eyre::Result<()> {
-        let suave_signer = self
-            .eoa_wallets
-            .get("suave_signer")
-            .expect("funded suave's wallet not initialized");
+    pub async fn trigger_auction(&self) -> eyre::Result<B256> {
+        self.send_suapp_ccr(
+            self.suave_signer.address(),
+            Bytes::from(IAuctionSuapp::runAuctionCall::SELECTOR),
+            None,
+        )
+        .await
+        .context("failed to send trigger auction CCR")
+    }
 
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(suave_signer.address())
-            .await
-            .context("failed to build generic transaction")?
-            .input(Bytes::from(IAuctionSuapp::runAuctionCall::SELECTOR).into());
+    pub async fn new_pending_txn(&self, swapper: &String, swap: Swap) -> eyre::Result<B256> {
+        let swapper = self
+            .accounts
+            .wallet(swapper, AccountRole::Swapper)
+            .context("failed to get swapper's wallet")?
+            .clone();
 
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-        self.send_ccr(ConfidentialComputeRequest::new(cc_record, None))
+        // non-bid swaps are resent until they land, so their nonces are handed out in order
+        let nonce = self
+            .reserve_l1_nonce(swapper.address())
             .await
-            .context("failed to send trigger auction CCR")?;
-        Ok(())
+            .context("failed to reserve swapper's nonce")?;
+        let swapper_address = swapper.address();
+        match self.submit_pending_txn(swapper, nonce, &swap).await {
+            Ok(tx_hash) => Ok(tx_hash),
+            Err(e) => {
+                // the swap never reached the suapp, its nonce is free for the next one
+                self.release_l1_nonce(swapper_address, nonce)
+                    .await
+                    .context("failed to release swapper's nonce")?;
+                Err(e)
+            }
+        }
     }
 
-    pub async fn new_pending_txn(
-        &mut self,
-        swapper: &String,
-        amount_in: u128,
-        token_0_in: bool,
-    ) -> eyre::Result<()> {
-        let swapper = self
-            .eoa_wallets
-            .get(swapper)
-            .expect("swapper's wallet not initialized");
-
+    /// Signs `swap` with `nonce` and hands it to the suapp as a non-bid swap.
+    async fn submit_pending_txn(
+        &self,
+        swapper: LocalWallet,
+        nonce: u64,
+        swap: &Swap,
+    ) -> eyre::Result<B256> {
+        // non-bid swaps can land in any upcoming block, start their deadline at the next one
+        let next_block = self
+            .l1_provider
+            .get_block_number()
+            .await
+            .context("failed to get L1 block number")?
+            + 1;
+        let target_block_timestamp = self.target_block_timestamp(next_block).await?;
         let signed_swap_transaction = self
-            .new_pending_swap_txn(swapper.clone(), amount_in, token_0_in)
+            .new_pending_swap_txn(swapper, nonce, swap, target_block_timestamp)
             .await
             .context("failed to create swap transaction for new pending tx")?;
 
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(self.suave_provider.default_signer_address())
-            .await
-            .context("failed to build generic transaction")?
-            .input(Bytes::from(IAuctionSuapp::newPendingTxnCall::SELECTOR).into());
-
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-        self.send_ccr(ConfidentialComputeRequest::new(
-            cc_record,
+        self.send_suapp_ccr(
+            self.suave_signer.address(),
+            Bytes::from(IAuctionSuapp::newPendingTxnCall::SELECTOR),
             Some(signed_swap_transaction.into()),
-        ))
+        )
         .await
-        .context("failed to send swap CCR")?;
-        Ok(())
+        .context("failed to send swap CCR")
     }
 
+    /// Sends a bid on the next auction, checked with `validate_bid()` first. A bid failing
+    /// validation errors with a `BidRejectionReport`, unless `force` is set and it's sent
+    /// anyway.
+    ///
+    /// Returns once the kettle accepted the bid's CCR, `wait_for_ccr()` on its hash gives the
+    /// stored bid's id.
     pub async fn new_bid(
-        &mut self,
+        &self,
         bidder: &String,
         block_number: u128,
         bid_amount: u128,
-        in_amount: u128,
-        token_0_in: bool,
-    ) -> eyre::Result<()> {
-        // grab bidder and suave signer
+        swap: Swap,
+        force: bool,
+    ) -> eyre::Result<BidSubmission> {
+        // grab bidder
         let bidder = self
-            .eoa_wallets
-            .get(bidder)
-            .expect("bidders's wallet not initialized");
-        let suave_signer = self
-            .eoa_wallets
-            .get("suave_signer")
-            .expect("funded suave's wallet not initialized");
+            .accounts
+            .wallet(bidder, AccountRole::Bidder)
+            .context("failed to get bidder's wallet")?
+            .clone();
+
+        // create swap router transaction input, a losing bid's swap never lands so the
+        // bidder's pending nonce is reused instead of reserving a new one
+        let nonce = self
+            .pending_l1_nonce(bidder.address())
+            .await
+            .context("failed to get bidder's nonce")?;
+        let target_block =
+            u64::try_from(block_number).context("target block does not fit in u64")?;
+        let target_block_timestamp = self.target_block_timestamp(target_block).await?;
 
-        // create swap router transaction input
         let signed_swap_txn = self
-            .new_pending_swap_txn(bidder.clone(), in_amount, token_0_in)
+            .new_pending_swap_txn(bidder.clone(), nonce, &swap, target_block_timestamp)
             .await
             .context("failed when building bid's inner swap transaction")?;
 
-        // create and sign over withdraw 712 request
-        let my_domain: alloy_sol_types::Eip712Domain = alloy_sol_types::eip712_domain!(
-            name: "AuctionDeposits",
-            version: "v1",
-            chain_id: 17000u64, // holesky
-            verifying_contract: self.deposit_contract,
-        );
-
-        let bid_request = WithdrawBid {
-            bidder: bidder.address(),
-            blockNumber: U256::from(block_number),
-            amount: U256::from(bid_amount),
-        };
+        // the suapp drops bids failing these without telling anyone, `force` sends them anyway
+        let report = self
+            .validate_bid(bidder.address(), target_block, bid_amount, &signed_swap_txn)
+            .await
+            .context("failed to validate bid")?;
+        if !report.is_empty() && !force {
+            return Err(eyre::Report::new(report));
+        }
 
-        let bid_signing_hash = bid_request.eip712_signing_hash(&my_domain);
-        let bid_signature = bidder
-            .sign_hash(&bid_signing_hash)
+        // create and sign over withdraw 712 request, checked locally against the bidder
+        let signed_bid = self
+            .bid_signer
+            .sign(&bidder, block_number, bid_amount)
             .await
-            .context("failed to sign bid EIP712 hash")?;
+            .context("failed to sign bid")?;
 
         // create bid input
         let bid = IAuctionSuapp::Bid {
@@ -493,226 +1060,185 @@ impl AuctionSuapp {
             blockNumber: U256::from(block_number),
             amount: U256::from(bid_amount),
             swapTxn: signed_swap_txn.into(),
-            v: bid_signature.v().y_parity_byte() + 27,
-            r: bid_signature.r().into(),
-            s: bid_signature.s().into(),
+            v: signed_bid.v(),
+            r: signed_bid.r(),
+            s: signed_bid.s(),
         }
         .abi_encode();
 
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(suave_signer.address())
-            .await
-            .context("failed to build generic suave transaction")?
-            .input(
+        let ccr_hash = self
+            .send_suapp_ccr(
+                self.suave_signer.address(),
                 Bytes::from(
                     IAuctionSuapp::newBidCall {
                         salt: "111".to_string(),
                     }
                     .abi_encode(),
-                )
-                .into(),
-            );
-
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-
-        self.send_ccr(ConfidentialComputeRequest::new(cc_record, Some(bid.into())))
+                ),
+                Some(bid.into()),
+            )
             .await
             .context("failed to send bid CCR")?;
-        Ok(())
+        Ok(BidSubmission { ccr_hash, report })
     }
 
-    pub async fn clear_swaps(&mut self) -> eyre::Result<()> {
-        let suave_signer = self
-            .eoa_wallets
-            .get("suave_signer")
-            .expect("funded suave's wallet not initialized");
-
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(suave_signer.address())
-            .await
-            .context("failed to build generic transaction")?
-            .input(Bytes::from(IAuctionSuapp::_resetSwapsCall::SELECTOR).into());
-
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-        self.send_ccr(ConfidentialComputeRequest::new(cc_record, None))
+    /// Drops the suapp's non-bid swaps, the swappers' nonces reserved for them are resynced
+    /// since they'll never land.
+    pub async fn clear_swaps(&self) -> eyre::Result<B256> {
+        let tx_hash = self
+            .send_suapp_ccr(
+                self.suave_signer.address(),
+                Bytes::from(IAuctionSuapp::_resetSwapsCall::SELECTOR),
+                None,
+            )
             .await
             .context("failed to send clear swaps CCR")?;
-        Ok(())
-    }
-
-    pub async fn initialize_l1_block(&mut self) -> eyre::Result<()> {
-        let suave_signer = self
-            .eoa_wallets
-            .get("suave_signer")
-            .expect("funded suave's wallet not initialized");
-
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(suave_signer.address())
-            .await
-            .context("failed to build generic transaction")?
-            .input(Bytes::from(IAuctionSuapp::initLastL1BlockCall::SELECTOR).into());
 
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-        self.send_ccr(ConfidentialComputeRequest::new(cc_record, None))
-            .await
-            .context("failed to send L1 block init CCR")?;
-        Ok(())
+        for swapper in self.accounts.names_with_role(AccountRole::Swapper) {
+            let address = self
+                .accounts
+                .wallet(&swapper, AccountRole::Swapper)
+                .context("failed to get swapper's wallet")?
+                .address();
+            self.resync_l1_nonce(address)
+                .await
+                .context(format!("failed to resync {}'s nonce", swapper))?;
+        }
+        Ok(tx_hash)
     }
 
-    pub async fn set_l1_url(&mut self) -> eyre::Result<()> {
-        let suave_signer = self
-            .eoa_wallets
-            .get("suave_signer")
-            .expect("funded suave's wallet not initialized");
+    pub async fn initialize_l1_block(&self) -> eyre::Result<B256> {
+        self.send_suapp_ccr(
+            self.suave_signer.address(),
+            Bytes::from(IAuctionSuapp::initLastL1BlockCall::SELECTOR),
+            None,
+        )
+        .await
+        .context("failed to send L1 block init CCR")
+    }
 
+    pub async fn set_l1_url(&self) -> eyre::Result<B256> {
         let confidential_inputs = self.l1_rpc.abi_encode_packed();
 
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(suave_signer.address())
-            .await
-            .context("failed to build generic transaction")?
-            .input(Bytes::from(IAuctionSuapp::setL1UrlCall::SELECTOR).into());
-
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-        self.send_ccr(ConfidentialComputeRequest::new(
-            cc_record,
+        self.send_suapp_ccr(
+            self.suave_signer.address(),
+            Bytes::from(IAuctionSuapp::setL1UrlCall::SELECTOR),
             Some(confidential_inputs.into()),
-        ))
+        )
         .await
-        .context("failed to send L1 init CCR")?;
-        Ok(())
+        .context("failed to send L1 init CCR")
     }
 
-    pub async fn set_bundle_url(&mut self) -> eyre::Result<()> {
-        let suave_signer = self
-            .eoa_wallets
-            .get("suave_signer")
-            .expect("funded suave's wallet not initialized");
-
+    pub async fn set_bundle_url(&self) -> eyre::Result<B256> {
         let confidential_inputs = self.bundle_rpc.abi_encode_packed();
 
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(suave_signer.address())
-            .await
-            .context("failed to build generic transaction")?
-            .input(Bytes::from(IAuctionSuapp::setBundleUrlCall::SELECTOR).into());
-
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-        self.send_ccr(ConfidentialComputeRequest::new(
-            cc_record,
+        self.send_suapp_ccr(
+            self.suave_signer.address(),
+            Bytes::from(IAuctionSuapp::setBundleUrlCall::SELECTOR),
             Some(confidential_inputs.into()),
-        ))
+        )
         .await
-        .context("failed to send bundle init CCR")?;
-        Ok(())
-    }
-
-    pub async fn set_signing_key(&mut self) -> eyre::Result<()> {
-        let suave_signer = self
-            .eoa_wallets
-            .get("suave_signer")
-            .expect("funded suave's wallet not initialized");
-
-        let suave_stored_wallet_pk = self
-            .eoa_wallets
-            .get("suapp_signer")
-            .expect("suapp's signing wallet not initialized")
-            .signer()
-            .to_bytes()
-            .abi_encode_packed();
-
-        let suave_stored_wallet_address = self
-            .eoa_wallets
-            .get("suapp_signer")
-            .expect("suapp's signing wallet not initialized")
-            .address();
-
-        // create generic transaction request and add function specific data
-        let tx = self
-            .build_generic_suave_transaction(suave_signer.address())
-            .await
-            .context("failed to build generic transaction")?
-            .input(
-                Bytes::from(
-                    IAuctionSuapp::setSigningKeyCall {
-                        pubKey: suave_stored_wallet_address,
-                    }
-                    .abi_encode(),
-                )
-                .into(),
-            );
-
-        let cc_record = ConfidentialComputeRecord::from_tx_request(tx, self.execution_node)
-            .context("failed to create ccr")?;
-        self.send_ccr(ConfidentialComputeRequest::new(
-            cc_record,
+        .context("failed to send bundle init CCR")
+    }
+
+    pub async fn set_signing_key(&self) -> eyre::Result<B256> {
+        let suave_stored_wallet_pk = self.suapp_signer.signer().to_bytes().abi_encode_packed();
+
+        let suave_stored_wallet_address = self.suapp_signer.address();
+
+        self.send_suapp_ccr(
+            self.suave_signer.address(),
+            Bytes::from(
+                IAuctionSuapp::setSigningKeyCall {
+                    pubKey: suave_stored_wallet_address,
+                }
+                .abi_encode(),
+            ),
             Some(suave_stored_wallet_pk.into()),
-        ))
+        )
         .await
-        .context("failed to send init signing key CCR")?;
-        Ok(())
+        .context("failed to send init signing key CCR")
     }
 
-    pub async fn print_auction_stats(&mut self) -> eyre::Result<()> {
-        // grab from amm's visibility storage slots
-        let slot_0 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(0), BlockId::latest())
+    /// Reads a single storage slot of the auction suapp.
+    async fn get_suapp_storage_slot(&self, slot: u64) -> eyre::Result<U256> {
+        self.suave_provider
+            .get_storage_at(self.auction_suapp, U256::from(slot), BlockId::latest())
             .await
-            .context("failed grabbing amm's storage slot")?;
-        let slot_1 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(1), BlockId::latest())
-            .await
-            .context("failed grabbing amm's storage slot")?;
-        let slot_2 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(2), BlockId::latest())
-            .await
-            .context("failed grabbing amm's storage slot")?;
-        let slot_3 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(3), BlockId::latest())
-            .await
-            .context("failed grabbing amm's storage slot")?;
-        let slot_4 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(4), BlockId::latest())
-            .await
-            .context("failed grabbing amm's storage slot")?;
-        let slot_5 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(5), BlockId::latest())
-            .await
-            .context("failed grabbing amm's storage slot")?;
-        let slot_6 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(6), BlockId::latest())
-            .await
-            .context("failed grabbing amm's storage slot")?;
-        let slot_7 = self
-            .suave_provider
-            .get_storage_at(self.auction_suapp, U256::from(7), BlockId::latest())
-            .await
-            .context("failed grabbing amm's storage slot")?;
-
-        println!("Auction Stats");
-        println!("  auctioned block      : {}", slot_0);
-        println!("  last nonce used      : {}", slot_1);
-        println!("  included swap txns   : {}", slot_2);
-        println!("  total landed         : {}", slot_4);
-        println!("  winning bid $        : {}", slot_5);
+            .context(format!("failed grabbing amm's storage slot {}", slot))
+    }
 
-        Ok(())
+    /// Reads the suapp's visibility/functional stats from storage.
+    pub async fn get_auction_stats(&self) -> eyre::Result<AuctionStats> {
+        let last_auction_processed_l1_block = self
+            .get_suapp_storage_slot(auction_suapp_slots::LAST_AUCTION_PROCESSED_L1_BLOCK)
+            .await?;
+        let nonce_used = self
+            .get_suapp_storage_slot(auction_suapp_slots::NONCE_USED)
+            .await?;
+        let included_txns = self
+            .get_suapp_storage_slot(auction_suapp_slots::INCLUDED_TXNS)
+            .await?;
+        let not_landed_but_sent = self
+            .get_suapp_storage_slot(auction_suapp_slots::NOT_LANDED_BUT_SENT)
+            .await?;
+        let landed = self
+            .get_suapp_storage_slot(auction_suapp_slots::LANDED)
+            .await?;
+        let winning_bid_amount = self
+            .get_suapp_storage_slot(auction_suapp_slots::WINNING_BID_AMOUNT)
+            .await?;
+        let chain_id = self
+            .get_suapp_storage_slot(auction_suapp_slots::CHAIN_ID)
+            .await?;
+        let gas_needed_post_auction_results = self
+            .get_suapp_storage_slot(auction_suapp_slots::GAS_NEEDED_POST_AUCTION_RESULTS)
+            .await?;
+        let auction_duration = self
+            .get_suapp_storage_slot(auction_suapp_slots::AUCTION_DURATION)
+            .await?;
+
+        Ok(AuctionStats {
+            last_auction_processed_l1_block: u64::try_from(last_auction_processed_l1_block)
+                .context("auctioned block does not fit in u64")?,
+            nonce_used: u64::try_from(nonce_used).context("nonce used does not fit in u64")?,
+            included_txns: u64::try_from(included_txns)
+                .context("included txns does not fit in u64")?,
+            not_landed_but_sent: u64::try_from(not_landed_but_sent)
+                .context("not landed but sent does not fit in u64")?,
+            landed: u64::try_from(landed).context("landed does not fit in u64")?,
+            winning_bid_amount,
+            chain_id: u64::try_from(chain_id).context("chain id does not fit in u64")?,
+            gas_needed_post_auction_results: u64::try_from(gas_needed_post_auction_results)
+                .context("gas needed post auction results does not fit in u64")?,
+            auction_duration: u64::try_from(auction_duration)
+                .context("auction duration does not fit in u64")?,
+        })
     }
 }
+
+/// Builds a plain HTTP provider for `rpc_url`.
+pub(crate) fn http_provider(rpc_url: &str) -> eyre::Result<RootProvider<Http<ReqwestClient>>> {
+    let rpc_url = url::Url::parse(rpc_url).context("failed to build url from rpc string")?;
+    ProviderBuilder::new()
+        .on_http(rpc_url)
+        .context("failed to build provider from given rpc url")
+}
+
+/// Calls a view function on `to` at `block`.
+pub(crate) async fn view<C: SolCall>(
+    provider: &RootProvider<Http<ReqwestClient>>,
+    to: Address,
+    call: C,
+    block: BlockId,
+) -> eyre::Result<C::Return> {
+    let tx = TransactionRequest::default()
+        .to(to)
+        .input(Bytes::from(call.abi_encode()).into());
+    let return_data = provider
+        .call(&tx, block)
+        .await
+        .context(format!("failed to call {}", C::SIGNATURE))?;
+    C::abi_decode_returns(&return_data, true)
+        .context(format!("failed to decode {} return data", C::SIGNATURE))
+}
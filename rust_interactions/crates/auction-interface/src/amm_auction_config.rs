@@ -1,44 +1,182 @@
+use std::{fmt::Display, fs, str::FromStr};
+
 use alloy_primitives::Address;
 use eyre::Context;
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer};
+
+/// Which bid strategy the block listener bids with.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BidStrategyKind {
+    /// Small random bids with a fixed swap
+    #[default]
+    Random,
+    /// The same bid and swap every block
+    Fixed,
+    /// Bids a share of the value of arbitraging the pool against a reference price
+    Arbitrage,
+}
+
+/// Where the `arbitrage` bid strategy's reference price comes from.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceFeedKind {
+    /// `BID_STRATEGY_REFERENCE_PRICE`
+    #[default]
+    Static,
+    /// A CSV or JSONL file of recorded quotes
+    Replay,
+    /// A JSON ticker polled over HTTP
+    Http,
+    /// A JSON ticker streamed over a websocket
+    Websocket,
+}
 
 #[derive(Deserialize, Clone)]
 pub struct AmmAuctionConfig {
     pub use_local: bool,
     pub execution_node_suave_local: Address,
     pub execution_node_suave: Address,
-    pub suave_signer_local: Address,
-    pub suave_signer_local_pk: String,
-    pub suave_signer: Address,
-    pub suave_signer_pk: String,
-    pub suapp_signer: Address,
-    pub suapp_signer_pk: String,
-    pub bidder_0: Address,
-    pub bidder_0_pk: String,
-    pub bidder_1: Address,
-    pub bidder_1_pk: String,
-    pub bidder_2: Address,
-    pub bidder_2_pk: String,
-    pub swapper_0: Address,
-    pub swapper_0_pk: String,
-    pub swapper_1: Address,
-    pub swapper_1_pk: String,
-    pub swapper_2: Address,
-    pub swapper_2_pk: String,
+    /// TOML/JSON keyfile with the named accounts, see `AccountRegistry`. When unset the
+    /// fixed `*_pk` fields below are used instead.
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub accounts_file: Option<String>,
+    /// Name of the keyfile account paying for CCRs, defaults to the first `suave_signer`
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suave_signer_account: Option<String>,
+    /// Name of the keyfile account stored in the suapp, defaults to the first `suapp_signer`
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suapp_signer_account: Option<String>,
+    /// Dev mode: allow plaintext hex keys, from the keyfile or the `*_pk` fields below
+    #[serde(default)]
+    pub allow_plaintext_keys: bool,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suave_signer_local: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suave_signer_local_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suave_signer: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suave_signer_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suapp_signer: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suapp_signer_pk: Option<String>,
+    /// Key deploying the L1 contracts and sending the guard's admin calls, `bidder_0_pk`'s
+    /// when unset
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub admin_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bidder_0: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bidder_0_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bidder_1: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bidder_1_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bidder_2: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bidder_2_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swapper_0: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swapper_0_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swapper_1: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swapper_1_pk: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swapper_2: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swapper_2_pk: Option<String>,
     pub chain_id_l1: u64,
+    /// Read the bid EIP-712 domain from the deposits contract's `eip712Domain()` instead of
+    /// building it from `chain_id_l1`, falls back to `chain_id_l1` if the contract lacks it.
+    /// The shipped `AuctionDeposits` (OpenZeppelin `draft-EIP712`) has no `eip712Domain()`, so
+    /// this only does something for deposits contracts built on newer OpenZeppelin versions
+    #[serde(default)]
+    pub query_eip712_domain: bool,
     pub chain_id_suave: u64,
     pub rpc_url_l1: String,
     pub wss_l1: String,
     pub rpc_url_suave_local: String,
     pub rpc_url_suave: String,
     pub rpc_url_bundle: String,
+    /// `RPC_URL_BUNDLE` is a mock builder, read the bundles it received to verify landed
+    /// blocks against
+    #[serde(default)]
+    pub verify_bundle_records: bool,
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub rpc_url_suave_execution_endpoint: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub suapp_amm: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub auction_deposits: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub auction_guard: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub swap_router: Option<Address>,
+    /// `UniswapV3FactoryAuctioned` the pool is resolved from
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub factory: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub pool: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub token_0: Option<Address>,
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub token_1: Option<Address>,
+    /// Fee tier of the pools swaps go through, defaults to 3000
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swap_fee_tier: Option<u32>,
+    /// Seconds past the target block's timestamp built swaps stay valid, defaults to 120
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub swap_deadline_offset_secs: Option<u64>,
+    /// Strategy the block listener bids with, `random` (default), `fixed` or `arbitrage`
+    #[serde(default)]
+    pub bid_strategy: BidStrategyKind,
+    /// Bid in wei of the `fixed` strategy
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_strategy_amount: Option<u64>,
+    /// Amount of the input token the `fixed` strategy swaps
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_strategy_swap_amount: Option<u64>,
+    /// If the `fixed` strategy swaps token 0 in, defaults to true
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_strategy_token_0_in: Option<bool>,
+    /// Token 1 per token 0 price the `arbitrage` strategy trades the pool towards with the
+    /// `static` price feed
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_strategy_reference_price: Option<f64>,
+    /// Share of the arbitrage value the `arbitrage` strategy bids, defaults to 0.5
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_strategy_bid_fraction: Option<f64>,
+    /// Bid token units the `arbitrage` strategy values a token 1 unit of profit at, defaults
+    /// to 1
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub bid_strategy_bid_per_token_1: Option<f64>,
+    /// Source of the `arbitrage` strategy's reference price, `static` (default), `replay`,
+    /// `http` or `websocket`
+    #[serde(default)]
+    pub price_feed: PriceFeedKind,
+    /// Replay file or ticker url of the price feed
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub price_feed_source: Option<String>,
+    /// Seconds after which a reference price is stale, defaults to 30
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub price_feed_max_age_secs: Option<u64>,
+    /// Seconds between HTTP ticker polls, defaults to 2
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub price_feed_poll_interval_secs: Option<u64>,
+    /// File to persist handed out nonces to, so restarts don't reuse in-flight nonces
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub nonce_store: Option<String>,
+    /// Multiplier applied to the CCR gas limit profiles
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub suave_gas_safety_multiplier: Option<f64>,
+    /// Multiplier applied to L1 gas estimates and profiles
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub l1_gas_safety_multiplier: Option<f64>,
 }
 
 impl AmmAuctionConfig {
@@ -49,4 +187,45 @@ impl AmmAuctionConfig {
         let config: AmmAuctionConfig = envy::from_env().wrap_err("Error parsing .env")?;
         Ok(config)
     }
+
+    /// Writes contract addresses into an env file, replacing the lines of keys that are
+    /// already set and appending the others.
+    pub fn write_addresses(env_file: &str, addresses: &[(&str, Address)]) -> eyre::Result<()> {
+        let contents = fs::read_to_string(env_file).unwrap_or_default();
+        let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+
+        for (key, address) in addresses {
+            let line = format!("{}={}", key, address);
+            let existing = lines.iter_mut().find(|existing| {
+                existing
+                    .split_once('=')
+                    .is_some_and(|(existing_key, _)| existing_key.trim() == *key)
+            });
+            match existing {
+                Some(existing) => *existing = line,
+                None => lines.push(line),
+            }
+        }
+
+        let mut contents = lines.join("\n");
+        contents.push('\n');
+        fs::write(env_file, contents).wrap_err(format!("failed to write {}", env_file))
+    }
+}
+
+/// Reads an optional env var, treating one that's set but empty (`KEY=`) as unset, as the
+/// sample .env leaves every optional key.
+fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)?
+        .as_deref()
+        .map(str::trim)
+    {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(de::Error::custom),
+    }
 }
@@ -0,0 +1,213 @@
+use alloy_primitives::{Address, U256};
+use color_eyre::eyre;
+
+use crate::{
+    pool::u256_to_f64,
+    swap::Swap,
+    v3_math::{simulate_exact_input, TickLiquidity, MAX_SQRT_RATIO, MIN_SQRT_RATIO},
+};
+
+/// The most profitable swap against the pool given a reference price.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrageOpportunity {
+    /// If the swap sells token 0 for token 1
+    pub token_0_in: bool,
+    /// Input including the pool fee
+    pub amount_in: U256,
+    pub amount_out: U256,
+    /// Pool price the swap stops at, where the next unit would no longer be profitable
+    pub sqrt_price_limit_x96: U256,
+    /// Pool tick after the swap
+    pub tick_after: i32,
+    /// Initialized ticks the swap crosses
+    pub ticks_crossed: Vec<i32>,
+    /// Expected profit in token 1's smallest unit, valuing token 0 at the reference price
+    pub profit: U256,
+}
+
+impl ArbitrageOpportunity {
+    /// Highest bid that still leaves the swap profitable, the truthful bid in a second price
+    /// auction. `bid_per_token_1` is the bid token's smallest units per smallest unit of
+    /// token 1.
+    pub fn max_profitable_bid(&self, bid_per_token_1: f64) -> u128 {
+        (u256_to_f64(self.profit) * bid_per_token_1) as u128
+    }
+
+    /// The bid's exact input swap, reverting if the pool moved and less would come out.
+    pub fn swap(&self, token_in: Address, token_out: Address) -> Swap {
+        Swap::exact_input_single(token_in, token_out, self.amount_in, self.amount_out)
+            .with_sqrt_price_limit_x96(self.sqrt_price_limit_x96)
+    }
+}
+
+/// Sizes the swap that moves the pool to `reference_price` (token 1 per token 0, adjusted for
+/// decimals like `PoolState::price_0_in_1`), net of the pool fee.
+///
+/// Selling token 0 pays while the pool's price after the fee is above the reference price,
+/// buying it while the price plus the fee is below, so the swap stops where the marginal unit
+/// breaks even, or at the edge of the pool's loaded tick bitmap words if that comes first.
+/// Returns `None` if the pool is within the fee of the reference price. Gas isn't accounted
+/// for.
+pub fn optimal_arbitrage(
+    pool: &TickLiquidity,
+    reference_price: f64,
+    decimals_0: u8,
+    decimals_1: u8,
+) -> eyre::Result<Option<ArbitrageOpportunity>> {
+    eyre::ensure!(
+        reference_price.is_finite() && reference_price > 0.0,
+        "reference price {} is not positive",
+        reference_price
+    );
+    // token 1 per token 0 in the tokens' smallest units
+    let reference_price =
+        reference_price * 10f64.powi(i32::from(decimals_1) - i32::from(decimals_0));
+    let fee_complement = 1.0 - f64::from(pool.fee) / 1_000_000.0;
+
+    let sqrt_price = u256_to_f64(pool.sqrt_price_x96) / 2f64.powi(96);
+    let pool_price = sqrt_price * sqrt_price;
+    let (token_0_in, target_price) = if pool_price * fee_complement > reference_price {
+        (true, reference_price / fee_complement)
+    } else if pool_price < reference_price * fee_complement {
+        (false, reference_price * fee_complement)
+    } else {
+        return Ok(None);
+    };
+
+    let sqrt_price_limit_x96 = f64_to_u256(target_price.sqrt() * 2f64.powi(96)).clamp(
+        MIN_SQRT_RATIO + U256::from(1),
+        MAX_SQRT_RATIO - U256::from(1),
+    );
+    let on_swap_side = if token_0_in {
+        sqrt_price_limit_x96 < pool.sqrt_price_x96
+    } else {
+        sqrt_price_limit_x96 > pool.sqrt_price_x96
+    };
+    if !on_swap_side {
+        return Ok(None);
+    }
+
+    // swap as much as it takes to reach the limit, then resimulate with exactly that input
+    // for the amount out the pool will give
+    let to_limit = simulate_exact_input(
+        pool,
+        token_0_in,
+        U256::from(u128::MAX),
+        sqrt_price_limit_x96,
+    )?;
+    if to_limit.amount_in.is_zero() {
+        return Ok(None);
+    }
+    let swap = simulate_exact_input(pool, token_0_in, to_limit.amount_in, sqrt_price_limit_x96)?;
+
+    let amount_in = u256_to_f64(swap.amount_in);
+    let amount_out = u256_to_f64(swap.amount_out);
+    let profit = if token_0_in {
+        amount_out - amount_in * reference_price
+    } else {
+        amount_out * reference_price - amount_in
+    };
+    if profit < 1.0 {
+        return Ok(None);
+    }
+
+    Ok(Some(ArbitrageOpportunity {
+        token_0_in,
+        amount_in: swap.amount_in,
+        amount_out: swap.amount_out,
+        sqrt_price_limit_x96,
+        tick_after: swap.tick,
+        ticks_crossed: swap.ticks_crossed,
+        profit: f64_to_u256(profit),
+    }))
+}
+
+/// Converts a non-negative float, truncating any fraction.
+fn f64_to_u256(value: f64) -> U256 {
+    if value < 2f64.powi(127) {
+        U256::from(value as u128)
+    } else {
+        // keep the top bits, the float doesn't have more precision than that anyway
+        let exponent = value.log2().floor() as usize - 100;
+        U256::from((value / 2f64.powi(exponent as i32)) as u128) << exponent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::uint;
+
+    use super::*;
+    use crate::v3_math::tests::pool;
+
+    #[test]
+    fn reference_above_pool_buys_token_0() {
+        // pool at 1, the swap pushes it up to 1.005 * 0.997
+        let opportunity = optimal_arbitrage(&pool(), 1.005, 18, 18).unwrap().unwrap();
+        assert!(!opportunity.token_0_in);
+        assert_eq!(
+            opportunity.sqrt_price_limit_x96,
+            uint!(79306757482142867025425334272_U256)
+        );
+        assert_eq!(opportunity.amount_in, U256::from(994992938920331u64));
+        assert_eq!(opportunity.amount_out, U256::from(991024855558194u64));
+        assert_eq!(opportunity.tick_after, 19);
+        assert!(opportunity.ticks_crossed.is_empty());
+        assert_eq!(opportunity.profit, U256::from(987040915653u64));
+        assert_eq!(opportunity.max_profitable_bid(2.0), 1974081831306);
+        assert_eq!(opportunity.max_profitable_bid(0.5), 493520457826);
+    }
+
+    #[test]
+    fn reference_below_pool_sells_token_0() {
+        let opportunity = optimal_arbitrage(&pool(), 0.995, 18, 18).unwrap().unwrap();
+        assert!(opportunity.token_0_in);
+        assert_eq!(
+            opportunity.sqrt_price_limit_x96,
+            uint!(79148656059200908679970816000_U256)
+        );
+        assert_eq!(opportunity.amount_in, U256::from(1007543224488879u64));
+        assert_eq!(opportunity.amount_out, U256::from(1003512545796003u64));
+        assert_eq!(opportunity.tick_after, -21);
+        assert!(opportunity.ticks_crossed.is_empty());
+        assert_eq!(opportunity.profit, U256::from(1007037429568u64));
+    }
+
+    #[test]
+    fn no_swap_within_the_fee() {
+        for reference_price in [0.999, 1.0, 1.002] {
+            assert_eq!(
+                optimal_arbitrage(&pool(), reference_price, 18, 18).unwrap(),
+                None
+            );
+        }
+        // the same prices scaled by the tokens' decimals
+        assert_eq!(optimal_arbitrage(&pool(), 1e-12, 6, 18).unwrap(), None);
+        assert!(optimal_arbitrage(&pool(), 0.0, 18, 18).is_err());
+    }
+
+    #[test]
+    fn swap_crosses_tick_to_reach_the_limit() {
+        // the limit at 0.98 / 0.997 lies past the concentrated position's lower tick
+        let opportunity = optimal_arbitrage(&pool(), 0.98, 18, 18).unwrap().unwrap();
+        assert!(opportunity.token_0_in);
+        assert_eq!(
+            opportunity.sqrt_price_limit_x96,
+            uint!(78549792554851328652201689088_U256)
+        );
+        assert_eq!(opportunity.ticks_crossed, vec![-60]);
+        assert_eq!(opportunity.tick_after, -172);
+        assert_eq!(opportunity.amount_in, U256::from(5837779171853785u64));
+        assert_eq!(opportunity.amount_out, U256::from(5778793800013687u64));
+        assert_eq!(opportunity.profit, U256::from(57770211596978u64));
+    }
+
+    #[test]
+    fn f64_to_u256_keeps_large_values() {
+        assert_eq!(f64_to_u256(0.0), U256::ZERO);
+        assert_eq!(f64_to_u256(12.9), U256::from(12));
+        assert_eq!(f64_to_u256(2f64.powi(127)), U256::from(1) << 127);
+        assert_eq!(f64_to_u256(2f64.powi(130)), U256::from(1) << 130);
+        assert_eq!(f64_to_u256(1.5 * 2f64.powi(200)), U256::from(3) << 199);
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+
+use alloy::signers::{wallet::LocalWallet, Signer};
+use alloy_primitives::{Address, Signature, B256, U256};
+use alloy_sol_types::{eip712_domain, sol, Eip712Domain, SolCall, SolStruct};
+use color_eyre::{eyre, eyre::Context};
+
+use crate::amm_auction::WithdrawBid;
+
+sol! {
+    /// ERC-5267 domain getter, only exposed by deposits contracts built on newer
+    /// OpenZeppelin `EIP712` versions
+    interface IERC5267 {
+        function eip712Domain() external view returns (
+            bytes1 fields,
+            string name,
+            string version,
+            uint256 chainId,
+            address verifyingContract,
+            bytes32 salt,
+            uint256[] extensions
+        );
+    }
+}
+
+/// Errors from locally checking a `WithdrawBid` signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BidSignatureError {
+    /// The domain bids are signed over isn't the one the deposits contract checks against
+    DomainMismatch { signed: B256, onchain: B256 },
+    /// The signature couldn't be recovered
+    Unrecoverable(String),
+    /// The signature recovers to a different address than the bidder, `withdrawBid()`
+    /// would revert with `WrongSigner`
+    WrongSigner { bidder: Address, recovered: Address },
+}
+
+impl fmt::Display for BidSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BidSignatureError::DomainMismatch { signed, onchain } => write!(
+                f,
+                "bids are signed over domain separator {} but the deposits contract uses {}",
+                signed, onchain
+            ),
+            BidSignatureError::Unrecoverable(e) => {
+                write!(f, "failed to recover bid signature: {}", e)
+            }
+            BidSignatureError::WrongSigner { bidder, recovered } => write!(
+                f,
+                "bid signature recovers to {} instead of bidder {}",
+                recovered, bidder
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BidSignatureError {}
+
+/// A signed `WithdrawBid` ready to be put into a bid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedBid {
+    pub request: WithdrawBid,
+    pub signing_hash: B256,
+    pub signature: Signature,
+}
+
+impl SignedBid {
+    /// The `v` value as `ecrecover` expects it.
+    pub fn v(&self) -> u8 {
+        self.signature.v().y_parity_byte() + 27
+    }
+
+    pub fn r(&self) -> B256 {
+        self.signature.r().into()
+    }
+
+    pub fn s(&self) -> B256 {
+        self.signature.s().into()
+    }
+}
+
+/// Signs and verifies the `WithdrawBid` EIP-712 messages `AuctionDeposits` checks when
+/// collecting a winning bid.
+///
+/// Every signature is recovered from the `v`, `r` and `s` put into the bid over the signing
+/// domain, `AuctionDeposits`' on `CHAIN_ID_L1` unless told otherwise, and checked against the
+/// bidder. If the deposits contract reports its domain through `eip712Domain()` the signing
+/// domain is also checked against it, `AuctionDeposits` built on OpenZeppelin 4.0 doesn't.
+#[derive(Debug, Clone)]
+pub struct BidSigner {
+    domain: Eip712Domain,
+    /// Domain the deposits contract reported, if it has `eip712Domain()`
+    onchain_domain: Option<Eip712Domain>,
+}
+
+impl BidSigner {
+    /// Uses `AuctionDeposits`' domain on the given L1 chain.
+    pub fn new(chain_id_l1: u64, deposit_contract: Address) -> Self {
+        Self::from_domain(eip712_domain!(
+            name: "AuctionDeposits",
+            version: "v1",
+            chain_id: chain_id_l1,
+            verifying_contract: deposit_contract,
+        ))
+    }
+
+    pub fn from_domain(domain: Eip712Domain) -> Self {
+        Self {
+            domain,
+            onchain_domain: None,
+        }
+    }
+
+    /// Signs over and checks against the domain the deposits contract reports, see
+    /// `decode_eip712_domain()`.
+    pub fn from_eip712_domain_return(return_data: &[u8]) -> eyre::Result<Self> {
+        let domain = Self::decode_eip712_domain(return_data)?;
+        Ok(Self::from_domain(domain.clone()).with_onchain_domain(domain))
+    }
+
+    /// Checks bids against `onchain_domain`, the domain the deposits contract reported.
+    pub fn with_onchain_domain(mut self, onchain_domain: Eip712Domain) -> Self {
+        self.onchain_domain = Some(onchain_domain);
+        self
+    }
+
+    /// Decodes the return data of an `eip712Domain()` call on the deposits contract, keeping
+    /// only the fields its `fields` bitmap marks as used.
+    pub fn decode_eip712_domain(return_data: &[u8]) -> eyre::Result<Eip712Domain> {
+        let domain = IERC5267::eip712DomainCall::abi_decode_returns(return_data, true)
+            .context("failed to decode eip712Domain() return data")?;
+        eyre::ensure!(
+            domain.extensions.is_empty(),
+            "eip712Domain() lists extensions {:?}, which aren't supported",
+            domain.extensions
+        );
+
+        // ERC-5267: bit 0 name, 1 version, 2 chainId, 3 verifyingContract, 4 salt
+        let fields = domain.fields[0];
+        let used = |bit: u8| fields & (1 << bit) != 0;
+        Ok(Eip712Domain::new(
+            used(0).then(|| domain.name.into()),
+            used(1).then(|| domain.version.into()),
+            used(2).then_some(domain.chainId),
+            used(3).then_some(domain.verifyingContract),
+            used(4).then_some(domain.salt),
+        ))
+    }
+
+    /// Calldata for querying the deposits contract's domain.
+    pub fn eip712_domain_calldata() -> Vec<u8> {
+        IERC5267::eip712DomainCall {}.abi_encode()
+    }
+
+    pub fn domain(&self) -> &Eip712Domain {
+        &self.domain
+    }
+
+    pub fn onchain_domain(&self) -> Option<&Eip712Domain> {
+        self.onchain_domain.as_ref()
+    }
+
+    /// The EIP-712 hash the bidder signs, for external tooling to verify against.
+    pub fn signing_hash(&self, bidder: Address, block_number: u128, amount: u128) -> B256 {
+        withdraw_bid(bidder, block_number, amount).eip712_signing_hash(&self.domain)
+    }
+
+    /// Signs a bid and checks the signature with `verify()`.
+    pub async fn sign(
+        &self,
+        bidder: &LocalWallet,
+        block_number: u128,
+        amount: u128,
+    ) -> eyre::Result<SignedBid> {
+        let request = withdraw_bid(bidder.address(), block_number, amount);
+        let signing_hash = request.eip712_signing_hash(&self.domain);
+        let signature = bidder
+            .sign_hash(&signing_hash)
+            .await
+            .context("failed to sign bid EIP712 hash")?;
+
+        let signed_bid = SignedBid {
+            request,
+            signing_hash,
+            signature,
+        };
+        self.verify(&signed_bid)
+            .context("bid signature failed verification")?;
+        Ok(signed_bid)
+    }
+
+    /// Recovers the signer of a bid from its `v`, `r` and `s` over the signing domain and
+    /// checks it is the bidder, as `withdrawBid()` does. The signing domain has to be the
+    /// deposits contract's if it reported one.
+    pub fn verify(&self, signed_bid: &SignedBid) -> Result<(), BidSignatureError> {
+        if let Some(onchain_domain) = &self.onchain_domain {
+            if self.domain.separator() != onchain_domain.separator() {
+                return Err(BidSignatureError::DomainMismatch {
+                    signed: self.domain.separator(),
+                    onchain: onchain_domain.separator(),
+                });
+            }
+        }
+
+        // what `ecrecover` gets in `withdrawBid()`
+        let signature = Signature::from_rs_and_parity(
+            U256::from_be_bytes(signed_bid.r().0),
+            U256::from_be_bytes(signed_bid.s().0),
+            u64::from(signed_bid.v()),
+        )
+        .map_err(|e| BidSignatureError::Unrecoverable(e.to_string()))?;
+        let signing_hash = signed_bid.request.eip712_signing_hash(&self.domain);
+        let recovered = signature
+            .recover_address_from_prehash(&signing_hash)
+            .map_err(|e| BidSignatureError::Unrecoverable(e.to_string()))?;
+        if recovered != signed_bid.request.bidder {
+            return Err(BidSignatureError::WrongSigner {
+                bidder: signed_bid.request.bidder,
+                recovered,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn withdraw_bid(bidder: Address, block_number: u128, amount: u128) -> WithdrawBid {
+    WithdrawBid {
+        bidder,
+        blockNumber: U256::from(block_number),
+        amount: U256::from(amount),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Anvil's first dev key.
+    const BIDDER_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    #[tokio::test]
+    async fn signatures_are_recovered_without_an_onchain_domain() {
+        let bidder: LocalWallet = BIDDER_KEY.parse().unwrap();
+        let bid_signer = BidSigner::new(17000, Address::repeat_byte(3));
+        let signed_bid = bid_signer.sign(&bidder, 101, 1000).await.unwrap();
+        assert_eq!(signed_bid.request.bidder, bidder.address());
+
+        let mut forged = signed_bid.clone();
+        forged.request.amount = U256::from(1);
+        assert!(matches!(
+            bid_signer.verify(&forged),
+            Err(BidSignatureError::WrongSigner { bidder: b, .. }) if b == bidder.address()
+        ));
+    }
+
+    #[tokio::test]
+    async fn signing_domain_is_checked_against_the_onchain_domain() {
+        let bidder: LocalWallet = BIDDER_KEY.parse().unwrap();
+        let deposits = Address::repeat_byte(3);
+        let bid_signer = BidSigner::new(17000, deposits)
+            .with_onchain_domain(BidSigner::new(1, deposits).domain().clone());
+        let e = bid_signer.sign(&bidder, 101, 1000).await.unwrap_err();
+        assert!(matches!(
+            e.downcast_ref::<BidSignatureError>(),
+            Some(BidSignatureError::DomainMismatch { .. })
+        ));
+    }
+}
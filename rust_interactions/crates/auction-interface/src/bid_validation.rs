@@ -0,0 +1,104 @@
+use std::fmt;
+
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{Revert, SolError};
+
+use crate::guard::GuardRevert;
+
+/// A precondition a bid failed before being sent to the suapp.
+///
+/// The suapp drops bids that fail these without reporting anything back, so they're
+/// checked up front.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BidRejection {
+    /// The bidder's `AuctionDeposits` balance can't cover the bid
+    InsufficientDeposit { deposit: U256, bid_amount: U256 },
+    /// The target block was already produced
+    StaleTargetBlock {
+        target_block: u64,
+        latest_block: u64,
+    },
+    /// The inner swap reverts even with the auction won
+    SwapReverts { reason: String },
+}
+
+impl BidRejection {
+    /// Rejection for a swap simulation that reverted with `data`.
+    pub fn swap_reverts(data: &[u8]) -> Self {
+        let reason = if let Ok(revert) = Revert::abi_decode(data, true) {
+            revert.reason
+        } else {
+            match GuardRevert::decode(data) {
+                GuardRevert::Unknown(data) if data.is_empty() => "no revert data".to_string(),
+                GuardRevert::Unknown(data) => data.to_string(),
+                revert => format!("{:?}", revert),
+            }
+        };
+        BidRejection::SwapReverts { reason }
+    }
+}
+
+impl fmt::Display for BidRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BidRejection::InsufficientDeposit {
+                deposit,
+                bid_amount,
+            } => write!(
+                f,
+                "deposit of {} does not cover bid of {}",
+                deposit, bid_amount
+            ),
+            BidRejection::StaleTargetBlock {
+                target_block,
+                latest_block,
+            } => write!(
+                f,
+                "target block {} is not after the latest block {}",
+                target_block, latest_block
+            ),
+            BidRejection::SwapReverts { reason } => write!(f, "swap reverts: {}", reason),
+        }
+    }
+}
+
+/// Everything wrong with a bid, returned as the error of a rejected `new_bid()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BidRejectionReport {
+    pub bidder: Address,
+    pub target_block: u64,
+    pub bid_amount: U256,
+    pub rejections: Vec<BidRejection>,
+}
+
+impl BidRejectionReport {
+    pub fn new(bidder: Address, target_block: u64, bid_amount: U256) -> Self {
+        Self {
+            bidder,
+            target_block,
+            bid_amount,
+            rejections: Vec::new(),
+        }
+    }
+
+    /// If the bid passed every check.
+    pub fn is_empty(&self) -> bool {
+        self.rejections.is_empty()
+    }
+}
+
+impl fmt::Display for BidRejectionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bid of {} by {} for block {} rejected",
+            self.bid_amount, self.bidder, self.target_block
+        )?;
+        for rejection in &self.rejections {
+            write!(f, "\n  - {}", rejection)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BidRejectionReport {}
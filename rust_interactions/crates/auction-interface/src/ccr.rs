@@ -0,0 +1,217 @@
+use std::fmt;
+
+use alloy::transports::{RpcError, TransportError};
+use alloy_primitives::{hex, Address, Bytes, FixedBytes, B256, U64};
+use alloy_sol_types::{SolError, SolEvent};
+use serde::Deserialize;
+
+use crate::amm_auction::IAuctionSuapp;
+
+/// Whether a CCR's callback transaction succeeded on Suave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CcrStatus {
+    Success,
+    Reverted,
+}
+
+/// Events emitted by `AuctionSuapp.sol`'s callbacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuappEvent {
+    /// A bid was stored, `salted_return` is `keccak256(abi.encode(msg.sender, salt))`
+    NewBid {
+        salted_return: B256,
+        bid_id: FixedBytes<16>,
+    },
+    /// A non-bid swap was stored
+    NonBidTxnId {
+        sender: Address,
+        txn_id: FixedBytes<16>,
+    },
+    /// The suapp's signing key record was updated
+    UpdateKey { new_key: FixedBytes<16> },
+}
+
+impl SuappEvent {
+    /// Decodes a suapp log, returns `None` for logs that aren't suapp events.
+    pub fn decode(topics: &[B256], data: &[u8]) -> Option<Self> {
+        match topics.first()? {
+            topic if *topic == IAuctionSuapp::NewBid::SIGNATURE_HASH => {
+                let event =
+                    IAuctionSuapp::NewBid::decode_raw_log(topics.iter().copied(), data, true)
+                        .ok()?;
+                Some(SuappEvent::NewBid {
+                    salted_return: event.saltedReturn,
+                    bid_id: event.bidId,
+                })
+            }
+            topic if *topic == IAuctionSuapp::NonBidTxnId::SIGNATURE_HASH => {
+                let event =
+                    IAuctionSuapp::NonBidTxnId::decode_raw_log(topics.iter().copied(), data, true)
+                        .ok()?;
+                Some(SuappEvent::NonBidTxnId {
+                    sender: event.sender,
+                    txn_id: event.txnId,
+                })
+            }
+            topic if *topic == IAuctionSuapp::UpdateKey::SIGNATURE_HASH => {
+                let event =
+                    IAuctionSuapp::UpdateKey::decode_raw_log(topics.iter().copied(), data, true)
+                        .ok()?;
+                Some(SuappEvent::UpdateKey {
+                    new_key: event.newKey,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Custom errors declared in `AuctionSuapp.sol`, returned when a CCR's confidential
+/// execution reverts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuappRevert {
+    OnlyOwner,
+    StaleBid,
+    AuctionNotEnded,
+    AuctionAlreadyRan,
+    /// Revert data that doesn't match any of the suapp's errors
+    Unknown(Bytes),
+}
+
+impl SuappRevert {
+    /// Maps revert data onto the suapp's errors.
+    pub fn decode(data: &[u8]) -> Self {
+        match data.get(..4) {
+            Some(selector) if selector == IAuctionSuapp::OnlyOwner::SELECTOR => {
+                SuappRevert::OnlyOwner
+            }
+            Some(selector) if selector == IAuctionSuapp::StaleBid::SELECTOR => {
+                SuappRevert::StaleBid
+            }
+            Some(selector) if selector == IAuctionSuapp::AuctionNotEnded::SELECTOR => {
+                SuappRevert::AuctionNotEnded
+            }
+            Some(selector) if selector == IAuctionSuapp::AuctionAlreadyRan::SELECTOR => {
+                SuappRevert::AuctionAlreadyRan
+            }
+            _ => SuappRevert::Unknown(Bytes::copy_from_slice(data)),
+        }
+    }
+
+    /// Pulls the revert data out of a node's error response to a CCR, if there is any.
+    pub fn from_transport_error(err: &TransportError) -> Option<Self> {
+        revert_data(err).map(|data| SuappRevert::decode(&data))
+    }
+}
+
+/// Pulls the revert data out of a node's error response to a call or transaction.
+pub(crate) fn revert_data(err: &TransportError) -> Option<Bytes> {
+    let RpcError::ErrorResp(payload) = err else {
+        return None;
+    };
+
+    if let Some(data) = &payload.data {
+        if let Ok(data) = serde_json::from_str::<Bytes>(data.get()) {
+            return Some(data);
+        }
+    }
+
+    // kettles report confidential execution reverts in the message,
+    // e.g. "execution reverted: 0x...", other errors can hold unrelated hex like addresses
+    let reason = payload.message.strip_prefix("execution reverted")?;
+    reason
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix("0x"))
+        .find_map(|data| hex::decode(data).ok())
+        .map(Bytes::from)
+}
+
+impl fmt::Display for SuappRevert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuappRevert::OnlyOwner => write!(f, "suapp reverted: OnlyOwner"),
+            SuappRevert::StaleBid => write!(f, "suapp reverted: StaleBid"),
+            SuappRevert::AuctionNotEnded => write!(f, "suapp reverted: AuctionNotEnded"),
+            SuappRevert::AuctionAlreadyRan => write!(f, "suapp reverted: AuctionAlreadyRan"),
+            SuappRevert::Unknown(data) => write!(f, "suapp reverted with unknown data: {}", data),
+        }
+    }
+}
+
+impl std::error::Error for SuappRevert {}
+
+/// Result of a CCR that made it on chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CcrOutcome {
+    pub tx_hash: B256,
+    pub block_number: Option<u64>,
+    pub status: CcrStatus,
+    /// Suapp events emitted by the CCR's callback
+    pub events: Vec<SuappEvent>,
+}
+
+impl CcrOutcome {
+    /// The stored bid's id if this was an accepted `newBid` CCR.
+    pub fn bid_id(&self) -> Option<FixedBytes<16>> {
+        self.events.iter().find_map(|event| match event {
+            SuappEvent::NewBid { bid_id, .. } => Some(*bid_id),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for CcrOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ccr {} ({:?}", self.tx_hash, self.status)?;
+        if let Some(block_number) = self.block_number {
+            write!(f, " in suave block {}", block_number)?;
+        }
+        write!(f, ")")?;
+        for event in &self.events {
+            write!(f, " {:?}", event)?;
+        }
+        Ok(())
+    }
+}
+
+/// Subset of a Suave transaction receipt, Suave's transaction types aren't known to alloy's
+/// Ethereum receipt types.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SuaveReceipt {
+    pub(crate) status: Option<U64>,
+    pub(crate) block_number: Option<U64>,
+    pub(crate) logs: Vec<SuaveLog>,
+}
+
+/// Subset of a log in a Suave transaction receipt.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SuaveLog {
+    pub(crate) address: Address,
+    pub(crate) topics: Vec<B256>,
+    pub(crate) data: Bytes,
+}
+
+impl SuaveReceipt {
+    /// Builds the outcome from the receipt, keeping only the logs emitted by `suapp`.
+    pub(crate) fn into_outcome(self, tx_hash: B256, suapp: Address) -> CcrOutcome {
+        let status = if self.status == Some(U64::from(1)) {
+            CcrStatus::Success
+        } else {
+            CcrStatus::Reverted
+        };
+        let events = self
+            .logs
+            .iter()
+            .filter(|log| log.address == suapp)
+            .filter_map(|log| SuappEvent::decode(&log.topics, &log.data))
+            .collect();
+
+        CcrOutcome {
+            tx_hash,
+            block_number: self.block_number.map(|number| number.to::<u64>()),
+            status,
+            events,
+        }
+    }
+}
@@ -0,0 +1,844 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    providers::Provider,
+    signers::{wallet::LocalWallet, Signer},
+    sol,
+};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::{SolCall, SolValue};
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accounts::{AccountRegistry, AccountRole},
+    amm_auction::{view, IAuctionDeposits},
+    amm_auction_config::AmmAuctionConfig,
+    gas::GasPolicy,
+    l1_client::L1Client,
+    pool::{IUniswapV3FactoryAuctioned, IUniswapV3PoolAuctioned},
+    swap::{DEFAULT_DEADLINE_OFFSET_SECS, DEFAULT_FEE_TIER},
+};
+
+sol! {
+    interface IERC20Mintable {
+        function mint(address to, uint256 amount) external;
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+
+    interface INonfungiblePositionManager {
+        struct MintParams {
+            address token0;
+            address token1;
+            uint24 fee;
+            int24 tickLower;
+            int24 tickUpper;
+            uint256 amount0Desired;
+            uint256 amount1Desired;
+            uint256 amount0Min;
+            uint256 amount1Min;
+            address recipient;
+            uint256 deadline;
+        }
+
+        function mint(MintParams calldata params) external payable returns (uint256 tokenId, uint128 liquidity, uint256 amount0, uint256 amount1);
+    }
+}
+
+/// Largest tick a Uniswap V3 pool supports, liquidity is provided over the whole range.
+const MAX_TICK: i32 = 887272;
+/// Observations the pool keeps so oracle reads work right after deployment.
+const OBSERVATION_CARDINALITY: u16 = 10;
+/// Gas limit `AuctionSuapp.sol` uses for the `postAuctionResults()` transaction.
+pub(crate) const GAS_NEEDED_POST_AUCTION_RESULTS: u64 = 2_000_000;
+
+/// Env keys the deployed contracts are written to, by their key in the deployment state.
+const ENV_KEYS: [(&str, &str); 6] = [
+    ("suapp_amm", "SUAPP_AMM"),
+    ("auction_deposits", "AUCTION_DEPOSITS"),
+    ("auction_guard", "AUCTION_GUARD"),
+    ("swap_router", "SWAP_ROUTER"),
+    ("pool", "POOL"),
+    ("factory", "FACTORY"),
+];
+
+/// Subset of a forge build artifact.
+#[derive(Deserialize)]
+struct ForgeArtifact {
+    bytecode: ForgeBytecode,
+}
+
+#[derive(Deserialize)]
+struct ForgeBytecode {
+    object: String,
+}
+
+/// Compiled contracts in forge's `out` directory.
+pub struct ForgeArtifacts {
+    out_dir: PathBuf,
+}
+
+impl ForgeArtifacts {
+    pub fn new(out_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            out_dir: out_dir.into(),
+        }
+    }
+
+    /// Creation bytecode of `contract`, compiled from the source file named `file`.
+    pub fn bytecode(&self, file: &str, contract: &str) -> eyre::Result<Bytes> {
+        let path = self.out_dir.join(file).join(format!("{}.json", contract));
+        let contents = fs::read_to_string(&path).context(format!(
+            "failed to read artifact {}, run `forge build` first",
+            path.display()
+        ))?;
+        let artifact: ForgeArtifact = serde_json::from_str(&contents)
+            .context(format!("failed to parse artifact {}", path.display()))?;
+        eyre::ensure!(
+            !artifact.bytecode.object.contains("__$"),
+            "{} needs linked libraries, which aren't supported",
+            contract
+        );
+        artifact
+            .bytecode
+            .object
+            .parse()
+            .context(format!("failed to decode {}'s bytecode", contract))
+    }
+
+    /// Creation bytecode followed by the abi encoded constructor args.
+    pub fn creation_code(
+        &self,
+        file: &str,
+        contract: &str,
+        constructor_args: &[u8],
+    ) -> eyre::Result<Bytes> {
+        let mut code = self.bytecode(file, contract)?.to_vec();
+        code.extend_from_slice(constructor_args);
+        Ok(code.into())
+    }
+}
+
+/// A deployment that was sent but hadn't landed when the state was last saved.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PendingContract {
+    pub tx_hash: B256,
+    /// Where the contract is created, from the sender and the deployment's nonce
+    pub address: Address,
+}
+
+/// Progress of a deployment, saved after every step so a failed run can be resumed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DeploymentState {
+    /// L1 chain the contracts are deployed to, resuming against another chain is refused
+    pub chain_id_l1: Option<u64>,
+    /// Deployed contracts by name
+    pub contracts: BTreeMap<String, Address>,
+    /// Deployments sent but not seen landing yet by name, checked before deploying again
+    #[serde(default)]
+    pub pending: BTreeMap<String, PendingContract>,
+    /// Steps that don't deploy a contract and already went through
+    pub completed: BTreeSet<String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl DeploymentState {
+    /// Loads the state saved at `path`, starting a new deployment if there is none.
+    pub fn load(path: impl Into<PathBuf>) -> eyre::Result<Self> {
+        let path = path.into();
+        let mut state = if path.exists() {
+            let contents = fs::read_to_string(&path).context(format!(
+                "failed to read deployment state {}",
+                path.display()
+            ))?;
+            serde_json::from_str(&contents).context(format!(
+                "failed to parse deployment state {}",
+                path.display()
+            ))?
+        } else {
+            DeploymentState::default()
+        };
+        state.path = path;
+        Ok(state)
+    }
+
+    /// Starts a new deployment saved at `path`, discarding any saved progress.
+    pub fn fresh(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn contract(&self, name: &str) -> Option<Address> {
+        self.contracts.get(name).copied()
+    }
+
+    fn record_contract(&mut self, name: &str, address: Address) -> eyre::Result<()> {
+        self.pending.remove(name);
+        self.contracts.insert(name.to_string(), address);
+        self.save()
+    }
+
+    fn record_pending(&mut self, name: &str, pending: PendingContract) -> eyre::Result<()> {
+        self.pending.insert(name.to_string(), pending);
+        self.save()
+    }
+
+    fn is_done(&self, step: &str) -> bool {
+        self.completed.contains(step)
+    }
+
+    fn mark_done(&mut self, step: &str) -> eyre::Result<()> {
+        self.completed.insert(step.to_string());
+        self.save()
+    }
+
+    fn save(&self) -> eyre::Result<()> {
+        let contents =
+            serde_json::to_string_pretty(self).context("failed to serialize deployment state")?;
+        fs::write(&self.path, contents).context(format!(
+            "failed to write deployment state {}",
+            self.path.display()
+        ))
+    }
+
+    /// The pool's tokens as `(token_0, token_1)`, sorted like the pool sorts them.
+    pub fn tokens(&self) -> Option<(Address, Address)> {
+        let token_a = self.contract("token_a")?;
+        let token_b = self.contract("token_b")?;
+        Some(if token_a < token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        })
+    }
+
+    /// The deployed addresses `AmmAuctionConfig` reads, by env key.
+    pub fn env_addresses(&self) -> Vec<(&'static str, Address)> {
+        let mut addresses: Vec<(&'static str, Address)> = ENV_KEYS
+            .iter()
+            .filter_map(|(name, key)| self.contract(name).map(|address| (*key, address)))
+            .collect();
+        if let Some((token_0, token_1)) = self.tokens() {
+            addresses.push(("TOKEN_0", token_0));
+            addresses.push(("TOKEN_1", token_1));
+        }
+        addresses
+    }
+}
+
+/// Optional parts of a deployment.
+#[derive(Debug, Clone)]
+pub struct DeployOptions {
+    /// Fee tier of the auctioned pool
+    pub pool_fee: u32,
+    /// Add liquidity and give the funded accounts tokens approved for the swap router
+    pub init_pool_state: bool,
+    /// Tokens of each kind minted for the liquidity and to each funded account
+    pub token_amount: U256,
+    /// Amount each funded bidder deposits into `AuctionDeposits`
+    pub bid_deposit: Option<U256>,
+}
+
+impl Default for DeployOptions {
+    fn default() -> Self {
+        Self {
+            pool_fee: DEFAULT_FEE_TIER,
+            init_pool_state: false,
+            // 10 ether
+            token_amount: U256::from(10_000_000_000_000_000_000u128),
+            bid_deposit: None,
+        }
+    }
+}
+
+/// A bidder or swapper the deployment gives tokens or deposits for.
+pub struct FundedAccount {
+    pub name: String,
+    pub role: AccountRole,
+    pub l1: L1Client,
+}
+
+/// Deploys and wires together the auctioned Uniswap pool, `AuctionGuard.sol`,
+/// `AuctionDeposits.sol` and `AuctionSuapp.sol` from forge's build artifacts.
+///
+/// Mirrors `Deployments.s.sol`'s `freshL1Contracts()` and `deploySuaveAMMAuction()`. Every
+/// step is recorded in the `DeploymentState` as it completes, so rerunning after a failure
+/// skips what's already deployed.
+pub struct Deployer {
+    artifacts: ForgeArtifacts,
+    /// Deploys the L1 contracts and becomes their owner/admin
+    admin: L1Client,
+    /// Deploys the suapp on Suave
+    suave: L1Client,
+    suapp_signer: Address,
+    chain_id_l1: u64,
+    funded_accounts: Vec<FundedAccount>,
+    options: DeployOptions,
+    state: DeploymentState,
+}
+
+impl Deployer {
+    pub fn new(
+        artifacts: ForgeArtifacts,
+        admin: L1Client,
+        suave: L1Client,
+        suapp_signer: Address,
+        chain_id_l1: u64,
+        state: DeploymentState,
+    ) -> eyre::Result<Self> {
+        if let Some(state_chain_id) = state.chain_id_l1 {
+            eyre::ensure!(
+                state_chain_id == chain_id_l1,
+                "deployment state {} is for chain {} but CHAIN_ID_L1 is {}, start a fresh \
+                 deployment instead",
+                state.path().display(),
+                state_chain_id,
+                chain_id_l1
+            );
+        }
+
+        Ok(Self {
+            artifacts,
+            admin,
+            suave,
+            suapp_signer,
+            chain_id_l1,
+            funded_accounts: Vec::new(),
+            options: DeployOptions::default(),
+            state,
+        })
+    }
+
+    /// Builds a deployer from the .env's accounts and rpcs, deploying the L1 contracts from
+    /// the `admin` account (the first admin if not given) and funding every bidder and
+    /// swapper.
+    pub fn from_config(
+        config: &AmmAuctionConfig,
+        artifacts: ForgeArtifacts,
+        admin: Option<&str>,
+        state: DeploymentState,
+    ) -> eyre::Result<Self> {
+        let accounts = AccountRegistry::from_config(config)?;
+
+        let mut l1_gas = GasPolicy::l1_default();
+        if let Some(multiplier) = config.l1_gas_safety_multiplier {
+            l1_gas = l1_gas.with_safety_multiplier(multiplier);
+        }
+        let l1_client = |wallet: &LocalWallet| -> eyre::Result<L1Client> {
+            Ok(L1Client::new(&config.rpc_url_l1, wallet.clone())
+                .context("failed to build L1 client")?
+                .with_gas_policy(l1_gas.clone()))
+        };
+
+        let admin_l1 = l1_client(
+            accounts
+                .resolve(admin, AccountRole::Admin)
+                .context("failed to resolve admin")?,
+        )?;
+        let suave_rpc = if config.use_local {
+            &config.rpc_url_suave_local
+        } else {
+            &config.rpc_url_suave
+        };
+        let suave_signer = accounts
+            .resolve(
+                config.suave_signer_account.as_deref(),
+                AccountRole::SuaveSigner,
+            )
+            .context("failed to resolve suave signer")?;
+        let suave = L1Client::new(suave_rpc, suave_signer.clone())
+            .context("failed to build Suave client")?;
+        let suapp_signer = accounts
+            .resolve(
+                config.suapp_signer_account.as_deref(),
+                AccountRole::SuappSigner,
+            )
+            .context("failed to resolve suapp signer")?;
+
+        let mut deployer = Self::new(
+            artifacts,
+            admin_l1,
+            suave,
+            suapp_signer.address(),
+            config.chain_id_l1,
+            state,
+        )?;
+        for role in [AccountRole::Bidder, AccountRole::Swapper] {
+            for name in accounts.names_with_role(role) {
+                let l1 = l1_client(accounts.wallet(&name, role)?)?;
+                deployer = deployer.with_funded_account(FundedAccount { name, role, l1 });
+            }
+        }
+        Ok(deployer)
+    }
+
+    pub fn with_options(mut self, options: DeployOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds a bidder or swapper to fund when initializing the pool state.
+    pub fn with_funded_account(mut self, account: FundedAccount) -> Self {
+        self.funded_accounts.push(account);
+        self
+    }
+
+    /// Runs all steps that aren't done yet, returning the deployment's final state.
+    pub async fn run(mut self) -> eyre::Result<DeploymentState> {
+        let l1_chain_id = self
+            .admin
+            .provider()
+            .get_chain_id()
+            .await
+            .context("failed to get L1 chain id")?;
+        eyre::ensure!(
+            l1_chain_id == self.chain_id_l1,
+            "CHAIN_ID_L1 is {} but the L1 rpc is on chain {}",
+            self.chain_id_l1,
+            l1_chain_id
+        );
+        self.state.chain_id_l1 = Some(self.chain_id_l1);
+        self.state.save()?;
+
+        // auction contracts
+        let auction_deposits = self
+            .deploy_l1(
+                "auction_deposits",
+                "AuctionDeposits.sol",
+                "AuctionDeposits",
+                &[],
+            )
+            .await?;
+        let auction_guard = self
+            .deploy_l1(
+                "auction_guard",
+                "AuctionGuard.sol",
+                "AuctionGuard",
+                &(auction_deposits, self.suapp_signer).abi_encode_params(),
+            )
+            .await?;
+        send_step(
+            &mut self.state,
+            "set_auction_guard",
+            &self.admin,
+            auction_deposits,
+            IAuctionDeposits::setAuctionGuardCall {
+                auctionGuard_: auction_guard,
+            },
+            U256::ZERO,
+        )
+        .await?;
+
+        // tokens
+        for (name, symbol) in [("token_a", "A"), ("token_b", "B"), ("weth", "WETH")] {
+            self.deploy_l1(
+                name,
+                "ERC20Mintable.sol",
+                "ERC20Mintable",
+                &(symbol.to_string(), symbol.to_string()).abi_encode_params(),
+            )
+            .await?;
+        }
+        let (token_0, token_1) = self.state.tokens().context("tokens not deployed")?;
+        let weth = self.state.contract("weth").context("weth not deployed")?;
+
+        // auctioned uniswap contracts
+        let factory = self
+            .deploy_l1(
+                "factory",
+                "UniswapV3FactoryAuctioned.sol",
+                "UniswapV3FactoryAuctioned",
+                &[],
+            )
+            .await?;
+        let pool = self
+            .create_pool(factory, token_0, token_1, auction_guard)
+            .await?;
+        send_step(
+            &mut self.state,
+            "initialize_pool",
+            &self.admin,
+            pool,
+            // price of 1, tick 0
+            IUniswapV3PoolAuctioned::initializeCall {
+                sqrtPriceX96: U256::from(1) << 96,
+            },
+            U256::ZERO,
+        )
+        .await?;
+        send_step(
+            &mut self.state,
+            "increase_observation_cardinality",
+            &self.admin,
+            pool,
+            IUniswapV3PoolAuctioned::increaseObservationCardinalityNextCall {
+                observationCardinalityNext: OBSERVATION_CARDINALITY,
+            },
+            U256::ZERO,
+        )
+        .await?;
+        let position_manager = self
+            .deploy_l1(
+                "position_manager",
+                "NonfungiblePositionManager.sol",
+                "NonfungiblePositionManager",
+                &(factory, weth, "Test token descriptor".to_string(), pool).abi_encode_params(),
+            )
+            .await?;
+        let swap_router = self
+            .deploy_l1(
+                "swap_router",
+                "SwapRouter.sol",
+                "SwapRouter",
+                &(factory, weth, pool).abi_encode_params(),
+            )
+            .await?;
+
+        // suapp
+        if self.state.contract("suapp_amm").is_none() {
+            let creation_code = self.artifacts.creation_code(
+                "AuctionSuapp.sol",
+                "AuctionSuapp",
+                &(
+                    auction_deposits,
+                    auction_guard,
+                    U256::from(self.chain_id_l1),
+                    U256::from(GAS_NEEDED_POST_AUCTION_RESULTS),
+                )
+                    .abi_encode_params(),
+            )?;
+            deploy(
+                &mut self.state,
+                "suapp_amm",
+                &self.suave,
+                "AuctionSuapp",
+                creation_code,
+            )
+            .await?;
+        }
+
+        if self.options.init_pool_state {
+            self.add_liquidity(pool, position_manager, token_0, token_1)
+                .await?;
+            for account in &self.funded_accounts {
+                for (token_name, token) in [("token_0", token_0), ("token_1", token_1)] {
+                    fund_and_approve(
+                        &mut self.state,
+                        &self.admin,
+                        &account.name,
+                        &account.l1,
+                        token_name,
+                        token,
+                        "swap_router",
+                        swap_router,
+                        self.options.token_amount,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        if let Some(bid_deposit) = self.options.bid_deposit {
+            for account in &self.funded_accounts {
+                if account.role != AccountRole::Bidder {
+                    continue;
+                }
+                send_step(
+                    &mut self.state,
+                    &format!("deposit for {}", account.name),
+                    &account.l1,
+                    auction_deposits,
+                    IAuctionDeposits::depositCall {},
+                    bid_deposit,
+                )
+                .await?;
+            }
+        }
+
+        Ok(self.state)
+    }
+
+    /// Deploys an L1 contract from the admin unless it's already deployed.
+    async fn deploy_l1(
+        &mut self,
+        name: &str,
+        file: &str,
+        contract: &str,
+        constructor_args: &[u8],
+    ) -> eyre::Result<Address> {
+        if let Some(address) = self.state.contract(name) {
+            println!("{} already deployed at {}", name, address);
+            return Ok(address);
+        }
+        let creation_code = self
+            .artifacts
+            .creation_code(file, contract, constructor_args)?;
+        deploy(&mut self.state, name, &self.admin, contract, creation_code).await
+    }
+
+    /// Creates the auctioned pool, reusing it if the factory already has one for the tokens.
+    async fn create_pool(
+        &mut self,
+        factory: Address,
+        token_0: Address,
+        token_1: Address,
+        auction_guard: Address,
+    ) -> eyre::Result<Address> {
+        if let Some(pool) = self.state.contract("pool") {
+            println!("pool already deployed at {}", pool);
+            return Ok(pool);
+        }
+
+        let get_pool = IUniswapV3FactoryAuctioned::getPoolCall {
+            tokenA: token_0,
+            tokenB: token_1,
+            fee: self.options.pool_fee,
+        };
+        let mut pool = view(
+            self.admin.provider(),
+            factory,
+            get_pool.clone(),
+            BlockId::latest(),
+        )
+        .await?
+        .pool;
+        if pool.is_zero() {
+            let receipt = self
+                .admin
+                .send(
+                    factory,
+                    IUniswapV3FactoryAuctioned::createPoolCall {
+                        tokenA: token_0,
+                        tokenB: token_1,
+                        fee: self.options.pool_fee,
+                        auction: auction_guard,
+                    }
+                    .abi_encode()
+                    .into(),
+                    U256::ZERO,
+                )
+                .await
+                .context("failed to send createPool()")?;
+            eyre::ensure!(receipt.success, "createPool() {} reverted", receipt.tx_hash);
+            pool = view(self.admin.provider(), factory, get_pool, BlockId::latest())
+                .await?
+                .pool;
+            eyre::ensure!(!pool.is_zero(), "factory has no pool after createPool()");
+        }
+        println!("pool: {}", pool);
+        self.state.record_contract("pool", pool)?;
+        Ok(pool)
+    }
+
+    /// Mints the admin tokens and provides them as liquidity over the pool's whole range.
+    async fn add_liquidity(
+        &mut self,
+        pool: Address,
+        position_manager: Address,
+        token_0: Address,
+        token_1: Address,
+    ) -> eyre::Result<()> {
+        for (token_name, token) in [("token_0", token_0), ("token_1", token_1)] {
+            fund_and_approve(
+                &mut self.state,
+                &self.admin,
+                "admin",
+                &self.admin,
+                token_name,
+                token,
+                "position_manager",
+                position_manager,
+                self.options.token_amount,
+            )
+            .await?;
+        }
+        if self.state.is_done("add_liquidity") {
+            println!("skipping add_liquidity, already done");
+            return Ok(());
+        }
+
+        let tick_spacing = view(
+            self.admin.provider(),
+            pool,
+            IUniswapV3PoolAuctioned::tickSpacingCall {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0;
+        eyre::ensure!(tick_spacing > 0, "pool has no tick spacing");
+        // widest range that's a multiple of the tick spacing
+        let tick_upper = MAX_TICK / tick_spacing * tick_spacing;
+
+        let latest = self
+            .admin
+            .provider()
+            .get_block_by_number(BlockNumberOrTag::Latest, false)
+            .await
+            .context("failed to get latest L1 block")?
+            .context("latest L1 block not found")?;
+        let deadline = U256::from(latest.header.timestamp + DEFAULT_DEADLINE_OFFSET_SECS);
+
+        send_step(
+            &mut self.state,
+            "add_liquidity",
+            &self.admin,
+            position_manager,
+            INonfungiblePositionManager::mintCall {
+                params: INonfungiblePositionManager::MintParams {
+                    token0: token_0,
+                    token1: token_1,
+                    fee: self.options.pool_fee,
+                    tickLower: -tick_upper,
+                    tickUpper: tick_upper,
+                    amount0Desired: self.options.token_amount,
+                    amount1Desired: self.options.token_amount,
+                    amount0Min: U256::ZERO,
+                    amount1Min: U256::ZERO,
+                    recipient: self.admin.address(),
+                    deadline,
+                },
+            },
+            U256::ZERO,
+        )
+        .await
+    }
+}
+
+/// Deploys `contract` and records it as `name`.
+///
+/// The address is saved as pending as soon as the deployment is sent, so a run interrupted
+/// before it landed picks up the contract instead of deploying a second one.
+async fn deploy(
+    state: &mut DeploymentState,
+    name: &str,
+    l1: &L1Client,
+    contract: &str,
+    creation_code: Bytes,
+) -> eyre::Result<Address> {
+    if let Some(pending) = state.pending.get(name).copied() {
+        if let Some(address) = land_pending(l1, contract, pending).await? {
+            state.record_contract(name, address)?;
+            return Ok(address);
+        }
+    }
+
+    println!("deploying {}", contract);
+    let (tx_hash, address) = l1
+        .submit_deployment(creation_code)
+        .await
+        .context(format!("failed to deploy {}", contract))?;
+    state.record_pending(name, PendingContract { tx_hash, address })?;
+    let address = l1
+        .wait_for_deployment(tx_hash)
+        .await
+        .context(format!("failed to deploy {}", contract))?;
+    println!("{}: {}", contract, address);
+    state.record_contract(name, address)?;
+    Ok(address)
+}
+
+/// Resolves a deployment sent by an earlier run, `None` if it didn't create the contract.
+async fn land_pending(
+    l1: &L1Client,
+    contract: &str,
+    pending: PendingContract,
+) -> eyre::Result<Option<Address>> {
+    if l1.has_code(pending.address).await? {
+        println!("{} already deployed at {}", contract, pending.address);
+        return Ok(Some(pending.address));
+    }
+    println!(
+        "waiting for {}'s earlier deployment {}",
+        contract, pending.tx_hash
+    );
+    match l1.wait_for_deployment(pending.tx_hash).await {
+        Ok(address) => {
+            println!("{}: {}", contract, address);
+            Ok(Some(address))
+        }
+        Err(e) => {
+            println!(
+                "--> !!! earlier deployment of {} didn't land, deploying again: {:?}",
+                contract, e
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Sends `call` from `from` unless the step already completed, then records it.
+async fn send_step<C: SolCall>(
+    state: &mut DeploymentState,
+    step: &str,
+    from: &L1Client,
+    to: Address,
+    call: C,
+    value: U256,
+) -> eyre::Result<()> {
+    if state.is_done(step) {
+        println!("skipping {}, already done", step);
+        return Ok(());
+    }
+    println!("{}", step);
+    let receipt = from
+        .send(to, call.abi_encode().into(), value)
+        .await
+        .context(format!("failed to send {}", step))?;
+    eyre::ensure!(receipt.success, "{} {} reverted", step, receipt.tx_hash);
+    state.mark_done(step)
+}
+
+/// Mints `user` tokens from the admin and has `user` approve `spender` to spend all of them.
+#[allow(clippy::too_many_arguments)]
+async fn fund_and_approve(
+    state: &mut DeploymentState,
+    admin: &L1Client,
+    user_name: &str,
+    user: &L1Client,
+    token_name: &str,
+    token: Address,
+    spender_name: &str,
+    spender: Address,
+    amount: U256,
+) -> eyre::Result<()> {
+    send_step(
+        state,
+        &format!("mint {} to {} for {}", token_name, user_name, spender_name),
+        admin,
+        token,
+        IERC20Mintable::mintCall {
+            to: user.address(),
+            amount,
+        },
+        U256::ZERO,
+    )
+    .await?;
+    send_step(
+        state,
+        &format!(
+            "approve {} for {} by {}",
+            spender_name, token_name, user_name
+        ),
+        user,
+        token,
+        IERC20Mintable::approveCall {
+            spender,
+            amount: U256::MAX,
+        },
+        U256::ZERO,
+    )
+    .await
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use alloy::{
+    eips::BlockId, network::TransactionBuilder, providers::Provider,
+    rpc::types::eth::TransactionRequest, transports::TransportError,
+};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::{SolCall, SolError};
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+
+use crate::{
+    amm_auction::{auction_guard_slots, IAuctionDeposits},
+    amm_auction_config::AmmAuctionConfig,
+    ccr::revert_data,
+    guard::GuardClient,
+    l1_client::{L1Client, L1Receipt},
+};
+
+/// Blocks the guard's last concluded auction may trail the latest block by before auctions
+/// are taken to not be landing, a withdrawal would then revert with `AuctionNotDone`.
+const MAX_BLOCKS_SINCE_CONCLUDED: u64 = 2;
+
+/// Custom errors declared in `AuctionDeposits.sol`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepositsRevert {
+    OnlyAuction,
+    AuctionAlreadySet,
+    /// The current block's auction hasn't concluded, withdrawals would front-run bids
+    AuctionNotDone,
+    AuctionAlreadyWithdrawn,
+    WrongBlockNumber,
+    WrongSigner,
+    NotEnoughFunds,
+    ZeroAddress,
+    /// Sending the funds failed, holds the recipient's revert data
+    TransferError(Bytes),
+    /// Revert data that doesn't match any of the contract's errors
+    Unknown(Bytes),
+}
+
+impl DepositsRevert {
+    /// Maps revert data onto the contract's errors.
+    pub fn decode(data: &[u8]) -> Self {
+        let Some(selector) = data.get(..4) else {
+            return DepositsRevert::Unknown(Bytes::copy_from_slice(data));
+        };
+        match selector {
+            s if s == IAuctionDeposits::OnlyAuction::SELECTOR => DepositsRevert::OnlyAuction,
+            s if s == IAuctionDeposits::AuctionAlreadySet::SELECTOR => {
+                DepositsRevert::AuctionAlreadySet
+            }
+            s if s == IAuctionDeposits::AuctionNotDone::SELECTOR => DepositsRevert::AuctionNotDone,
+            s if s == IAuctionDeposits::AuctionAlreadyWithdrawn::SELECTOR => {
+                DepositsRevert::AuctionAlreadyWithdrawn
+            }
+            s if s == IAuctionDeposits::WrongBlockNumber::SELECTOR => {
+                DepositsRevert::WrongBlockNumber
+            }
+            s if s == IAuctionDeposits::WrongSigner::SELECTOR => DepositsRevert::WrongSigner,
+            s if s == IAuctionDeposits::NotEnoughFunds::SELECTOR => DepositsRevert::NotEnoughFunds,
+            s if s == IAuctionDeposits::ZeroAddress::SELECTOR => DepositsRevert::ZeroAddress,
+            s if s == IAuctionDeposits::TransferError::SELECTOR => {
+                match IAuctionDeposits::TransferError::abi_decode(data, true) {
+                    Ok(err) => DepositsRevert::TransferError(err.error),
+                    Err(_) => DepositsRevert::Unknown(Bytes::copy_from_slice(data)),
+                }
+            }
+            _ => DepositsRevert::Unknown(Bytes::copy_from_slice(data)),
+        }
+    }
+
+    /// Pulls the revert data out of a node's error response, if there is any.
+    pub fn from_transport_error(err: &alloy::transports::TransportError) -> Option<Self> {
+        revert_data(err).map(|data| DepositsRevert::decode(&data))
+    }
+}
+
+impl fmt::Display for DepositsRevert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepositsRevert::TransferError(data) => {
+                write!(f, "deposits reverted: TransferError({})", data)
+            }
+            DepositsRevert::Unknown(data) => {
+                write!(f, "deposits reverted with unknown data: {}", data)
+            }
+            revert => write!(f, "deposits reverted: {:?}", revert),
+        }
+    }
+}
+
+impl std::error::Error for DepositsRevert {}
+
+/// Client for a bidder's funds in `AuctionDeposits.sol`.
+pub struct DepositsClient {
+    l1: L1Client,
+    auction_deposits: Address,
+    auction_guard: Address,
+    guard: GuardClient,
+}
+
+impl DepositsClient {
+    pub fn new(l1: L1Client, auction_deposits: Address, auction_guard: Address) -> Self {
+        let guard = GuardClient::from_provider(l1.provider().clone(), auction_guard);
+        Self {
+            l1,
+            auction_deposits,
+            auction_guard,
+            guard,
+        }
+    }
+
+    /// Builds a client sending from `l1`'s wallet to the configured contracts.
+    pub fn from_config(config: &AmmAuctionConfig, l1: L1Client) -> eyre::Result<Self> {
+        Ok(Self::new(
+            l1,
+            config
+                .auction_deposits
+                .context("auction deposits not set")?,
+            config.auction_guard.context("auction guard not set")?,
+        ))
+    }
+
+    /// Address the client deposits and withdraws for.
+    pub fn address(&self) -> Address {
+        self.l1.address()
+    }
+
+    /// Deposits `amount` wei for the client's wallet.
+    pub async fn deposit(&self, amount: U256) -> eyre::Result<L1Receipt> {
+        let receipt = self
+            .l1
+            .send(
+                self.auction_deposits,
+                IAuctionDeposits::depositCall {}.abi_encode().into(),
+                amount,
+            )
+            .await
+            .context("failed to send deposit")?;
+        eyre::ensure!(receipt.success, "deposit {} reverted", receipt.tx_hash);
+        Ok(receipt)
+    }
+
+    /// The deposited balance of `bidder`.
+    pub async fn balance_of(&self, bidder: Address) -> eyre::Result<U256> {
+        let return_data = self
+            .l1
+            .call(
+                self.auction_deposits,
+                IAuctionDeposits::balanceOfCall { bidder }
+                    .abi_encode()
+                    .into(),
+                BlockId::latest(),
+            )
+            .await
+            .context("failed to call balanceOf()")?;
+        let balance = IAuctionDeposits::balanceOfCall::abi_decode_returns(&return_data, true)
+            .context("failed to decode balanceOf() return data")?;
+        Ok(balance._0)
+    }
+
+    /// Withdraws `amount` wei of the client's deposit to `to`.
+    ///
+    /// While auctions are enabled, withdrawals revert with `AuctionNotDone` unless the
+    /// block's auction concluded before them in the same block, so the guard is checked
+    /// first: with `currentBlockAuctionDone()` already true (auctions disabled) the
+    /// withdrawal is sent right away, otherwise it's only sent while auctions are landing,
+    /// the guard's last concluded block being at most `MAX_BLOCKS_SINCE_CONCLUDED` behind.
+    /// The withdrawal is simulated as if its block's auction had concluded, so any other
+    /// revert is returned as a `DepositsRevert` without sending. It's sent once, a revert is
+    /// an error rather than resent into the same conditions.
+    pub async fn withdraw(&self, to: Address, amount: U256) -> eyre::Result<L1Receipt> {
+        let input: Bytes = IAuctionDeposits::withdrawCall { to, amount }
+            .abi_encode()
+            .into();
+
+        let auction_done = self
+            .guard
+            .current_block_auction_done()
+            .await
+            .context("failed to check if the current block's auction is done")?;
+        if !auction_done {
+            self.ensure_auctions_landing().await?;
+        }
+
+        if let Err(e) = self.simulate_after_auction(input.clone()).await {
+            return match DepositsRevert::from_transport_error(&e) {
+                Some(revert) => Err(eyre::Report::new(revert).wrap_err("withdraw would revert")),
+                None => Err(e).context("failed to simulate withdraw"),
+            };
+        }
+
+        let receipt = self
+            .l1
+            .send(self.auction_deposits, input, U256::ZERO)
+            .await
+            .context("failed to send withdraw")?;
+        eyre::ensure!(
+            receipt.success,
+            "withdraw {} reverted, it likely landed before its block's auction concluded",
+            receipt.tx_hash
+        );
+        Ok(receipt)
+    }
+
+    /// Errors unless auctions are enabled and concluding, so a withdrawal can land after
+    /// its block's auction.
+    async fn ensure_auctions_landing(&self) -> eyre::Result<()> {
+        let enabled = self
+            .guard
+            .auctions_enabled()
+            .await
+            .context("failed to check if auctions are enabled")?;
+        eyre::ensure!(
+            enabled,
+            "auctions are disabled but the current block's auction isn't done"
+        );
+        let last_concluded_block = self
+            .guard
+            .last_concluded_block()
+            .await
+            .context("failed to get the last concluded block")?;
+        let latest = self
+            .l1
+            .provider()
+            .get_block_number()
+            .await
+            .context("failed to get L1 block number")?;
+        eyre::ensure!(
+            latest.saturating_sub(last_concluded_block) <= MAX_BLOCKS_SINCE_CONCLUDED,
+            "no auction concluded since block {} (latest {}), a withdrawal would revert with \
+             AuctionNotDone",
+            last_concluded_block,
+            latest
+        );
+        Ok(())
+    }
+
+    /// `eth_call`s `input` on the deposits contract in the pending block, with the guard's
+    /// auction for it already concluded.
+    async fn simulate_after_auction(&self, input: Bytes) -> Result<Bytes, TransportError> {
+        let pending_block = self.l1.provider().get_block_number().await? + 1;
+        let guard_key = self.auction_guard.to_string();
+        let slot_key =
+            B256::from(U256::from(auction_guard_slots::LAST_CONCLUDED_BLOCK)).to_string();
+        let state_override = serde_json::json!({
+            guard_key: { "stateDiff": { slot_key: B256::from(U256::from(pending_block)) } }
+        });
+        let tx = TransactionRequest::default()
+            .from(self.address())
+            .to(self.auction_deposits)
+            .input(input.into());
+        self.l1
+            .provider()
+            .client()
+            .request("eth_call", (tx, BlockId::pending(), state_override))
+            .await
+    }
+}
@@ -0,0 +1,408 @@
+use std::{collections::VecDeque, pin::Pin};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::client::WsConnect,
+    transports::http::Http,
+};
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use alloy_sol_types::SolEvent;
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use futures_util::{stream, Stream, StreamExt};
+use reqwest::Client as ReqwestClient;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::time::{sleep, Duration};
+
+use crate::{
+    amm_auction::{IAuctionDeposits, IAuctionGuard},
+    amm_auction_config::AmmAuctionConfig,
+    ccr::SuappEvent,
+};
+
+/// Most blocks requested in a single `eth_getLogs` call while catching up.
+const MAX_LOG_BLOCK_RANGE: u64 = 1000;
+/// Default interval between `eth_getLogs` polls.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Events emitted by `AuctionGuard.sol`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardEvent {
+    AuctionsEnabled {
+        enabled: bool,
+    },
+    /// `postAuctionResults()` was called by the suapp for the current block
+    AuctionSucceeded,
+    /// The auction winner's bid was collected, their swap can go through
+    SuccessfulPayment,
+    AdminChanged {
+        old_admin: Address,
+        new_admin: Address,
+    },
+    SuappKeyChanged {
+        old_suapp_key: Address,
+        new_suapp_key: Address,
+    },
+    FeeAddressChanged {
+        old_fee_address: Address,
+        new_fee_address: Address,
+    },
+}
+
+impl GuardEvent {
+    /// Decodes a guard log, returns `None` for logs that aren't guard events.
+    pub fn decode(topics: &[B256], data: &[u8]) -> Option<Self> {
+        let topic = *topics.first()?;
+        let topics = topics.iter().copied();
+        if topic == IAuctionGuard::AuctionsEnabled::SIGNATURE_HASH {
+            let event = IAuctionGuard::AuctionsEnabled::decode_raw_log(topics, data, true).ok()?;
+            Some(GuardEvent::AuctionsEnabled {
+                enabled: event.enabled,
+            })
+        } else if topic == IAuctionGuard::AuctionSucceeded::SIGNATURE_HASH {
+            Some(GuardEvent::AuctionSucceeded)
+        } else if topic == IAuctionGuard::SuccessfulPayment::SIGNATURE_HASH {
+            Some(GuardEvent::SuccessfulPayment)
+        } else if topic == IAuctionGuard::AdminChanged::SIGNATURE_HASH {
+            let event = IAuctionGuard::AdminChanged::decode_raw_log(topics, data, true).ok()?;
+            Some(GuardEvent::AdminChanged {
+                old_admin: event.oldAdmin,
+                new_admin: event.newAdmin,
+            })
+        } else if topic == IAuctionGuard::SuappKeyChanged::SIGNATURE_HASH {
+            let event = IAuctionGuard::SuappKeyChanged::decode_raw_log(topics, data, true).ok()?;
+            Some(GuardEvent::SuappKeyChanged {
+                old_suapp_key: event.oldSuappKey,
+                new_suapp_key: event.newSuappKey,
+            })
+        } else if topic == IAuctionGuard::FeeAddressChanged::SIGNATURE_HASH {
+            let event =
+                IAuctionGuard::FeeAddressChanged::decode_raw_log(topics, data, true).ok()?;
+            Some(GuardEvent::FeeAddressChanged {
+                old_fee_address: event.oldFeeAddress,
+                new_fee_address: event.newFeeAddress,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Events emitted by `AuctionDeposits.sol`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepositsEvent {
+    Deposit {
+        depositee: Address,
+        depositor: Address,
+        amount: U256,
+    },
+    /// Funds left the contract, either withdrawn by `from` or collected as a winning bid
+    Withdraw {
+        from: Address,
+        to: Address,
+        amount: U256,
+    },
+    EnforceSequencing {
+        enabled: bool,
+    },
+}
+
+impl DepositsEvent {
+    /// Decodes a deposits log, returns `None` for logs that aren't deposits events.
+    pub fn decode(topics: &[B256], data: &[u8]) -> Option<Self> {
+        let topic = *topics.first()?;
+        let topics = topics.iter().copied();
+        if topic == IAuctionDeposits::Deposit::SIGNATURE_HASH {
+            let event = IAuctionDeposits::Deposit::decode_raw_log(topics, data, true).ok()?;
+            Some(DepositsEvent::Deposit {
+                depositee: event.depositee,
+                depositor: event.depositor,
+                amount: event.amount,
+            })
+        } else if topic == IAuctionDeposits::Withdraw::SIGNATURE_HASH {
+            let event = IAuctionDeposits::Withdraw::decode_raw_log(topics, data, true).ok()?;
+            Some(DepositsEvent::Withdraw {
+                from: event.from,
+                to: event.to,
+                amount: event.amount,
+            })
+        } else if topic == IAuctionDeposits::EnforceSequencing::SIGNATURE_HASH {
+            let event =
+                IAuctionDeposits::EnforceSequencing::decode_raw_log(topics, data, true).ok()?;
+            Some(DepositsEvent::EnforceSequencing {
+                enabled: event.enabled,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// A typed event from one of the auction's contracts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuctionEvent {
+    /// From `AuctionSuapp` on Suave
+    Suapp(SuappEvent),
+    /// From `AuctionGuard` on L1
+    Guard(GuardEvent),
+    /// From `AuctionDeposits` on L1
+    Deposits(DepositsEvent),
+}
+
+/// An event and where it was emitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuctionEventLog {
+    pub event: AuctionEvent,
+    pub address: Address,
+    pub block_number: Option<u64>,
+    pub block_hash: Option<B256>,
+    pub tx_hash: Option<B256>,
+    pub log_index: Option<u64>,
+    /// Set when the log was dropped by a reorg
+    pub removed: bool,
+}
+
+/// Boxed stream of decoded auction events.
+pub type AuctionEventStream = Pin<Box<dyn Stream<Item = eyre::Result<AuctionEventLog>> + Send>>;
+
+/// The contracts whose events are decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuctionContracts {
+    pub auction_suapp: Address,
+    pub auction_guard: Address,
+    pub auction_deposits: Address,
+}
+
+impl AuctionContracts {
+    pub fn from_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        Ok(Self {
+            auction_suapp: config.suapp_amm.context("auction suapp not set")?,
+            auction_guard: config.auction_guard.context("auction guard not set")?,
+            auction_deposits: config
+                .auction_deposits
+                .context("auction deposits not set")?,
+        })
+    }
+
+    /// Decodes a log if it's one of the contracts' events.
+    pub fn decode(&self, address: Address, topics: &[B256], data: &[u8]) -> Option<AuctionEvent> {
+        if address == self.auction_suapp {
+            SuappEvent::decode(topics, data).map(AuctionEvent::Suapp)
+        } else if address == self.auction_guard {
+            GuardEvent::decode(topics, data).map(AuctionEvent::Guard)
+        } else if address == self.auction_deposits {
+            DepositsEvent::decode(topics, data).map(AuctionEvent::Deposits)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where a chain's logs are read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogSource {
+    /// `eth_subscribe` to logs over a websocket, only sees logs emitted after subscribing
+    WebSocket { url: String },
+    /// `eth_getLogs` polling over http, starting at `from_block` (or the latest block) and
+    /// moving a block cursor forward as blocks are read
+    HttpPolling {
+        url: String,
+        from_block: Option<u64>,
+        poll_interval: Duration,
+    },
+}
+
+impl LogSource {
+    /// No Suave websocket url is configured so the suapp's logs are always polled.
+    pub fn suave_from_config(config: &AmmAuctionConfig, from_block: Option<u64>) -> Self {
+        let url = if config.use_local {
+            config.rpc_url_suave_local.clone()
+        } else {
+            config.rpc_url_suave.clone()
+        };
+        LogSource::HttpPolling {
+            url,
+            from_block,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Subscribes over `WSS_L1` unless polling is asked for or a start block is given.
+    pub fn l1_from_config(config: &AmmAuctionConfig, poll: bool, from_block: Option<u64>) -> Self {
+        if poll || from_block.is_some() {
+            LogSource::HttpPolling {
+                url: config.rpc_url_l1.clone(),
+                from_block,
+                poll_interval: DEFAULT_POLL_INTERVAL,
+            }
+        } else {
+            LogSource::WebSocket {
+                url: config.wss_l1.clone(),
+            }
+        }
+    }
+}
+
+/// Log fields as returned by `eth_getLogs` and log subscriptions.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawLog {
+    pub(crate) address: Address,
+    pub(crate) topics: Vec<B256>,
+    pub(crate) data: Bytes,
+    pub(crate) block_number: Option<U64>,
+    pub(crate) block_hash: Option<B256>,
+    pub(crate) transaction_hash: Option<B256>,
+    pub(crate) log_index: Option<U64>,
+    #[serde(default)]
+    pub(crate) removed: bool,
+}
+
+impl RawLog {
+    fn decode(&self, contracts: &AuctionContracts) -> Option<AuctionEventLog> {
+        let event = contracts.decode(self.address, &self.topics, &self.data)?;
+        Some(AuctionEventLog {
+            event,
+            address: self.address,
+            block_number: self.block_number.map(|number| number.to::<u64>()),
+            block_hash: self.block_hash,
+            tx_hash: self.transaction_hash,
+            log_index: self.log_index.map(|index| index.to::<u64>()),
+            removed: self.removed,
+        })
+    }
+}
+
+/// Streams the events the contracts emit on a single chain.
+pub async fn chain_event_stream(
+    source: LogSource,
+    contracts: AuctionContracts,
+    addresses: Vec<Address>,
+) -> eyre::Result<AuctionEventStream> {
+    match source {
+        LogSource::WebSocket { url } => {
+            let provider = ProviderBuilder::new()
+                .on_ws(WsConnect::new(url))
+                .await
+                .context("failed to connect to websocket")?;
+            let subscription = provider
+                .subscribe::<_, RawLog>(("logs", json!({ "address": addresses })))
+                .await
+                .context("failed to subscribe to logs")?;
+
+            let events = subscription.into_stream().filter_map(move |log| {
+                // keep the provider alive for as long as the subscription is used
+                let _provider = &provider;
+                futures_util::future::ready(log.decode(&contracts).map(Ok))
+            });
+            Ok(Box::pin(events))
+        }
+        LogSource::HttpPolling {
+            url,
+            from_block,
+            poll_interval,
+        } => {
+            let rpc_url = url::Url::parse(&url).context("failed to parse log rpc url")?;
+            let provider = ProviderBuilder::new()
+                .on_http(rpc_url)
+                .context("failed to build provider from given rpc url")?;
+            let poller = LogPoller {
+                provider,
+                contracts,
+                addresses,
+                next_block: from_block,
+                poll_interval,
+                pending: VecDeque::new(),
+                polled: false,
+            };
+            Ok(Box::pin(stream::unfold(poller, |mut poller| async move {
+                let next = poller.next_event().await;
+                Some((next, poller))
+            })))
+        }
+    }
+}
+
+/// Streams the suapp's events from Suave merged with the guard's and deposit contract's
+/// events from L1.
+pub async fn auction_event_stream(
+    suave_source: LogSource,
+    l1_source: LogSource,
+    contracts: AuctionContracts,
+) -> eyre::Result<AuctionEventStream> {
+    let suave_events = chain_event_stream(suave_source, contracts, vec![contracts.auction_suapp])
+        .await
+        .context("failed to stream suave events")?;
+    let l1_events = chain_event_stream(
+        l1_source,
+        contracts,
+        vec![contracts.auction_guard, contracts.auction_deposits],
+    )
+    .await
+    .context("failed to stream L1 events")?;
+    Ok(Box::pin(stream::select(suave_events, l1_events)))
+}
+
+/// Polls `eth_getLogs` block range by block range.
+struct LogPoller {
+    provider: RootProvider<Http<ReqwestClient>>,
+    contracts: AuctionContracts,
+    addresses: Vec<Address>,
+    /// First block not yet read, `None` until the latest block is known
+    next_block: Option<u64>,
+    poll_interval: Duration,
+    /// Decoded events not yet handed out
+    pending: VecDeque<AuctionEventLog>,
+    /// Whether a poll was made yet, the first one isn't delayed
+    polled: bool,
+}
+
+impl LogPoller {
+    async fn next_event(&mut self) -> eyre::Result<AuctionEventLog> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+            if self.polled {
+                sleep(self.poll_interval).await;
+            }
+            self.polled = true;
+            self.poll().await?;
+        }
+    }
+
+    /// Reads the logs of the blocks after the cursor, moving the cursor past them.
+    async fn poll(&mut self) -> eyre::Result<()> {
+        let latest_block = self
+            .provider
+            .get_block_number()
+            .await
+            .context("failed to get block number")?;
+        let from_block = *self.next_block.get_or_insert(latest_block);
+        if from_block > latest_block {
+            return Ok(());
+        }
+        let to_block = latest_block.min(from_block + MAX_LOG_BLOCK_RANGE - 1);
+
+        let filter = json!({
+            "address": self.addresses,
+            "fromBlock": U64::from(from_block),
+            "toBlock": U64::from(to_block),
+        });
+        let logs: Vec<RawLog> = self
+            .provider
+            .client()
+            .request("eth_getLogs", (filter,))
+            .await
+            .context(format!(
+                "failed to get logs for blocks {}..={}",
+                from_block, to_block
+            ))?;
+
+        self.pending
+            .extend(logs.iter().filter_map(|log| log.decode(&self.contracts)));
+        self.next_block = Some(to_block + 1);
+        Ok(())
+    }
+}
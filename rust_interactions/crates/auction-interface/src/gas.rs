@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use alloy_sol_types::SolCall;
+
+use crate::amm_auction::{IAuctionSuapp, ISwapRouter};
+
+/// Picks gas limits and prices for transactions.
+///
+/// Gas limits come from `eth_estimateGas` when an estimate is available and fall back to a
+/// per-selector profile otherwise. Either is scaled by a safety multiplier.
+#[derive(Debug, Clone)]
+pub struct GasPolicy {
+    /// Gas limits by function selector, used when no estimate is available
+    profiles: HashMap<[u8; 4], u128>,
+    /// Gas limit for selectors without a profile
+    default_gas_limit: u128,
+    /// Multiplier applied to estimates and profiles
+    safety_multiplier: f64,
+    /// Added to the node's gas price to account for fluctuation between creation and sending
+    gas_price_bump: u128,
+    /// If `eth_estimateGas` should be tried before falling back to the profiles
+    estimate: bool,
+}
+
+impl GasPolicy {
+    /// Policy for CCRs to the auction suapp.
+    ///
+    /// Confidential calls can't be estimated without a kettle so only the profiles are used.
+    pub fn suave_default() -> Self {
+        let profiles = HashMap::from([
+            // fetches L1 state over http, loads every bid and pending swap, and sends the bundle
+            (IAuctionSuapp::runAuctionCall::SELECTOR, 10_000_000),
+            // fetches L1 block and current time over http
+            (IAuctionSuapp::newBidCall::SELECTOR, 3_000_000),
+            (IAuctionSuapp::newPendingTxnCall::SELECTOR, 500_000),
+            (IAuctionSuapp::setSigningKeyCall::SELECTOR, 500_000),
+            (IAuctionSuapp::setL1UrlCall::SELECTOR, 500_000),
+            (IAuctionSuapp::setBundleUrlCall::SELECTOR, 500_000),
+            (IAuctionSuapp::initLastL1BlockCall::SELECTOR, 500_000),
+            (IAuctionSuapp::_resetSwapsCall::SELECTOR, 200_000),
+        ]);
+
+        Self {
+            profiles,
+            default_gas_limit: 0x2f4240,
+            safety_multiplier: 1.0,
+            gas_price_bump: 1_000_000,
+            estimate: false,
+        }
+    }
+
+    /// Policy for L1 transactions.
+    pub fn l1_default() -> Self {
+        let profiles = HashMap::from([
+            // single pool swap plus the auction guard check
+            (ISwapRouter::exactInputSingleCall::SELECTOR, 250_000),
+            (ISwapRouter::exactOutputSingleCall::SELECTOR, 250_000),
+            // multi-hop swaps pay for a pool swap per hop
+            (ISwapRouter::exactInputCall::SELECTOR, 500_000),
+            (ISwapRouter::exactOutputCall::SELECTOR, 500_000),
+        ]);
+
+        Self {
+            profiles,
+            default_gas_limit: 0x0f4240,
+            safety_multiplier: 1.2,
+            gas_price_bump: 1_000_000_000,
+            estimate: true,
+        }
+    }
+
+    /// Sets the multiplier applied to estimates and profiles.
+    pub fn with_safety_multiplier(mut self, safety_multiplier: f64) -> Self {
+        self.safety_multiplier = safety_multiplier;
+        self
+    }
+
+    /// Sets or overrides the gas limit profile of a function selector.
+    pub fn with_profile(mut self, selector: [u8; 4], gas_limit: u128) -> Self {
+        self.profiles.insert(selector, gas_limit);
+        self
+    }
+
+    /// If `eth_estimateGas` should be tried for this policy's transactions.
+    pub fn should_estimate(&self) -> bool {
+        self.estimate
+    }
+
+    /// Gas limit for a call with `input`, using `estimate` if one was made.
+    pub fn gas_limit(&self, input: &[u8], estimate: Option<u128>) -> u128 {
+        let base = estimate.unwrap_or_else(|| {
+            input
+                .get(..4)
+                .and_then(|selector| self.profiles.get(selector))
+                .copied()
+                .unwrap_or(self.default_gas_limit)
+        });
+        (base as f64 * self.safety_multiplier).ceil() as u128
+    }
+
+    /// Gas price to use given the node's current gas price.
+    pub fn gas_price(&self, current_gas_price: u128) -> u128 {
+        current_gas_price.saturating_add(self.gas_price_bump)
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt;
+
+use alloy::{
+    eips::BlockId,
+    providers::RootProvider,
+    transports::{http::Http, TransportError},
+};
+use alloy_primitives::{Address, Bytes, U256};
+use alloy_sol_types::{SolCall, SolError};
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use reqwest::Client as ReqwestClient;
+use serde::Serialize;
+
+use crate::{
+    amm_auction::{http_provider, view, IAuctionGuard},
+    amm_auction_config::AmmAuctionConfig,
+    ccr::revert_data,
+    events::GuardEvent,
+    l1_client::{L1Client, L1Receipt},
+};
+
+/// Custom errors declared in `AuctionGuard.sol`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum GuardRevert {
+    WrongValidWinnerBlock,
+    WrongWinner,
+    AuctionsNotRunning,
+    AuctionAlreadyPosted,
+    /// The sender isn't the guard's admin
+    OnlyAdmin,
+    OnlySuappKey,
+    /// The zero address was passed where an account is needed
+    ZeroAddress,
+    /// Revert data that doesn't match any of the contract's errors
+    Unknown(Bytes),
+}
+
+impl GuardRevert {
+    /// Maps revert data onto the contract's errors.
+    pub fn decode(data: &[u8]) -> Self {
+        match data.get(..4) {
+            Some(s) if s == IAuctionGuard::WrongValidWinnerBlock::SELECTOR => {
+                GuardRevert::WrongValidWinnerBlock
+            }
+            Some(s) if s == IAuctionGuard::WrongWinner::SELECTOR => GuardRevert::WrongWinner,
+            Some(s) if s == IAuctionGuard::AuctionsNotRunning::SELECTOR => {
+                GuardRevert::AuctionsNotRunning
+            }
+            Some(s) if s == IAuctionGuard::AuctionAlreadyPosted::SELECTOR => {
+                GuardRevert::AuctionAlreadyPosted
+            }
+            Some(s) if s == IAuctionGuard::OnlyAdmin::SELECTOR => GuardRevert::OnlyAdmin,
+            Some(s) if s == IAuctionGuard::OnlySuappKey::SELECTOR => GuardRevert::OnlySuappKey,
+            Some(s) if s == IAuctionGuard::ZeroAddress::SELECTOR => GuardRevert::ZeroAddress,
+            _ => GuardRevert::Unknown(Bytes::copy_from_slice(data)),
+        }
+    }
+
+    /// Pulls the revert data out of a node's error response, if there is any.
+    pub fn from_transport_error(err: &TransportError) -> Option<Self> {
+        revert_data(err).map(|data| GuardRevert::decode(&data))
+    }
+}
+
+impl fmt::Display for GuardRevert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuardRevert::Unknown(data) => write!(f, "guard reverted with unknown data: {}", data),
+            revert => write!(f, "guard reverted: {:?}", revert),
+        }
+    }
+}
+
+impl std::error::Error for GuardRevert {}
+
+/// Snapshot of the guard's state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GuardState {
+    pub auctions_enabled: bool,
+    pub admin: Address,
+    pub suapp_key: Address,
+    pub fee_address: Address,
+    /// Last block whose auction concluded
+    pub last_concluded_block: u64,
+    /// Block the last auction winner's swap is valid in
+    pub winner_valid_block: u64,
+    pub winner_tx_origin: Address,
+    /// If the pending block's auction concluded
+    pub current_block_auction_done: bool,
+}
+
+impl fmt::Display for GuardState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Auction Guard")?;
+        writeln!(f, "  auctions enabled     : {}", self.auctions_enabled)?;
+        writeln!(f, "  admin                : {}", self.admin)?;
+        writeln!(f, "  suapp key            : {}", self.suapp_key)?;
+        writeln!(f, "  fee address          : {}", self.fee_address)?;
+        writeln!(f, "  last concluded block : {}", self.last_concluded_block)?;
+        writeln!(f, "  winner valid block   : {}", self.winner_valid_block)?;
+        writeln!(f, "  winner tx origin     : {}", self.winner_tx_origin)?;
+        write!(
+            f,
+            "  current block done   : {}",
+            self.current_block_auction_done
+        )
+    }
+}
+
+/// The guard's auction bookkeeping as of the end of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GuardBlockState {
+    pub block_number: u64,
+    pub auctions_enabled: bool,
+    pub last_concluded_block: u64,
+    pub winner_valid_block: u64,
+}
+
+impl GuardBlockState {
+    /// If guarded swaps reverted all block: auctions were on and the block's auction neither
+    /// concluded nor named a winner, so no results were posted for it.
+    pub fn no_results(&self) -> bool {
+        self.auctions_enabled
+            && self.last_concluded_block < self.block_number
+            && self.winner_valid_block != self.block_number
+    }
+
+    /// If a winner was posted but never swapped, so everyone else's swaps reverted.
+    pub fn winner_never_swapped(&self) -> bool {
+        self.auctions_enabled
+            && self.last_concluded_block < self.block_number
+            && self.winner_valid_block == self.block_number
+    }
+}
+
+/// Client for reading `AuctionGuard.sol`'s state and, with an admin wallet, managing it.
+pub struct GuardClient {
+    provider: RootProvider<Http<ReqwestClient>>,
+    auction_guard: Address,
+    admin: Option<L1Client>,
+}
+
+impl GuardClient {
+    pub fn new(l1_rpc: &str, auction_guard: Address) -> eyre::Result<Self> {
+        Ok(Self {
+            provider: http_provider(l1_rpc)?,
+            auction_guard,
+            admin: None,
+        })
+    }
+
+    /// Reads the guard through an existing L1 provider.
+    pub fn from_provider(
+        provider: RootProvider<Http<ReqwestClient>>,
+        auction_guard: Address,
+    ) -> Self {
+        Self {
+            provider,
+            auction_guard,
+            admin: None,
+        }
+    }
+
+    pub fn from_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        Self::new(
+            &config.rpc_url_l1,
+            config.auction_guard.context("auction guard not set")?,
+        )
+    }
+
+    /// Sets the wallet admin calls are sent from.
+    pub fn with_admin(mut self, admin: L1Client) -> Self {
+        self.admin = Some(admin);
+        self
+    }
+
+    /// Calls a view function on the guard at `block`.
+    async fn view<C: SolCall>(&self, call: C, block: BlockId) -> eyre::Result<C::Return> {
+        view(&self.provider, self.auction_guard, call, block).await
+    }
+
+    pub async fn last_concluded_block(&self) -> eyre::Result<u64> {
+        let block = self
+            .view(IAuctionGuard::lastConcludedBlockCall {}, BlockId::latest())
+            .await?
+            ._0;
+        u64::try_from(block).context("last concluded block does not fit in u64")
+    }
+
+    pub async fn winner_valid_block(&self) -> eyre::Result<u64> {
+        let block = self
+            .view(IAuctionGuard::winnerValidBlockCall {}, BlockId::latest())
+            .await?
+            ._0;
+        u64::try_from(block).context("winner valid block does not fit in u64")
+    }
+
+    /// If the pending block's auction concluded, so guarded calls and withdrawals go through.
+    pub async fn current_block_auction_done(&self) -> eyre::Result<bool> {
+        Ok(self
+            .view(
+                IAuctionGuard::currentBlockAuctionDoneCall {},
+                BlockId::pending(),
+            )
+            .await?
+            ._0)
+    }
+
+    pub async fn fee_address(&self) -> eyre::Result<Address> {
+        Ok(self
+            .view(IAuctionGuard::getFeeAddressCall {}, BlockId::latest())
+            .await?
+            ._0)
+    }
+
+    pub async fn auctions_enabled(&self) -> eyre::Result<bool> {
+        Ok(self
+            .view(IAuctionGuard::auctionsEnabledCall {}, BlockId::latest())
+            .await?
+            ._0)
+    }
+
+    pub async fn admin(&self) -> eyre::Result<Address> {
+        Ok(self
+            .view(IAuctionGuard::adminCall {}, BlockId::latest())
+            .await?
+            ._0)
+    }
+
+    pub async fn suapp_key(&self) -> eyre::Result<Address> {
+        Ok(self
+            .view(IAuctionGuard::suappKeyCall {}, BlockId::latest())
+            .await?
+            ._0)
+    }
+
+    /// Key the guard took auction results from at the end of `block_number`.
+    pub async fn suapp_key_at(&self, block_number: u64) -> eyre::Result<Address> {
+        Ok(self
+            .view(
+                IAuctionGuard::suappKeyCall {},
+                BlockId::number(block_number),
+            )
+            .await?
+            ._0)
+    }
+
+    pub async fn winner_tx_origin(&self) -> eyre::Result<Address> {
+        Ok(self
+            .view(IAuctionGuard::winnerTxOriginCall {}, BlockId::latest())
+            .await?
+            ._0)
+    }
+
+    /// Reads the guard's auction bookkeeping at the end of `block_number`.
+    pub async fn block_state(&self, block_number: u64) -> eyre::Result<GuardBlockState> {
+        let block = BlockId::number(block_number);
+        let last_concluded_block = self
+            .view(IAuctionGuard::lastConcludedBlockCall {}, block)
+            .await?
+            ._0;
+        let winner_valid_block = self
+            .view(IAuctionGuard::winnerValidBlockCall {}, block)
+            .await?
+            ._0;
+        Ok(GuardBlockState {
+            block_number,
+            auctions_enabled: self
+                .view(IAuctionGuard::auctionsEnabledCall {}, block)
+                .await?
+                ._0,
+            last_concluded_block: u64::try_from(last_concluded_block)
+                .context("last concluded block does not fit in u64")?,
+            winner_valid_block: u64::try_from(winner_valid_block)
+                .context("winner valid block does not fit in u64")?,
+        })
+    }
+
+    /// Reads all of the guard's state.
+    pub async fn state(&self) -> eyre::Result<GuardState> {
+        Ok(GuardState {
+            auctions_enabled: self.auctions_enabled().await?,
+            admin: self.admin().await?,
+            suapp_key: self.suapp_key().await?,
+            fee_address: self.fee_address().await?,
+            last_concluded_block: self.last_concluded_block().await?,
+            winner_valid_block: self.winner_valid_block().await?,
+            winner_tx_origin: self.winner_tx_origin().await?,
+            current_block_auction_done: self.current_block_auction_done().await?,
+        })
+    }
+
+    pub async fn set_admin(&self, new_admin: Address) -> eyre::Result<GuardEvent> {
+        self.send_admin_call(IAuctionGuard::setAdminCall {
+            newAdmin: new_admin,
+        })
+        .await
+    }
+
+    pub async fn set_suapp_key(&self, new_suapp_key: Address) -> eyre::Result<GuardEvent> {
+        self.send_admin_call(IAuctionGuard::setSuappKeyCall {
+            newSuappKey: new_suapp_key,
+        })
+        .await
+    }
+
+    pub async fn set_fee_address(&self, new_fee_address: Address) -> eyre::Result<GuardEvent> {
+        self.send_admin_call(IAuctionGuard::setFeeAddressCall {
+            newFeeAddress: new_fee_address,
+        })
+        .await
+    }
+
+    pub async fn enable_auction(&self, enabled: bool) -> eyre::Result<GuardEvent> {
+        self.send_admin_call(IAuctionGuard::enableAuctionCall {
+            setAuction: enabled,
+        })
+        .await
+    }
+
+    /// Simulates an admin call to surface `OnlyAdmin`/`ZeroAddress` reverts, sends it, and
+    /// returns the change event it emitted.
+    async fn send_admin_call<C: SolCall>(&self, call: C) -> eyre::Result<GuardEvent> {
+        let admin = self
+            .admin
+            .as_ref()
+            .context("no admin wallet set for guard admin calls")?;
+        let input: Bytes = call.abi_encode().into();
+
+        if let Err(e) = admin
+            .call(self.auction_guard, input.clone(), BlockId::pending())
+            .await
+        {
+            return match GuardRevert::from_transport_error(&e) {
+                Some(revert) => {
+                    Err(eyre::Report::new(revert)
+                        .wrap_err(format!("{} would revert", C::SIGNATURE)))
+                }
+                None => Err(e).context(format!("failed to simulate {}", C::SIGNATURE)),
+            };
+        }
+
+        let receipt = admin
+            .send(self.auction_guard, input, U256::ZERO)
+            .await
+            .context(format!("failed to send {}", C::SIGNATURE))?;
+        eyre::ensure!(
+            receipt.success,
+            "{} transaction {} reverted",
+            C::SIGNATURE,
+            receipt.tx_hash
+        );
+        self.change_event(&receipt)
+            .context(format!("{} emitted no change event", C::SIGNATURE))
+    }
+
+    /// The first of the guard's events in a receipt.
+    fn change_event(&self, receipt: &L1Receipt) -> Option<GuardEvent> {
+        receipt
+            .logs
+            .iter()
+            .filter(|log| log.address == self.auction_guard)
+            .find_map(|log| GuardEvent::decode(&log.topics, &log.data))
+    }
+}
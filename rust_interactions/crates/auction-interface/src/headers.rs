@@ -0,0 +1,144 @@
+use std::pin::Pin;
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::{client::WsConnect, types::eth::Header},
+    transports::{http::Http, RpcError, TransportError},
+};
+use alloy_primitives::{B256, U64};
+use async_trait::async_trait;
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use futures_util::{Stream, StreamExt};
+use reqwest::Client as ReqwestClient;
+use serde::{Deserialize, Serialize};
+
+use crate::{amm_auction::http_provider, amm_auction_config::AmmAuctionConfig};
+
+/// Fields of an L1 block header the listener follows the chain with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct L1Header {
+    pub number: u64,
+    pub hash: B256,
+    pub parent_hash: B256,
+    pub timestamp: u64,
+}
+
+/// New L1 headers in the order the node announced them.
+pub type HeaderStream = Pin<Box<dyn Stream<Item = eyre::Result<L1Header>> + Send>>;
+
+impl TryFrom<&Header> for L1Header {
+    type Error = eyre::Report;
+
+    fn try_from(header: &Header) -> eyre::Result<Self> {
+        Ok(Self {
+            number: header.number.context("header has no number")?,
+            hash: header.hash.context("header has no hash")?,
+            parent_hash: header.parent_hash,
+            timestamp: header.timestamp,
+        })
+    }
+}
+
+/// Header fields as returned by `eth_getBlockByNumber`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawHeader {
+    number: U64,
+    hash: B256,
+    parent_hash: B256,
+    timestamp: U64,
+}
+
+impl From<RawHeader> for L1Header {
+    fn from(header: RawHeader) -> Self {
+        Self {
+            number: header.number.to::<u64>(),
+            hash: header.hash,
+            parent_hash: header.parent_hash,
+            timestamp: header.timestamp.to::<u64>(),
+        }
+    }
+}
+
+/// Reads L1 headers by number.
+#[async_trait]
+pub trait HeaderSource: Send + Sync {
+    /// Header of block `number`, `None` if it isn't produced yet.
+    async fn header(&self, number: u64) -> eyre::Result<Option<L1Header>>;
+}
+
+/// Reads L1 headers over http, for filling in blocks a subscription missed.
+pub struct HeaderClient {
+    provider: RootProvider<Http<ReqwestClient>>,
+}
+
+impl HeaderClient {
+    pub fn new(l1_rpc: &str) -> eyre::Result<Self> {
+        Ok(Self {
+            provider: http_provider(l1_rpc)?,
+        })
+    }
+
+    pub fn from_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        Self::new(&config.rpc_url_l1)
+    }
+
+    pub async fn latest_number(&self) -> eyre::Result<u64> {
+        self.provider
+            .get_block_number()
+            .await
+            .map_err(node_error)
+            .context("failed to get latest L1 block number")
+    }
+}
+
+#[async_trait]
+impl HeaderSource for HeaderClient {
+    async fn header(&self, number: u64) -> eyre::Result<Option<L1Header>> {
+        let header: Option<RawHeader> = self
+            .provider
+            .client()
+            .request("eth_getBlockByNumber", (U64::from(number), false))
+            .await
+            .map_err(node_error)
+            .context(format!("failed to get L1 block {}", number))?;
+        Ok(header.map(L1Header::from))
+    }
+}
+
+/// Subscribes to the L1's `newHeads` over its websocket.
+///
+/// The stream ends when the connection drops. Headers that fail to deserialize or are
+/// missing their number or hash come back as errors rather than ending it.
+pub async fn subscribe_headers(ws_url: &str) -> eyre::Result<HeaderStream> {
+    let provider = ProviderBuilder::new()
+        .on_ws(WsConnect::new(ws_url))
+        .await
+        .map_err(node_error)
+        .context("failed to connect to L1 websocket")?;
+    let subscription = provider
+        .subscribe_blocks()
+        .await
+        .map_err(node_error)
+        .context("failed to subscribe to L1 new heads")?;
+
+    let headers = subscription.into_result_stream().map(move |block| {
+        // keep the provider alive for as long as the subscription is used
+        let _provider = &provider;
+        let block = block.context("failed to deserialize L1 new head")?;
+        L1Header::try_from(&block.header)
+            .context(format!("malformed header {:?}", block.header.number))
+    });
+    Ok(Box::pin(headers))
+}
+
+/// Spells out JSON-RPC error objects the node answered with, other errors are kept as is.
+fn node_error(err: TransportError) -> eyre::Report {
+    match err {
+        RpcError::ErrorResp(payload) => {
+            eyre::eyre!("node returned error {}: {}", payload.code, payload.message)
+        }
+        err => eyre::Report::new(err),
+    }
+}
@@ -0,0 +1,283 @@
+use std::sync::Arc;
+
+use alloy::{
+    eips::{eip2718::Encodable2718, BlockId},
+    network::{EthereumSigner, TransactionBuilder},
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::eth::TransactionRequest,
+    signers::{wallet::LocalWallet, Signer},
+    transports::{http::Http, TransportError},
+};
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use reqwest::Client as ReqwestClient;
+use serde::Deserialize;
+use tokio::time::{sleep, Duration};
+
+use crate::{
+    events::RawLog,
+    gas::GasPolicy,
+    nonce_manager::{NonceErrorKind, NonceManager},
+};
+
+/// How often and how many times to poll for an L1 transaction's receipt before giving up.
+const L1_RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const L1_RECEIPT_POLL_ATTEMPTS: u32 = 60;
+
+/// Subset of an L1 transaction receipt.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptFields {
+    status: Option<U64>,
+    block_number: Option<U64>,
+    contract_address: Option<Address>,
+    logs: Vec<RawLog>,
+}
+
+/// Result of an L1 transaction that was included.
+#[derive(Debug)]
+pub struct L1Receipt {
+    pub tx_hash: B256,
+    pub block_number: Option<u64>,
+    pub success: bool,
+    /// Address of the contract a deployment created
+    pub contract_address: Option<Address>,
+    pub(crate) logs: Vec<RawLog>,
+}
+
+/// Signs and sends L1 transactions from a single wallet.
+///
+/// Only plain Ethereum transactions are built so this also works against Suave for
+/// transactions that aren't CCRs, like contract deployments. Nonces come from a
+/// `NonceManager`, share the wrapper's with `with_nonce_manager()` when it sends from the
+/// same wallet.
+pub struct L1Client {
+    provider: RootProvider<Http<ReqwestClient>>,
+    wallet: LocalWallet,
+    gas: GasPolicy,
+    nonces: Arc<NonceManager>,
+}
+
+impl L1Client {
+    pub fn new(rpc_url: &str, wallet: LocalWallet) -> eyre::Result<Self> {
+        let rpc_url = url::Url::parse(rpc_url).context("failed to build url from L1 rpc string")?;
+        let provider = ProviderBuilder::new()
+            .on_http(rpc_url)
+            .context("failed to build provider from given rpc url")?;
+
+        Ok(Self {
+            provider,
+            wallet,
+            gas: GasPolicy::l1_default(),
+            nonces: Arc::new(NonceManager::new()),
+        })
+    }
+
+    /// Hands out nonces from `nonces` instead of the client's own.
+    pub fn with_nonce_manager(mut self, nonces: Arc<NonceManager>) -> Self {
+        self.nonces = nonces;
+        self
+    }
+
+    /// Overrides the gas policy used for sent transactions.
+    pub fn with_gas_policy(mut self, gas: GasPolicy) -> Self {
+        self.gas = gas;
+        self
+    }
+
+    /// Address transactions are sent from.
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    pub fn provider(&self) -> &RootProvider<Http<ReqwestClient>> {
+        &self.provider
+    }
+
+    /// `eth_call`s `input` on `to` from this client's address against `block`.
+    ///
+    /// The node's error is returned as is so callers can decode revert data out of it.
+    pub async fn call(
+        &self,
+        to: Address,
+        input: Bytes,
+        block: BlockId,
+    ) -> Result<Bytes, TransportError> {
+        let tx = TransactionRequest::default()
+            .from(self.address())
+            .to(to)
+            .input(input.into());
+        self.provider.call(&tx, block).await
+    }
+
+    /// Signs and sends a transaction, then waits for it to be included.
+    pub async fn send(&self, to: Address, input: Bytes, value: U256) -> eyre::Result<L1Receipt> {
+        let tx = TransactionRequest::default().to(to).with_value(value);
+        self.sign_and_send(tx, input).await
+    }
+
+    /// Deploys a contract from its creation code (bytecode plus encoded constructor args),
+    /// returning the created contract's address.
+    pub async fn deploy(&self, creation_code: Bytes) -> eyre::Result<Address> {
+        let (tx_hash, _) = self.submit_deployment(creation_code).await?;
+        self.wait_for_deployment(tx_hash).await
+    }
+
+    /// Sends a deployment without waiting for it, returning its hash and the address the
+    /// contract is created at once it lands.
+    pub async fn submit_deployment(&self, creation_code: Bytes) -> eyre::Result<(B256, Address)> {
+        let (tx_hash, nonce) = self
+            .submit(TransactionRequest::default(), creation_code)
+            .await
+            .context("failed to send deployment")?;
+        Ok((tx_hash, self.address().create(nonce)))
+    }
+
+    /// Waits for a deployment to be included, returning the created contract's address.
+    pub async fn wait_for_deployment(&self, tx_hash: B256) -> eyre::Result<Address> {
+        let receipt = self.wait_for_receipt(tx_hash).await?;
+        eyre::ensure!(receipt.success, "deployment {} reverted", receipt.tx_hash);
+        receipt.contract_address.context(format!(
+            "deployment {} created no contract",
+            receipt.tx_hash
+        ))
+    }
+
+    /// Whether there is contract code at `address` in the latest block.
+    pub async fn has_code(&self, address: Address) -> eyre::Result<bool> {
+        let code: Bytes = self
+            .provider
+            .client()
+            .request("eth_getCode", (address, BlockId::latest()))
+            .await
+            .context(format!("failed to get code at {}", address))?;
+        Ok(!code.is_empty())
+    }
+
+    async fn sign_and_send(&self, tx: TransactionRequest, input: Bytes) -> eyre::Result<L1Receipt> {
+        let (tx_hash, _) = self.submit(tx, input).await?;
+        self.wait_for_receipt(tx_hash).await
+    }
+
+    /// Signs and sends a transaction with the next nonce, returning its hash and nonce.
+    async fn submit(&self, tx: TransactionRequest, input: Bytes) -> eyre::Result<(B256, u64)> {
+        let gas_price = self.gas.gas_price(
+            self.provider
+                .get_gas_price()
+                .await
+                .context("failed to get gas price")?,
+        );
+        let chain_id = self
+            .provider
+            .get_chain_id()
+            .await
+            .context("failed to get chain id")?;
+        let pending_count = self
+            .provider
+            .get_transaction_count(self.address(), BlockId::pending())
+            .await
+            .context("failed to get transaction count for address")?;
+        let nonce = self
+            .nonces
+            .next_nonce(chain_id, self.address(), pending_count)
+            .await
+            .context("failed to reserve nonce")?;
+
+        let tx = tx
+            .from(self.address())
+            .with_gas_price(gas_price)
+            .with_chain_id(chain_id)
+            .with_nonce(nonce)
+            .input(input.clone().into());
+
+        let estimate = if self.gas.should_estimate() {
+            self.provider
+                .estimate_gas(&tx, BlockId::pending())
+                .await
+                .ok()
+        } else {
+            None
+        };
+        let gas = self.gas.gas_limit(&input, estimate);
+
+        let tx_hash = match self.sign_and_send_raw(tx.gas_limit(gas)).await {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                // a rejected nonce is resynced, any other unsent one is handed out again
+                if NonceErrorKind::classify(&e).is_some() {
+                    let pending_count = self
+                        .provider
+                        .get_transaction_count(self.address(), BlockId::pending())
+                        .await
+                        .context("failed to get transaction count for address")?;
+                    self.nonces
+                        .resync(chain_id, self.address(), pending_count)
+                        .await?;
+                } else {
+                    self.nonces.release(chain_id, self.address(), nonce).await?;
+                }
+                return Err(e);
+            }
+        };
+        Ok((tx_hash, nonce))
+    }
+
+    async fn sign_and_send_raw(&self, tx: TransactionRequest) -> eyre::Result<B256> {
+        let mut encoded_tx = Vec::new();
+        tx.build(&EthereumSigner::from(self.wallet.clone()))
+            .await
+            .context("failed to sign transaction")?
+            .encode_2718(&mut encoded_tx);
+
+        let pending_tx = self
+            .provider
+            .send_raw_transaction(&encoded_tx)
+            .await
+            .context("failed to send transaction")?;
+        Ok(*pending_tx.tx_hash())
+    }
+
+    /// Polls for a transaction's receipt until it's included.
+    pub async fn wait_for_receipt(&self, tx_hash: B256) -> eyre::Result<L1Receipt> {
+        for _ in 0..L1_RECEIPT_POLL_ATTEMPTS {
+            let receipt: Option<ReceiptFields> = self
+                .provider
+                .client()
+                .request("eth_getTransactionReceipt", (tx_hash,))
+                .await
+                .context("failed to get transaction receipt")?;
+            if let Some(receipt) = receipt {
+                return Ok(L1Receipt {
+                    tx_hash,
+                    block_number: receipt.block_number.map(|number| number.to::<u64>()),
+                    success: receipt.status == Some(U64::from(1)),
+                    contract_address: receipt.contract_address,
+                    logs: receipt.logs,
+                });
+            }
+            sleep(L1_RECEIPT_POLL_INTERVAL).await;
+        }
+        eyre::bail!("timed out waiting for receipt of transaction {}", tx_hash)
+    }
+
+    /// Waits until a block after the current one is produced.
+    pub async fn wait_for_next_block(&self) -> eyre::Result<u64> {
+        let current_block = self
+            .provider
+            .get_block_number()
+            .await
+            .context("failed to get block number")?;
+        loop {
+            sleep(Duration::from_secs(1)).await;
+            let block = self
+                .provider
+                .get_block_number()
+                .await
+                .context("failed to get block number")?;
+            if block > current_block {
+                return Ok(block);
+            }
+        }
+    }
+}
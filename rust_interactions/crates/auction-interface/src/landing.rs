@@ -0,0 +1,599 @@
+use std::fmt;
+
+use alloy::{
+    providers::{Provider, RootProvider},
+    transports::http::Http,
+};
+use alloy_primitives::{Address, Bytes, B256, U64};
+use alloy_sol_types::SolCall;
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use reqwest::Client as ReqwestClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    amm_auction::{http_provider, IAuctionGuard},
+    amm_auction_config::AmmAuctionConfig,
+    events::{GuardEvent, RawLog},
+    guard::GuardClient,
+};
+
+/// How much of an auction's bundle made it into its block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LandingStatus {
+    /// Every transaction landed in order and the auction's guard event fired
+    Landed,
+    /// The auction results landed but some of the bundle's transactions or the event didn't
+    PartiallyLanded,
+    /// The auction results aren't in the block
+    Missing,
+    /// The bundle's transactions landed out of order
+    Reordered,
+}
+
+/// A transaction found in the auctioned block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LandedTx {
+    pub hash: B256,
+    /// Position in the block
+    pub index: u64,
+}
+
+/// What of an auction's bundle landed in the block it was for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleLandingReport {
+    pub block_number: u64,
+    pub status: LandingStatus,
+    /// The suapp key's `postAuctionResults()` transaction
+    pub post_results: Option<LandedTx>,
+    /// Bidder the results named, if the auction had a winner
+    pub winner: Option<Address>,
+    pub winner_swap: Option<LandedTx>,
+    /// Non-bid swaps the suapp included in the bundle, if known
+    pub non_bid_expected: Option<u64>,
+    /// Non-bid swaps in their place after the auction's transactions, or without the bundle
+    /// records, the swap router transactions there
+    pub non_bid_landed: u64,
+    /// `SuccessfulPayment` or `AuctionSucceeded` emitted by the results transaction
+    pub auction_event_fired: bool,
+    /// What kept the bundle from fully landing
+    pub issues: Vec<String>,
+}
+
+impl fmt::Display for BundleLandingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Bundle Landing")?;
+        writeln!(f, "  block                : {}", self.block_number)?;
+        writeln!(f, "  status               : {:?}", self.status)?;
+        match &self.post_results {
+            Some(tx) => writeln!(
+                f,
+                "  post results tx      : {} (index {})",
+                tx.hash, tx.index
+            )?,
+            None => writeln!(f, "  post results tx      : none")?,
+        }
+        match (&self.winner, &self.winner_swap) {
+            (Some(winner), Some(tx)) => writeln!(
+                f,
+                "  winner swap          : {} from {} (index {})",
+                tx.hash, winner, tx.index
+            )?,
+            (Some(winner), None) => writeln!(f, "  winner swap          : none from {}", winner)?,
+            (None, _) => writeln!(f, "  winner swap          : no winner")?,
+        }
+        match self.non_bid_expected {
+            Some(expected) => writeln!(
+                f,
+                "  non-bid swaps        : {} of {}",
+                self.non_bid_landed, expected
+            )?,
+            None => writeln!(f, "  non-bid swaps        : {}", self.non_bid_landed)?,
+        }
+        write!(f, "  auction event fired  : {}", self.auction_event_fired)?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Subset of a block's transaction.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockTx {
+    hash: B256,
+    from: Address,
+    to: Option<Address>,
+    input: Bytes,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockFields {
+    transactions: Vec<BlockTx>,
+}
+
+/// Subset of a bundle served by the mock builder's `mock_getBundles`.
+#[derive(Debug, Deserialize)]
+struct RecordedBundleHashes {
+    txs: Vec<RecordedTxHash>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordedTxHash {
+    hash: B256,
+}
+
+/// Checks auctioned L1 blocks for the bundle the suapp sent for them.
+///
+/// The suapp's bundle is its key's `postAuctionResults()` transaction, then the winner's swap
+/// if there was a winner, then the non-bid swaps it holds in the order they were sent. With
+/// the bundle records of a `MockBuilder` the block is matched against the bundle the suapp
+/// sent, hash by hash. Without them only the results transaction and the winner's swap right
+/// after it can be told apart, and the non-bid swaps are counted as the swap router
+/// transactions following them, which may include others' swaps.
+pub struct BundleVerifier {
+    provider: RootProvider<Http<ReqwestClient>>,
+    guard: GuardClient,
+    auction_guard: Address,
+    swap_router: Address,
+    /// Mock builder the suapp's bundles are read from, if set
+    bundle_records: Option<RootProvider<Http<ReqwestClient>>>,
+}
+
+impl BundleVerifier {
+    pub fn new(l1_rpc: &str, auction_guard: Address, swap_router: Address) -> eyre::Result<Self> {
+        Ok(Self {
+            provider: http_provider(l1_rpc)?,
+            guard: GuardClient::new(l1_rpc, auction_guard)?,
+            auction_guard,
+            swap_router,
+            bundle_records: None,
+        })
+    }
+
+    /// Reads the bundle records from `RPC_URL_BUNDLE` if `VERIFY_BUNDLE_RECORDS` is set.
+    pub fn from_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        let verifier = Self::new(
+            &config.rpc_url_l1,
+            config.auction_guard.context("auction guard not set")?,
+            config.swap_router.context("swap router not set")?,
+        )?;
+        if config.verify_bundle_records {
+            verifier.with_bundle_records(&config.rpc_url_bundle)
+        } else {
+            Ok(verifier)
+        }
+    }
+
+    /// Matches blocks against the bundles the mock builder at `bundle_rpc` received.
+    pub fn with_bundle_records(mut self, bundle_rpc: &str) -> eyre::Result<Self> {
+        self.bundle_records = Some(http_provider(bundle_rpc)?);
+        Ok(self)
+    }
+
+    /// Checks the bundle for `block_number` landed. `non_bid_expected` is the number of
+    /// non-bid swaps the suapp put in it, from its stats, if known. The bundle records take
+    /// precedence over it.
+    pub async fn verify(
+        &self,
+        block_number: u64,
+        non_bid_expected: Option<u64>,
+    ) -> eyre::Result<BundleLandingReport> {
+        let block: Option<BlockFields> = self
+            .provider
+            .client()
+            .request("eth_getBlockByNumber", (U64::from(block_number), true))
+            .await
+            .context(format!("failed to get block {}", block_number))?;
+        let transactions = block
+            .context(format!("block {} not produced yet", block_number))?
+            .transactions;
+        let suapp_key = self
+            .guard
+            .suapp_key_at(block_number)
+            .await
+            .context("failed to get suapp key")?;
+        let recorded = match &self.bundle_records {
+            Some(bundle_records) => Some(
+                recorded_bundle(bundle_records, block_number)
+                    .await?
+                    .context(format!(
+                        "the mock builder received no bundle for block {}",
+                        block_number
+                    ))?,
+            ),
+            None => None,
+        };
+
+        let mut report = BundleLandingReport {
+            block_number,
+            status: LandingStatus::Landed,
+            post_results: None,
+            winner: None,
+            winner_swap: None,
+            non_bid_expected,
+            non_bid_landed: 0,
+            auction_event_fired: false,
+            issues: Vec::new(),
+        };
+
+        let post_results = transactions.iter().enumerate().find(|(_, tx)| {
+            tx.from == suapp_key
+                && tx.to == Some(self.auction_guard)
+                && tx
+                    .input
+                    .starts_with(&IAuctionGuard::postAuctionResultsCall::SELECTOR)
+                && recorded
+                    .as_ref()
+                    .map_or(true, |recorded| recorded.first() == Some(&tx.hash))
+        });
+        let Some((post_index, post_tx)) = post_results else {
+            report.status = LandingStatus::Missing;
+            report.issues.push(
+                match recorded.as_ref().and_then(|recorded| recorded.first()) {
+                    Some(hash) => format!(
+                        "the bundle's postAuctionResults() {} isn't in the block",
+                        hash
+                    ),
+                    None => format!("no postAuctionResults() from suapp key {}", suapp_key),
+                },
+            );
+            return Ok(report);
+        };
+        report.post_results = Some(LandedTx {
+            hash: post_tx.hash,
+            index: post_index as u64,
+        });
+
+        let results = IAuctionGuard::postAuctionResultsCall::abi_decode(&post_tx.input, true)
+            .context("failed to decode postAuctionResults() input")?;
+        if results.auction {
+            report.winner = Some(results.bidder);
+        }
+        let (reordered, mut partial) = match &recorded {
+            Some(recorded) => self.match_recorded(&mut report, &transactions, post_index, recorded),
+            None => self.match_identities(&mut report, &transactions, post_index),
+        };
+
+        report.auction_event_fired = self.auction_event_fired(block_number, post_tx.hash).await?;
+        if results.auction && !report.auction_event_fired {
+            partial = true;
+            report
+                .issues
+                .push("payment failed, no SuccessfulPayment or AuctionSucceeded".to_string());
+        }
+
+        report.status = if reordered {
+            LandingStatus::Reordered
+        } else if partial {
+            LandingStatus::PartiallyLanded
+        } else {
+            LandingStatus::Landed
+        };
+        Ok(report)
+    }
+
+    /// Compares the transactions from the results one on with the recorded bundle's, position
+    /// by position. Returns if any landed elsewhere and if any are missing.
+    fn match_recorded(
+        &self,
+        report: &mut BundleLandingReport,
+        transactions: &[BlockTx],
+        post_index: usize,
+        recorded: &[B256],
+    ) -> (bool, bool) {
+        let mut reordered = false;
+        let mut partial = false;
+        let non_bid_start = if report.winner.is_some() { 2 } else { 1 };
+        report.non_bid_expected = Some(recorded.len().saturating_sub(non_bid_start) as u64);
+
+        for (position, expected) in recorded.iter().enumerate().skip(1) {
+            let index = post_index + position;
+            let landed_at = transactions.iter().position(|tx| tx.hash == *expected);
+            match landed_at {
+                Some(landed_at) if landed_at == index => {
+                    if position >= non_bid_start {
+                        report.non_bid_landed += 1;
+                    }
+                }
+                Some(landed_at) => {
+                    reordered = true;
+                    report.issues.push(format!(
+                        "bundle tx {} {} at index {} instead of {}",
+                        position, expected, landed_at, index
+                    ));
+                }
+                None => {
+                    partial = true;
+                    report.issues.push(format!(
+                        "bundle tx {} {} isn't in the block",
+                        position, expected
+                    ));
+                }
+            }
+            if position < non_bid_start {
+                report.winner_swap = landed_at.map(|landed_at| LandedTx {
+                    hash: *expected,
+                    index: landed_at as u64,
+                });
+            }
+        }
+        (reordered, partial)
+    }
+
+    /// Takes the winner's swap right after the results and counts the swap router
+    /// transactions following as non-bid swaps. Returns if any are missing, nothing can be
+    /// found to be reordered without the bundle.
+    fn match_identities(
+        &self,
+        report: &mut BundleLandingReport,
+        transactions: &[BlockTx],
+        post_index: usize,
+    ) -> (bool, bool) {
+        let mut partial = false;
+        let mut non_bid_start = post_index + 1;
+        if let Some(winner) = report.winner {
+            match transactions.get(post_index + 1) {
+                Some(tx) if tx.from == winner && tx.to == Some(self.swap_router) => {
+                    report.winner_swap = Some(LandedTx {
+                        hash: tx.hash,
+                        index: post_index as u64 + 1,
+                    });
+                    non_bid_start += 1;
+                }
+                _ => {
+                    partial = true;
+                    report.issues.push(format!(
+                        "no swap from winner {} right after the results at {}",
+                        winner, post_index
+                    ));
+                }
+            }
+        }
+
+        report.non_bid_landed = transactions
+            .iter()
+            .skip(non_bid_start)
+            .take_while(|tx| tx.to == Some(self.swap_router))
+            .count() as u64;
+        if let Some(expected) = report.non_bid_expected {
+            if report.non_bid_landed < expected {
+                partial = true;
+                report.issues.push(format!(
+                    "{} of {} non-bid swaps follow the auction's transactions",
+                    report.non_bid_landed, expected
+                ));
+            }
+        }
+        (false, partial)
+    }
+
+    /// If the guard emitted `SuccessfulPayment` or `AuctionSucceeded` in `tx_hash`.
+    async fn auction_event_fired(&self, block_number: u64, tx_hash: B256) -> eyre::Result<bool> {
+        let filter = json!({
+            "fromBlock": U64::from(block_number),
+            "toBlock": U64::from(block_number),
+            "address": self.auction_guard,
+        });
+        let logs: Vec<RawLog> = self
+            .provider
+            .client()
+            .request("eth_getLogs", (filter,))
+            .await
+            .context(format!(
+                "failed to get guard logs of block {}",
+                block_number
+            ))?;
+        Ok(logs
+            .iter()
+            .filter(|log| log.transaction_hash == Some(tx_hash))
+            .filter_map(|log| GuardEvent::decode(&log.topics, &log.data))
+            .any(|event| {
+                matches!(
+                    event,
+                    GuardEvent::SuccessfulPayment | GuardEvent::AuctionSucceeded
+                )
+            }))
+    }
+}
+
+/// Hashes of the last bundle the mock builder received for `block_number`, in order.
+async fn recorded_bundle(
+    bundle_records: &RootProvider<Http<ReqwestClient>>,
+    block_number: u64,
+) -> eyre::Result<Option<Vec<B256>>> {
+    let bundles: Vec<RecordedBundleHashes> = bundle_records
+        .client()
+        .request("mock_getBundles", (U64::from(block_number),))
+        .await
+        .context(format!(
+            "failed to get the mock builder's bundles for block {}",
+            block_number
+        ))?;
+    Ok(bundles
+        .last()
+        .map(|bundle| bundle.txs.iter().map(|tx| tx.hash).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUARD: Address = Address::repeat_byte(0x0a);
+    const ROUTER: Address = Address::repeat_byte(0x0b);
+    const SUAPP_KEY: Address = Address::repeat_byte(0x0c);
+    const WINNER: Address = Address::repeat_byte(0x0d);
+    const SWAPPER: Address = Address::repeat_byte(0x0e);
+
+    fn verifier() -> BundleVerifier {
+        BundleVerifier::new("http://127.0.0.1:8545", GUARD, ROUTER).unwrap()
+    }
+
+    fn tx(hash: u8, from: Address, to: Address) -> BlockTx {
+        BlockTx {
+            hash: B256::repeat_byte(hash),
+            from,
+            to: Some(to),
+            input: Bytes::new(),
+        }
+    }
+
+    fn empty_report(winner: Option<Address>, non_bid_expected: Option<u64>) -> BundleLandingReport {
+        BundleLandingReport {
+            block_number: 100,
+            status: LandingStatus::Landed,
+            post_results: None,
+            winner,
+            winner_swap: None,
+            non_bid_expected,
+            non_bid_landed: 0,
+            auction_event_fired: false,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Results, winner's swap and two non-bid swaps.
+    fn recorded() -> Vec<B256> {
+        [0x10, 0x11, 0x12, 0x13].map(B256::repeat_byte).to_vec()
+    }
+
+    #[test]
+    fn recorded_bundle_landed() {
+        let transactions = [
+            tx(0x01, SWAPPER, ROUTER),
+            tx(0x10, SUAPP_KEY, GUARD),
+            tx(0x11, WINNER, ROUTER),
+            tx(0x12, SWAPPER, ROUTER),
+            tx(0x13, SWAPPER, ROUTER),
+        ];
+        let mut report = empty_report(Some(WINNER), None);
+        let (reordered, partial) =
+            verifier().match_recorded(&mut report, &transactions, 1, &recorded());
+
+        assert!(!reordered && !partial);
+        assert_eq!(
+            report.winner_swap,
+            Some(LandedTx {
+                hash: B256::repeat_byte(0x11),
+                index: 2
+            })
+        );
+        assert_eq!(report.non_bid_expected, Some(2));
+        assert_eq!(report.non_bid_landed, 2);
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn recorded_bundle_reordered() {
+        let transactions = [
+            tx(0x10, SUAPP_KEY, GUARD),
+            tx(0x12, SWAPPER, ROUTER),
+            tx(0x11, WINNER, ROUTER),
+            tx(0x13, SWAPPER, ROUTER),
+        ];
+        let mut report = empty_report(Some(WINNER), None);
+        let (reordered, partial) =
+            verifier().match_recorded(&mut report, &transactions, 0, &recorded());
+
+        assert!(reordered && !partial);
+        // the winner's swap is still reported where it landed
+        assert_eq!(
+            report.winner_swap,
+            Some(LandedTx {
+                hash: B256::repeat_byte(0x11),
+                index: 2
+            })
+        );
+        assert_eq!(report.non_bid_landed, 1);
+        assert_eq!(report.issues.len(), 2);
+    }
+
+    #[test]
+    fn recorded_bundle_partially_landed() {
+        let transactions = [tx(0x10, SUAPP_KEY, GUARD), tx(0x11, WINNER, ROUTER)];
+        let mut report = empty_report(Some(WINNER), None);
+        let (reordered, partial) =
+            verifier().match_recorded(&mut report, &transactions, 0, &recorded());
+
+        assert!(!reordered && partial);
+        assert!(report.winner_swap.is_some());
+        assert_eq!(report.non_bid_expected, Some(2));
+        assert_eq!(report.non_bid_landed, 0);
+        assert_eq!(report.issues.len(), 2);
+    }
+
+    #[test]
+    fn recorded_bundle_missing_after_the_results() {
+        let transactions = [tx(0x10, SUAPP_KEY, GUARD), tx(0x01, SWAPPER, ROUTER)];
+        let mut report = empty_report(Some(WINNER), None);
+        let (reordered, partial) =
+            verifier().match_recorded(&mut report, &transactions, 0, &recorded());
+
+        assert!(!reordered && partial);
+        assert_eq!(report.winner_swap, None);
+        assert_eq!(report.non_bid_landed, 0);
+        assert_eq!(report.issues.len(), 3);
+    }
+
+    #[test]
+    fn identities_landed() {
+        let transactions = [
+            tx(0x10, SUAPP_KEY, GUARD),
+            tx(0x11, WINNER, ROUTER),
+            tx(0x12, SWAPPER, ROUTER),
+            tx(0x13, SWAPPER, ROUTER),
+            // not a swap, ends the non-bid swaps
+            tx(0x14, SWAPPER, GUARD),
+            tx(0x15, SWAPPER, ROUTER),
+        ];
+        let mut report = empty_report(Some(WINNER), Some(2));
+        let (reordered, partial) = verifier().match_identities(&mut report, &transactions, 0);
+
+        assert!(!reordered && !partial);
+        assert_eq!(
+            report.winner_swap,
+            Some(LandedTx {
+                hash: B256::repeat_byte(0x11),
+                index: 1
+            })
+        );
+        assert_eq!(report.non_bid_landed, 2);
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn identities_without_the_winners_swap() {
+        // someone else's swap landed right after the results
+        let transactions = [
+            tx(0x10, SUAPP_KEY, GUARD),
+            tx(0x12, SWAPPER, ROUTER),
+            tx(0x11, WINNER, ROUTER),
+        ];
+        let mut report = empty_report(Some(WINNER), Some(3));
+        let (reordered, partial) = verifier().match_identities(&mut report, &transactions, 0);
+
+        assert!(!reordered && partial);
+        assert_eq!(report.winner_swap, None);
+        assert_eq!(report.non_bid_landed, 2);
+        assert_eq!(report.issues.len(), 2);
+    }
+
+    #[test]
+    fn identities_missing_non_bid_swaps() {
+        let transactions = [tx(0x10, SUAPP_KEY, GUARD)];
+        let mut report = empty_report(None, Some(1));
+        let (reordered, partial) = verifier().match_identities(&mut report, &transactions, 0);
+
+        assert!(!reordered && partial);
+        assert_eq!(report.non_bid_landed, 0);
+        assert_eq!(report.issues.len(), 1);
+
+        // without the suapp's stats nothing is known to be missing
+        let mut report = empty_report(None, None);
+        let (reordered, partial) = verifier().match_identities(&mut report, &transactions, 0);
+        assert!(!reordered && !partial);
+    }
+}
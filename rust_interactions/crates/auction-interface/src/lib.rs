@@ -1,2 +1,26 @@
+pub mod accounts;
 pub mod amm_auction;
 pub mod amm_auction_config;
+pub mod arbitrage;
+pub mod bid_signer;
+pub mod bid_validation;
+pub mod ccr;
+pub mod deploy;
+pub mod deposits;
+pub mod events;
+pub mod gas;
+pub mod guard;
+pub mod headers;
+pub mod l1_client;
+pub mod landing;
+pub mod mock_builder;
+pub mod mock_kettle;
+pub mod nonce_manager;
+pub mod pool;
+pub mod pool_lock;
+pub mod price_feed;
+pub mod raw_tx;
+pub mod rpc_server;
+pub mod swap;
+pub mod ticker_server;
+pub mod v3_math;
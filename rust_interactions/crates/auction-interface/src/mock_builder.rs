@@ -0,0 +1,762 @@
+use std::{fmt, sync::Arc};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::http::Http,
+};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256, U64};
+use alloy_sol_types::SolCall;
+use async_trait::async_trait;
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use reqwest::Client as ReqwestClient;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::{
+    amm_auction::IAuctionGuard,
+    amm_auction_config::AmmAuctionConfig,
+    raw_tx::RawTransaction,
+    rpc_server::{param, RpcError, RpcHandler, RpcServer},
+};
+
+/// What a transaction is in the suapp's bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BundleTxKind {
+    /// The suapp key's `postAuctionResults()`, paying for the auction
+    Payment {
+        bidder: Address,
+        valid_block: U256,
+        price: U256,
+        auction: bool,
+    },
+    /// The winning bid's swap, right after the payment
+    WinnerSwap,
+    /// A non-bid swap, allowed to revert
+    NonBidSwap,
+    Other,
+    /// Not a transaction that could be decoded
+    Undecodable {
+        error: String,
+    },
+}
+
+/// A transaction of a received bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleTx {
+    pub hash: B256,
+    pub raw: Bytes,
+    pub kind: BundleTxKind,
+    /// If the bundle lists it in `revertingTxHashes`
+    pub may_revert: bool,
+    /// The decoded transaction, if it could be
+    pub tx: Option<RawTransaction>,
+}
+
+/// How a transaction of an applied bundle went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppliedTx {
+    pub hash: B256,
+    /// Position in the block, `None` if anvil rejected it
+    pub index: Option<u64>,
+    pub success: bool,
+}
+
+/// What happened applying a bundle to the anvil chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ApplyOutcome {
+    /// The bundle was mined in its block
+    Included {
+        block_number: u64,
+        txs: Vec<AppliedTx>,
+    },
+    /// A transaction not allowed to revert failed, the chain was rolled back like a builder
+    /// drops the bundle
+    Dropped { reason: String },
+    /// The bundle's block was already produced
+    Skipped { reason: String },
+    /// Anvil errored, anything already mined of the bundle was rolled back
+    Failed { error: String },
+}
+
+/// A bundle the mock builder received.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordedBundle {
+    /// Order the bundle was received in, from 0
+    pub index: usize,
+    pub block_number: u64,
+    /// keccak256 of the concatenated transaction hashes
+    pub bundle_hash: B256,
+    pub txs: Vec<BundleTx>,
+    pub reverting_tx_hashes: Vec<B256>,
+    /// Set when the builder applies bundles to anvil
+    pub applied: Option<ApplyOutcome>,
+}
+
+impl RecordedBundle {
+    /// The bundle's `postAuctionResults()` transaction.
+    pub fn payment(&self) -> Option<&BundleTx> {
+        self.txs
+            .iter()
+            .find(|tx| matches!(tx.kind, BundleTxKind::Payment { .. }))
+    }
+
+    pub fn winner_swap(&self) -> Option<&BundleTx> {
+        self.txs
+            .iter()
+            .find(|tx| tx.kind == BundleTxKind::WinnerSwap)
+    }
+
+    pub fn non_bid_swaps(&self) -> Vec<&BundleTx> {
+        self.txs
+            .iter()
+            .filter(|tx| tx.kind == BundleTxKind::NonBidSwap)
+            .collect()
+    }
+}
+
+impl fmt::Display for RecordedBundle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bundle {} for block {}: {} txs, {} may revert",
+            self.bundle_hash,
+            self.block_number,
+            self.txs.len(),
+            self.reverting_tx_hashes.len()
+        )?;
+        for tx in &self.txs {
+            write!(f, "\n  {} ", tx.hash)?;
+            match &tx.kind {
+                BundleTxKind::Payment {
+                    bidder,
+                    valid_block,
+                    price,
+                    auction,
+                } => write!(
+                    f,
+                    "payment: bidder {}, block {}, price {}, auction {}",
+                    bidder, valid_block, price, auction
+                )?,
+                BundleTxKind::WinnerSwap => write!(f, "winner swap")?,
+                BundleTxKind::NonBidSwap => write!(f, "non-bid swap")?,
+                BundleTxKind::Other => write!(f, "other")?,
+                BundleTxKind::Undecodable { error } => write!(f, "undecodable: {}", error)?,
+            }
+            if let Some(tx) = &tx.tx {
+                write!(f, " from {}", tx.from)?;
+            }
+        }
+        match &self.applied {
+            Some(ApplyOutcome::Included { block_number, txs }) => write!(
+                f,
+                "\n  applied in block {}, {} of {} txs succeeded",
+                block_number,
+                txs.iter().filter(|tx| tx.success).count(),
+                txs.len()
+            )?,
+            Some(ApplyOutcome::Dropped { reason }) => write!(f, "\n  dropped: {}", reason)?,
+            Some(ApplyOutcome::Skipped { reason }) => write!(f, "\n  not applied: {}", reason)?,
+            Some(ApplyOutcome::Failed { error }) => write!(f, "\n  failed to apply: {}", error)?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// What the mock builder knows about the deployment, for decoding bundles.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockBuilderConfig {
+    /// Payments are only recognized when sent to the guard, if set
+    pub auction_guard: Option<Address>,
+    /// Transactions to the router are recognized as non-bid swaps even when not allowed to
+    /// revert, if set
+    pub swap_router: Option<Address>,
+    /// Anvil rpc url to apply bundles to
+    pub anvil_url: Option<String>,
+}
+
+impl MockBuilderConfig {
+    pub fn from_config(config: &AmmAuctionConfig) -> Self {
+        Self {
+            auction_guard: config.auction_guard,
+            swap_router: config.swap_router,
+            anvil_url: None,
+        }
+    }
+
+    pub fn with_anvil(mut self, anvil_url: impl Into<String>) -> Self {
+        self.anvil_url = Some(anvil_url.into());
+        self
+    }
+}
+
+/// A local stand-in for a block builder's `eth_sendBundle`.
+///
+/// Every bundle is recorded with its transactions decoded, and with an anvil url set, mined
+/// into its target block of the anvil chain: empty blocks are mined up to it, then the
+/// bundle's transactions in order. If a transaction not in `revertingTxHashes` fails the
+/// chain is rolled back, as a builder would drop the bundle. Anvil has to be started with
+/// `--order fifo` for the transactions to be mined in the bundle's order, and with the L1's
+/// chain id and state (e.g. `--fork-url`) for them to be valid.
+///
+/// Besides the `bundles()` methods, the recorded bundles are served over JSON-RPC with
+/// `mock_getBundles` (optionally for a block number) and cleared with `mock_clearBundles`.
+pub struct MockBuilder {
+    server: RpcServer,
+    builder: Arc<BuilderRpc>,
+}
+
+impl MockBuilder {
+    /// Starts the mock builder on a free local port.
+    pub async fn spawn(config: MockBuilderConfig) -> eyre::Result<Self> {
+        Self::spawn_on("127.0.0.1:0", config).await
+    }
+
+    pub async fn spawn_on(addr: &str, config: MockBuilderConfig) -> eyre::Result<Self> {
+        let anvil = config
+            .anvil_url
+            .as_deref()
+            .map(|anvil_url| {
+                let anvil_url =
+                    url::Url::parse(anvil_url).context("failed to build url from anvil string")?;
+                ProviderBuilder::new()
+                    .on_http(anvil_url)
+                    .context("failed to build provider from given anvil url")
+            })
+            .transpose()?;
+        let builder = Arc::new(BuilderRpc {
+            config,
+            anvil: anvil.map(Mutex::new),
+            bundles: Mutex::new(Vec::new()),
+        });
+        let server = RpcServer::spawn(addr, builder.clone())
+            .await
+            .context("failed to start mock builder")?;
+        Ok(Self { server, builder })
+    }
+
+    /// Bundle url to give the suapp.
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    /// Every bundle received, oldest first.
+    pub async fn bundles(&self) -> Vec<RecordedBundle> {
+        self.builder.bundles.lock().await.clone()
+    }
+
+    pub async fn bundles_for_block(&self, block_number: u64) -> Vec<RecordedBundle> {
+        self.builder
+            .bundles
+            .lock()
+            .await
+            .iter()
+            .filter(|bundle| bundle.block_number == block_number)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn last_bundle(&self) -> Option<RecordedBundle> {
+        self.builder.bundles.lock().await.last().cloned()
+    }
+
+    pub async fn clear(&self) {
+        self.builder.bundles.lock().await.clear();
+    }
+}
+
+/// `eth_sendBundle` params.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendBundleParams {
+    block_number: U64,
+    txs: Vec<Bytes>,
+    #[serde(default)]
+    reverting_tx_hashes: Vec<B256>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnvilReceipt {
+    status: Option<U64>,
+    block_number: Option<U64>,
+    transaction_index: Option<U64>,
+}
+
+struct BuilderRpc {
+    config: MockBuilderConfig,
+    /// Locked while a bundle is applied so bundles are mined one at a time
+    anvil: Option<Mutex<RootProvider<Http<ReqwestClient>>>>,
+    bundles: Mutex<Vec<RecordedBundle>>,
+}
+
+#[async_trait]
+impl RpcHandler for BuilderRpc {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "eth_sendBundle" => {
+                let bundle: SendBundleParams = param(&params, 0)?;
+                let bundle_hash = self.receive(bundle).await;
+                Ok(json!({ "bundleHash": bundle_hash }))
+            }
+            "mock_getBundles" => {
+                let block_number: Option<U64> = match params.get(0) {
+                    Some(_) => param(&params, 0)?,
+                    None => None,
+                };
+                let bundles = self.bundles.lock().await;
+                Ok(json!(bundles
+                    .iter()
+                    .filter(|bundle| block_number.is_none()
+                        || block_number == Some(U64::from(bundle.block_number)))
+                    .collect::<Vec<_>>()))
+            }
+            "mock_clearBundles" => {
+                self.bundles.lock().await.clear();
+                Ok(json!(true))
+            }
+            method => Err(RpcError::method_not_found(method)),
+        }
+    }
+}
+
+impl BuilderRpc {
+    /// Records the bundle and applies it to anvil, returning its hash.
+    async fn receive(&self, params: SendBundleParams) -> B256 {
+        let mut bundle = RecordedBundle {
+            index: 0,
+            block_number: params.block_number.to::<u64>(),
+            bundle_hash: B256::ZERO,
+            txs: self.decode_txs(&params.txs, &params.reverting_tx_hashes),
+            reverting_tx_hashes: params.reverting_tx_hashes,
+            applied: None,
+        };
+        let mut tx_hashes = Vec::with_capacity(bundle.txs.len() * 32);
+        for tx in &bundle.txs {
+            tx_hashes.extend_from_slice(tx.hash.as_slice());
+        }
+        bundle.bundle_hash = keccak256(&tx_hashes);
+
+        if let Some(anvil) = &self.anvil {
+            let anvil = anvil.lock().await;
+            bundle.applied = Some(match apply_bundle(&anvil, &bundle).await {
+                Ok(outcome) => outcome,
+                Err(e) => ApplyOutcome::Failed {
+                    error: format!("{:#}", e),
+                },
+            });
+        }
+
+        let mut bundles = self.bundles.lock().await;
+        bundle.index = bundles.len();
+        println!("mock builder: {}", bundle);
+        let bundle_hash = bundle.bundle_hash;
+        bundles.push(bundle);
+        bundle_hash
+    }
+
+    /// Decodes the bundle's transactions and tells apart the suapp's payment, the winner's
+    /// swap right after it and the non-bid swaps.
+    fn decode_txs(&self, raw_txs: &[Bytes], reverting_tx_hashes: &[B256]) -> Vec<BundleTx> {
+        let mut txs = Vec::with_capacity(raw_txs.len());
+        let mut winner = None;
+        for raw in raw_txs {
+            let hash = keccak256(raw);
+            let may_revert = reverting_tx_hashes.contains(&hash);
+            let (kind, tx) = match RawTransaction::decode(raw) {
+                Ok(tx) => (self.classify(&tx, may_revert, winner.take()), Some(tx)),
+                Err(e) => (
+                    BundleTxKind::Undecodable {
+                        error: format!("{:#}", e),
+                    },
+                    None,
+                ),
+            };
+            // only the transaction right after the payment can be the winner's swap
+            if let BundleTxKind::Payment {
+                bidder,
+                auction: true,
+                ..
+            } = kind
+            {
+                winner = Some(bidder);
+            }
+            txs.push(BundleTx {
+                hash,
+                raw: raw.clone(),
+                kind,
+                may_revert,
+                tx,
+            });
+        }
+        txs
+    }
+
+    fn classify(
+        &self,
+        tx: &RawTransaction,
+        may_revert: bool,
+        winner: Option<Address>,
+    ) -> BundleTxKind {
+        let to_guard = self.config.auction_guard.is_none() || tx.to == self.config.auction_guard;
+        if to_guard
+            && tx
+                .input
+                .starts_with(&IAuctionGuard::postAuctionResultsCall::SELECTOR)
+        {
+            if let Ok(results) = IAuctionGuard::postAuctionResultsCall::abi_decode(&tx.input, true)
+            {
+                return BundleTxKind::Payment {
+                    bidder: results.bidder,
+                    valid_block: results.validBlock,
+                    price: results.price,
+                    auction: results.auction,
+                };
+            }
+        }
+        if winner == Some(tx.from) {
+            return BundleTxKind::WinnerSwap;
+        }
+        let to_router = self.config.swap_router.is_some() && tx.to == self.config.swap_router;
+        if may_revert || to_router {
+            return BundleTxKind::NonBidSwap;
+        }
+        BundleTxKind::Other
+    }
+}
+
+/// Mines `bundle` into its block of the anvil chain, rolling back if a transaction not
+/// allowed to revert fails or anvil errors midway. Automine is turned back on however it goes.
+async fn apply_bundle(
+    anvil: &RootProvider<Http<ReqwestClient>>,
+    bundle: &RecordedBundle,
+) -> eyre::Result<ApplyOutcome> {
+    let latest = anvil
+        .get_block_number()
+        .await
+        .context("failed to get anvil block number")?;
+    let next_block = latest + 1;
+    if bundle.block_number < next_block {
+        return Ok(ApplyOutcome::Skipped {
+            reason: format!(
+                "block {} already produced, anvil is at {}",
+                bundle.block_number, latest
+            ),
+        });
+    }
+    if bundle.block_number > next_block {
+        let _: Value = anvil
+            .client()
+            .request("anvil_mine", (U64::from(bundle.block_number - next_block),))
+            .await
+            .context("failed to mine up to the bundle's block")?;
+    }
+
+    let snapshot: U256 = anvil
+        .client()
+        .request("evm_snapshot", json!([]))
+        .await
+        .context("failed to snapshot anvil")?;
+    let _: Value = anvil
+        .client()
+        .request("evm_setAutomine", (false,))
+        .await
+        .context("failed to turn off automine")?;
+    let outcome = mine_bundle(anvil, bundle).await;
+    let _: Value = anvil
+        .client()
+        .request("evm_setAutomine", (true,))
+        .await
+        .context("failed to turn automine back on")?;
+
+    // a bundle that failed half way is rolled back like a dropped one
+    if !matches!(outcome, Ok(ApplyOutcome::Included { .. })) {
+        let _: Value = anvil
+            .client()
+            .request("evm_revert", (snapshot,))
+            .await
+            .context("failed to roll back dropped bundle")?;
+    }
+    outcome
+}
+
+async fn mine_bundle(
+    anvil: &RootProvider<Http<ReqwestClient>>,
+    bundle: &RecordedBundle,
+) -> eyre::Result<ApplyOutcome> {
+    let mut sent = Vec::with_capacity(bundle.txs.len());
+    for tx in &bundle.txs {
+        let result: Result<B256, _> = anvil
+            .client()
+            .request("eth_sendRawTransaction", (&tx.raw,))
+            .await;
+        match result {
+            Ok(_) => sent.push(tx),
+            Err(_) if tx.may_revert => {}
+            Err(e) => {
+                return Ok(ApplyOutcome::Dropped {
+                    reason: format!("anvil rejected {}: {}", tx.hash, e),
+                })
+            }
+        }
+    }
+    let _: Value = anvil
+        .client()
+        .request("evm_mine", json!([]))
+        .await
+        .context("failed to mine the bundle's block")?;
+
+    let mut txs = Vec::with_capacity(bundle.txs.len());
+    for tx in &bundle.txs {
+        if !sent.iter().any(|sent| sent.hash == tx.hash) {
+            txs.push(AppliedTx {
+                hash: tx.hash,
+                index: None,
+                success: false,
+            });
+            continue;
+        }
+        let receipt: Option<AnvilReceipt> = anvil
+            .client()
+            .request("eth_getTransactionReceipt", (tx.hash,))
+            .await
+            .context(format!("failed to get receipt of {}", tx.hash))?;
+        let receipt = receipt.context(format!("{} wasn't mined", tx.hash))?;
+        let success = receipt.status == Some(U64::from(1));
+        if receipt.block_number != Some(U64::from(bundle.block_number)) {
+            return Ok(ApplyOutcome::Dropped {
+                reason: format!(
+                    "{} mined in block {:?} instead of {}",
+                    tx.hash, receipt.block_number, bundle.block_number
+                ),
+            });
+        }
+        if !success && !tx.may_revert {
+            return Ok(ApplyOutcome::Dropped {
+                reason: format!("{} reverted and isn't allowed to", tx.hash),
+            });
+        }
+        txs.push(AppliedTx {
+            hash: tx.hash,
+            index: receipt.transaction_index.map(|index| index.to::<u64>()),
+            success,
+        });
+    }
+
+    // fifo ordering keeps the bundle's order, anything else is a misconfigured anvil
+    let indices: Vec<u64> = txs.iter().filter_map(|tx| tx.index).collect();
+    if indices.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Ok(ApplyOutcome::Dropped {
+            reason: "transactions mined out of order, is anvil running with --order fifo?"
+                .to_string(),
+        });
+    }
+
+    Ok(ApplyOutcome::Included {
+        block_number: bundle.block_number,
+        txs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        eips::eip2718::Encodable2718,
+        network::{EthereumSigner, TransactionBuilder},
+        rpc::types::eth::TransactionRequest,
+        signers::wallet::LocalWallet,
+    };
+
+    use super::*;
+
+    /// Anvil's first three dev keys.
+    const SUAPP_SIGNER_KEY: &str =
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const WINNER_KEY: &str = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+    const SWAPPER_KEY: &str = "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804e92b0d6b";
+
+    const CHAIN_ID: u64 = 17000;
+
+    async fn sign(wallet: &LocalWallet, to: Address, nonce: u64, input: Vec<u8>) -> Bytes {
+        let tx = TransactionRequest::default()
+            .from(wallet.address())
+            .to(to)
+            .with_gas_price(1_000_000_000)
+            .with_chain_id(CHAIN_ID)
+            .with_nonce(nonce)
+            .input(Bytes::from(input).into())
+            .gas_limit(200_000);
+        let mut signed = Vec::new();
+        tx.build(&EthereumSigner::from(wallet.clone()))
+            .await
+            .unwrap()
+            .encode_2718(&mut signed);
+        signed.into()
+    }
+
+    #[tokio::test]
+    async fn records_and_classifies_bundles() {
+        let suapp_signer: LocalWallet = SUAPP_SIGNER_KEY.parse().unwrap();
+        let winner: LocalWallet = WINNER_KEY.parse().unwrap();
+        let swapper: LocalWallet = SWAPPER_KEY.parse().unwrap();
+        let (auction_guard, swap_router) = (Address::repeat_byte(4), Address::repeat_byte(7));
+        let builder = MockBuilder::spawn(MockBuilderConfig {
+            auction_guard: Some(auction_guard),
+            swap_router: Some(swap_router),
+            anvil_url: None,
+        })
+        .await
+        .unwrap();
+
+        let payment = IAuctionGuard::postAuctionResultsCall {
+            bidder: winner.address(),
+            validBlock: U256::from(101),
+            price: U256::from(1000),
+            auction: true,
+            v: 27,
+            r: B256::repeat_byte(1),
+            s: B256::repeat_byte(2),
+        }
+        .abi_encode();
+        let raw_txs = vec![
+            sign(&suapp_signer, auction_guard, 3, payment).await,
+            sign(&winner, swap_router, 0, vec![0xaa]).await,
+            sign(&swapper, swap_router, 5, vec![0xbb]).await,
+            Bytes::from(vec![0xc0, 0x01]),
+        ];
+        let hashes: Vec<B256> = raw_txs.iter().map(keccak256).collect();
+
+        let response: Value = ReqwestClient::new()
+            .post(builder.url())
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_sendBundle",
+                "params": [{
+                    "blockNumber": U64::from(101),
+                    "txs": raw_txs,
+                    "revertingTxHashes": [hashes[2]],
+                }],
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let bundle_hash = keccak256(hashes.iter().flat_map(|hash| hash.0).collect::<Vec<u8>>());
+        assert_eq!(response["result"]["bundleHash"], json!(bundle_hash));
+
+        let bundle = builder.last_bundle().await.unwrap();
+        assert_eq!(bundle.index, 0);
+        assert_eq!(bundle.block_number, 101);
+        assert_eq!(bundle.bundle_hash, bundle_hash);
+        assert_eq!(bundle.reverting_tx_hashes, vec![hashes[2]]);
+        assert_eq!(bundle.applied, None);
+        assert_eq!(
+            bundle.txs.iter().map(|tx| tx.hash).collect::<Vec<_>>(),
+            hashes
+        );
+        assert_eq!(
+            bundle
+                .txs
+                .iter()
+                .map(|tx| tx.may_revert)
+                .collect::<Vec<_>>(),
+            vec![false, false, true, false]
+        );
+
+        assert_eq!(
+            bundle.txs[0].kind,
+            BundleTxKind::Payment {
+                bidder: winner.address(),
+                valid_block: U256::from(101),
+                price: U256::from(1000),
+                auction: true,
+            }
+        );
+        let payment = bundle.txs[0].tx.as_ref().unwrap();
+        assert_eq!(payment.hash, hashes[0]);
+        assert_eq!(payment.from, suapp_signer.address());
+        assert_eq!(payment.to, Some(auction_guard));
+        assert_eq!(payment.chain_id, Some(CHAIN_ID));
+        assert_eq!(payment.nonce, 3);
+        assert_eq!(payment.gas_limit, 200_000);
+
+        assert_eq!(bundle.txs[1].kind, BundleTxKind::WinnerSwap);
+        assert_eq!(bundle.txs[1].tx.as_ref().unwrap().from, winner.address());
+        assert_eq!(bundle.txs[2].kind, BundleTxKind::NonBidSwap);
+        assert_eq!(
+            bundle.txs[2].tx.as_ref().unwrap().input,
+            Bytes::from(vec![0xbb])
+        );
+        assert!(matches!(
+            bundle.txs[3].kind,
+            BundleTxKind::Undecodable { .. }
+        ));
+        assert_eq!(bundle.txs[3].tx, None);
+        assert_eq!(bundle.payment().unwrap().hash, hashes[0]);
+        assert_eq!(bundle.winner_swap().unwrap().hash, hashes[1]);
+        assert_eq!(bundle.non_bid_swaps().len(), 1);
+
+        // served over rpc by block number
+        for (block_number, count) in [(101, 1), (102, 0)] {
+            let response: Value = ReqwestClient::new()
+                .post(builder.url())
+                .json(&json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "mock_getBundles",
+                    "params": [U64::from(block_number)],
+                }))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            assert_eq!(response["result"].as_array().unwrap().len(), count);
+        }
+    }
+
+    #[tokio::test]
+    async fn winner_swap_only_right_after_payment() {
+        let suapp_signer: LocalWallet = SUAPP_SIGNER_KEY.parse().unwrap();
+        let winner: LocalWallet = WINNER_KEY.parse().unwrap();
+        let auction_guard = Address::repeat_byte(4);
+        let builder = MockBuilder::spawn(MockBuilderConfig {
+            auction_guard: Some(auction_guard),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        // a payment for a block without a winning bid doesn't make the next tx a winner swap
+        let payment = IAuctionGuard::postAuctionResultsCall {
+            bidder: winner.address(),
+            validBlock: U256::from(101),
+            price: U256::ZERO,
+            auction: false,
+            v: 0,
+            r: B256::ZERO,
+            s: B256::ZERO,
+        }
+        .abi_encode();
+        let txs = builder.builder.decode_txs(
+            &[
+                sign(&suapp_signer, auction_guard, 0, payment).await,
+                sign(&winner, Address::repeat_byte(7), 0, vec![]).await,
+            ],
+            &[],
+        );
+        assert!(matches!(
+            txs[0].kind,
+            BundleTxKind::Payment { auction: false, .. }
+        ));
+        assert_eq!(txs[1].kind, BundleTxKind::Other);
+    }
+}
@@ -0,0 +1,662 @@
+use std::{collections::HashMap, sync::Arc};
+
+use alloy_primitives::{keccak256, Address, Bytes, LogData, Signature, B256, U256, U64};
+use async_trait::async_trait;
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::{
+    amm_auction_config::AmmAuctionConfig,
+    deploy::GAS_NEEDED_POST_AUCTION_RESULTS,
+    raw_tx::rlp_items,
+    rpc_server::{param, RpcError, RpcHandler, RpcServer},
+};
+
+mod model;
+
+pub use model::{
+    DataId, HttpKettleHost, KettleHost, ModelRevert, SentBundle, SuappModel, SuappState,
+};
+
+/// Transaction type of a confidential compute request.
+const CCR_TX_TYPE: u8 = 0x43;
+/// Transaction type the request's record is signed as.
+const CCR_RECORD_TX_TYPE: u8 = 0x42;
+
+/// Gas price the mock reports, CCRs aren't charged.
+const MOCK_GAS_PRICE: u64 = 1_000_000_000;
+
+/// What the mock kettle is deployed as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockKettleConfig {
+    /// Address the suapp is "deployed" at
+    pub suapp: Address,
+    /// Kettle address CCRs must be addressed to
+    pub kettle_address: Address,
+    /// Suave chain id
+    pub chain_id: u64,
+    /// Account that "deployed" the suapp and can call its owner functions
+    pub owner: Address,
+    pub auction_deposits: Address,
+    pub auction_guard: Address,
+    /// L1 chain id the suapp signs auction results for
+    pub l1_chain_id: u64,
+    pub gas_needed_post_auction_results: u64,
+}
+
+impl MockKettleConfig {
+    /// Mirrors the config's deployment, with `owner` as the suapp's owner (the suave signer).
+    pub fn from_config(config: &AmmAuctionConfig, owner: Address) -> eyre::Result<Self> {
+        Ok(Self {
+            suapp: config.suapp_amm.context("auction suapp not set")?,
+            kettle_address: if config.use_local {
+                config.execution_node_suave_local
+            } else {
+                config.execution_node_suave
+            },
+            chain_id: config.chain_id_suave,
+            owner,
+            auction_deposits: config
+                .auction_deposits
+                .context("auction deposits not set")?,
+            auction_guard: config.auction_guard.context("auction guard not set")?,
+            l1_chain_id: config.chain_id_l1,
+            gas_needed_post_auction_results: GAS_NEEDED_POST_AUCTION_RESULTS,
+        })
+    }
+}
+
+/// An in-process Suave node and kettle running a Rust model of `AuctionSuapp.sol`.
+///
+/// It serves the JSON-RPC calls `AuctionSuapp` makes to Suave, so pointing the config's Suave
+/// rpc url at `url()` runs the wrapper against the model unchanged. Each accepted CCR is
+/// executed right away and mined in its own block with its callback's events, a reverting
+/// CCR is rejected with the revert data like a kettle does. The suapp's stats are readable
+/// with `eth_getStorageAt`.
+///
+/// The model's L1 and bundle requests go to the urls stored with `setL1Url()` and
+/// `setBundleUrl()` through the `KettleHost`.
+pub struct MockKettle {
+    server: RpcServer,
+    kettle: Arc<KettleRpc>,
+}
+
+impl MockKettle {
+    /// Starts the mock on a free local port.
+    pub async fn spawn(config: MockKettleConfig, host: Arc<dyn KettleHost>) -> eyre::Result<Self> {
+        Self::spawn_on("127.0.0.1:0", config, host).await
+    }
+
+    pub async fn spawn_on(
+        addr: &str,
+        config: MockKettleConfig,
+        host: Arc<dyn KettleHost>,
+    ) -> eyre::Result<Self> {
+        let kettle = Arc::new(KettleRpc {
+            chain: Mutex::new(MockChain {
+                model: SuappModel::new(
+                    config.owner,
+                    config.auction_deposits,
+                    config.auction_guard,
+                    config.l1_chain_id,
+                    config.gas_needed_post_auction_results,
+                ),
+                nonces: HashMap::new(),
+                block_number: 0,
+                receipts: HashMap::new(),
+                logs: Vec::new(),
+            }),
+            config,
+            host,
+        });
+        let server = RpcServer::spawn(addr, kettle.clone())
+            .await
+            .context("failed to start mock kettle")?;
+        Ok(Self { server, kettle })
+    }
+
+    /// Suave rpc url to give `AuctionSuapp`.
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    pub fn config(&self) -> &MockKettleConfig {
+        &self.kettle.config
+    }
+
+    /// Snapshot of the suapp model, for inspecting its state and sent bundles.
+    pub async fn model(&self) -> SuappModel {
+        self.kettle.chain.lock().await.model.clone()
+    }
+
+    /// Every bundle the suapp sent, oldest first.
+    pub async fn sent_bundles(&self) -> Vec<SentBundle> {
+        self.kettle.chain.lock().await.model.sent_bundles().to_vec()
+    }
+}
+
+/// A log of a mined CCR.
+#[derive(Debug, Clone)]
+struct MockLog {
+    data: LogData,
+    block_number: u64,
+    tx_hash: B256,
+    log_index: u64,
+}
+
+impl MockLog {
+    fn to_json(&self, address: Address) -> Value {
+        json!({
+            "address": address,
+            "topics": self.data.topics(),
+            "data": self.data.data,
+            "blockNumber": U64::from(self.block_number),
+            "transactionHash": self.tx_hash,
+            "logIndex": U64::from(self.log_index),
+            "removed": false,
+        })
+    }
+}
+
+/// The mock Suave chain, CCRs are executed one at a time.
+struct MockChain {
+    model: SuappModel,
+    nonces: HashMap<Address, u64>,
+    block_number: u64,
+    /// Block number and logs of each mined CCR
+    receipts: HashMap<B256, (u64, Vec<MockLog>)>,
+    logs: Vec<MockLog>,
+}
+
+struct KettleRpc {
+    config: MockKettleConfig,
+    host: Arc<dyn KettleHost>,
+    chain: Mutex<MockChain>,
+}
+
+#[async_trait]
+impl RpcHandler for KettleRpc {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "eth_chainId" => Ok(json!(U64::from(self.config.chain_id))),
+            "eth_gasPrice" => Ok(json!(U64::from(MOCK_GAS_PRICE))),
+            "eth_blockNumber" => Ok(json!(U64::from(self.chain.lock().await.block_number))),
+            "eth_kettleAddress" => Ok(json!([self.config.kettle_address])),
+            "eth_getTransactionCount" => {
+                let address: Address = param(&params, 0)?;
+                let nonce = self
+                    .chain
+                    .lock()
+                    .await
+                    .nonces
+                    .get(&address)
+                    .copied()
+                    .unwrap_or(0);
+                Ok(json!(U64::from(nonce)))
+            }
+            "eth_getStorageAt" => {
+                let address: Address = param(&params, 0)?;
+                let slot: U256 = param(&params, 1)?;
+                let value = if address == self.config.suapp {
+                    self.chain.lock().await.model.state.storage_at(slot)
+                } else {
+                    U256::ZERO
+                };
+                Ok(json!(B256::from(value)))
+            }
+            "eth_sendRawTransaction" => {
+                let raw: Bytes = param(&params, 0)?;
+                self.send_ccr(&raw).await.map(|tx_hash| json!(tx_hash))
+            }
+            "eth_getTransactionReceipt" => {
+                let tx_hash: B256 = param(&params, 0)?;
+                let chain = self.chain.lock().await;
+                Ok(match chain.receipts.get(&tx_hash) {
+                    Some((block_number, logs)) => json!({
+                        "transactionHash": tx_hash,
+                        "status": U64::from(1),
+                        "blockNumber": U64::from(*block_number),
+                        "logs": logs
+                            .iter()
+                            .map(|log| log.to_json(self.config.suapp))
+                            .collect::<Vec<_>>(),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            "eth_getLogs" => {
+                let filter: Value = param(&params, 0)?;
+                let chain = self.chain.lock().await;
+                let from_block = block_param(&filter["fromBlock"], chain.block_number)?;
+                let to_block = block_param(&filter["toBlock"], chain.block_number)?;
+                let wants_suapp = match &filter["address"] {
+                    Value::Null => true,
+                    address => serde_json::from_value::<Address>(address.clone())
+                        .map(|address| vec![address])
+                        .or_else(|_| serde_json::from_value::<Vec<Address>>(address.clone()))
+                        .map_err(RpcError::invalid_params)?
+                        .contains(&self.config.suapp),
+                };
+                Ok(json!(
+                    chain
+                        .logs
+                        .iter()
+                        .filter(|log| wants_suapp
+                            && (from_block..=to_block).contains(&log.block_number))
+                        .map(|log| log.to_json(self.config.suapp))
+                        .collect::<Vec<_>>()
+                ))
+            }
+            method => Err(RpcError::method_not_found(method)),
+        }
+    }
+}
+
+impl KettleRpc {
+    /// Checks a CCR like a kettle would, runs it on the model and mines it.
+    async fn send_ccr(&self, raw: &[u8]) -> Result<B256, RpcError> {
+        let ccr = ConfidentialComputeRequest::decode(raw).map_err(RpcError::invalid_params)?;
+        if ccr.kettle_address != self.config.kettle_address {
+            return Err(RpcError::server(format!(
+                "ccr is for kettle {}, this is kettle {}",
+                ccr.kettle_address, self.config.kettle_address
+            )));
+        }
+        if ccr.to != Some(self.config.suapp) {
+            return Err(RpcError::server(format!(
+                "no suapp deployed at {:?}",
+                ccr.to
+            )));
+        }
+
+        let mut chain = self.chain.lock().await;
+        let expected_nonce = chain.nonces.get(&ccr.sender).copied().unwrap_or(0);
+        if ccr.nonce < expected_nonce {
+            return Err(RpcError::server(format!(
+                "nonce too low: next nonce {}, tx nonce {}",
+                expected_nonce, ccr.nonce
+            )));
+        }
+        if ccr.nonce > expected_nonce {
+            return Err(RpcError::server(format!(
+                "nonce too high: next nonce {}, tx nonce {}",
+                expected_nonce, ccr.nonce
+            )));
+        }
+
+        let logs = match chain
+            .model
+            .execute(
+                self.host.as_ref(),
+                ccr.sender,
+                &ccr.data,
+                &ccr.confidential_inputs,
+            )
+            .await
+        {
+            Ok(logs) => logs,
+            Err(e) => {
+                return Err(match e.downcast_ref::<ModelRevert>() {
+                    Some(revert) => RpcError::server(revert.to_string()).with_data(json!(revert.0)),
+                    None => RpcError::server(format!("confidential execution failed: {:#}", e)),
+                })
+            }
+        };
+
+        let tx_hash = keccak256(raw);
+        chain.nonces.insert(ccr.sender, ccr.nonce + 1);
+        chain.block_number += 1;
+        let block_number = chain.block_number;
+        let first_index = chain.logs.len() as u64;
+        let logs: Vec<MockLog> = logs
+            .into_iter()
+            .enumerate()
+            .map(|(index, data)| MockLog {
+                data,
+                block_number,
+                tx_hash,
+                log_index: first_index + index as u64,
+            })
+            .collect();
+        chain.logs.extend(logs.iter().cloned());
+        chain.receipts.insert(tx_hash, (block_number, logs));
+        Ok(tx_hash)
+    }
+}
+
+/// A block number or tag param, tags other than `earliest` read as the latest block.
+fn block_param(value: &Value, latest: u64) -> Result<u64, RpcError> {
+    match value {
+        Value::Null => Ok(latest),
+        Value::String(tag) if tag == "earliest" => Ok(0),
+        Value::String(tag) if !tag.starts_with("0x") => Ok(latest),
+        value => serde_json::from_value::<U64>(value.clone())
+            .map(|number| number.to::<u64>())
+            .map_err(RpcError::invalid_params),
+    }
+}
+
+/// The fields of a confidential compute request the kettle looks at.
+#[derive(Debug)]
+struct ConfidentialComputeRequest {
+    sender: Address,
+    nonce: u64,
+    to: Option<Address>,
+    data: Bytes,
+    kettle_address: Address,
+    confidential_inputs: Bytes,
+}
+
+impl ConfidentialComputeRequest {
+    /// Decodes a `0x43` typed request, `rlp([record, confidentialInputs])` with the record
+    /// `[nonce, gasPrice, gas, to, value, data, kettleAddress, confidentialInputsHash, ...,
+    /// chainId, v, r, s]`, and recovers its sender.
+    ///
+    /// The record's signature is checked over `0x42 || rlp([kettleAddress,
+    /// confidentialInputsHash, nonce, gasPrice, gas, to, value, data])` as Suave nodes do,
+    /// EIP-712 signed records aren't supported.
+    fn decode(raw: &[u8]) -> eyre::Result<Self> {
+        let (tx_type, encoded) = raw.split_first().context("empty transaction")?;
+        eyre::ensure!(
+            *tx_type == CCR_TX_TYPE,
+            "transaction type {:#x} is not a confidential compute request",
+            tx_type
+        );
+        let outer = rlp_items(encoded)?;
+        let [outer] = outer.as_slice() else {
+            eyre::bail!("request is not a single rlp list");
+        };
+        eyre::ensure!(outer.list, "request is not an rlp list");
+        let request = rlp_items(outer.payload)?;
+
+        // the record is either nested or its fields come first, the inputs are last
+        let (record, confidential_inputs) = match request.as_slice() {
+            [record, inputs] if record.list => (rlp_items(record.payload)?, inputs),
+            [fields @ .., inputs] => (fields.to_vec(), inputs),
+            [] => eyre::bail!("empty request"),
+        };
+        eyre::ensure!(
+            record.len() >= 12,
+            "record has {} fields, expected at least 12",
+            record.len()
+        );
+        let is_eip712 = record.len() > 12 && record[8].payload == [1];
+        eyre::ensure!(!is_eip712, "EIP-712 signed records aren't supported");
+
+        let confidential_inputs = Bytes::copy_from_slice(confidential_inputs.payload);
+        let inputs_hash =
+            B256::try_from(record[7].payload).context("malformed confidential inputs hash")?;
+        eyre::ensure!(
+            keccak256(&confidential_inputs) == inputs_hash,
+            "confidential inputs don't match the record's hash"
+        );
+
+        // sign over the record's fields in the order the signing hash takes them
+        let mut signed_fields = Vec::new();
+        for index in [6, 7, 0, 1, 2, 3, 4, 5] {
+            signed_fields.extend_from_slice(record[index].raw);
+        }
+        let mut signing_payload = vec![CCR_RECORD_TX_TYPE];
+        alloy_rlp::Header {
+            list: true,
+            payload_length: signed_fields.len(),
+        }
+        .encode(&mut signing_payload);
+        signing_payload.extend_from_slice(&signed_fields);
+        let signing_hash = keccak256(&signing_payload);
+
+        let signature_fields = &record[record.len() - 3..];
+        let v = U256::from_be_slice(signature_fields[0].payload);
+        let signature = Signature::from_rs_and_parity(
+            U256::from_be_slice(signature_fields[1].payload),
+            U256::from_be_slice(signature_fields[2].payload),
+            v.to::<u64>(),
+        )
+        .map_err(|e| eyre::eyre!("malformed signature: {}", e))?;
+        let sender = signature
+            .recover_address_from_prehash(&signing_hash)
+            .map_err(|e| eyre::eyre!("failed to recover sender: {}", e))?;
+
+        Ok(Self {
+            sender,
+            nonce: U256::from_be_slice(record[0].payload).to::<u64>(),
+            to: match record[3].payload {
+                [] => None,
+                to => Some(Address::try_from(to).context("malformed to address")?),
+            },
+            data: Bytes::copy_from_slice(record[5].payload),
+            kettle_address: Address::try_from(record[6].payload)
+                .context("malformed kettle address")?,
+            confidential_inputs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use alloy::signers::wallet::LocalWallet;
+    use alloy_sol_types::SolCall;
+
+    use super::*;
+    use crate::{
+        accounts::{AccountRegistry, AccountRole},
+        amm_auction::{AuctionSuapp, IAuctionDeposits},
+        ccr::SuappRevert,
+        mock_builder::{BundleTxKind, MockBuilder, MockBuilderConfig},
+        nonce_manager::NonceManager,
+        swap::Swap,
+    };
+
+    /// Anvil's first three dev keys.
+    const OWNER_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const SUAPP_SIGNER_KEY: &str =
+        "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+    const BIDDER_KEY: &str = "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804e92b0d6b";
+
+    const L1_CHAIN_ID: u64 = 17000;
+    const DEPOSIT: u64 = 1_000_000_000_000_000_000;
+
+    /// An L1 node whose latest block the test sets, with every bidder's deposit at `DEPOSIT`
+    /// and every other call succeeding.
+    struct MockL1 {
+        auction_guard: Address,
+        /// Number and timestamp of the latest block
+        latest: Mutex<(u64, u64)>,
+    }
+
+    #[async_trait]
+    impl RpcHandler for MockL1 {
+        async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+            let (number, timestamp) = *self.latest.lock().await;
+            match method {
+                "eth_chainId" => Ok(json!(U64::from(L1_CHAIN_ID))),
+                "eth_blockNumber" => Ok(json!(U64::from(number))),
+                "eth_gasPrice" => Ok(json!(U64::from(MOCK_GAS_PRICE))),
+                "eth_estimateGas" => Ok(json!(U64::from(200_000))),
+                "eth_getTransactionCount" => Ok(json!(U64::ZERO)),
+                "eth_getBlockByNumber" => Ok(json!({
+                    "hash": B256::repeat_byte(0x11),
+                    "parentHash": B256::repeat_byte(0x10),
+                    "sha3Uncles": B256::ZERO,
+                    "miner": Address::ZERO,
+                    "stateRoot": B256::ZERO,
+                    "transactionsRoot": B256::ZERO,
+                    "receiptsRoot": B256::ZERO,
+                    "logsBloom": Bytes::from(vec![0u8; 256]),
+                    "difficulty": U64::ZERO,
+                    "number": U64::from(number),
+                    "gasLimit": U64::from(30_000_000),
+                    "gasUsed": U64::ZERO,
+                    "timestamp": U64::from(timestamp),
+                    "extraData": Bytes::new(),
+                    "mixHash": B256::ZERO,
+                    "nonce": "0x0000000000000000",
+                    "baseFeePerGas": U64::from(MOCK_GAS_PRICE),
+                    "size": U64::ZERO,
+                    "uncles": [],
+                    "transactions": [],
+                })),
+                "eth_call" => {
+                    let call: Value = param(&params, 0)?;
+                    let input: Bytes = serde_json::from_value(
+                        call.get("input")
+                            .or_else(|| call.get("data"))
+                            .cloned()
+                            .unwrap_or_default(),
+                    )
+                    .unwrap_or_default();
+                    let result = match input.get(..4) {
+                        Some(selector) if selector == IAuctionDeposits::balanceOfCall::SELECTOR => {
+                            Bytes::from(B256::from(U256::from(DEPOSIT)).to_vec())
+                        }
+                        Some(selector)
+                            if selector == IAuctionDeposits::auctionGuardCall::SELECTOR =>
+                        {
+                            Bytes::from(self.auction_guard.into_word().to_vec())
+                        }
+                        // the bid's swap simulation
+                        _ => Bytes::new(),
+                    };
+                    Ok(json!(result))
+                }
+                method => Err(RpcError::method_not_found(method)),
+            }
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+    }
+
+    #[tokio::test]
+    async fn auction_suapp_runs_against_mock_kettle() {
+        let owner: LocalWallet = OWNER_KEY.parse().unwrap();
+        let suapp_signer: LocalWallet = SUAPP_SIGNER_KEY.parse().unwrap();
+        let bidder: LocalWallet = BIDDER_KEY.parse().unwrap();
+        let (token_0, token_1) = (Address::repeat_byte(5), Address::repeat_byte(6));
+
+        let config = MockKettleConfig {
+            suapp: Address::repeat_byte(1),
+            kettle_address: Address::repeat_byte(2),
+            chain_id: 16813125,
+            owner: owner.address(),
+            auction_deposits: Address::repeat_byte(3),
+            auction_guard: Address::repeat_byte(4),
+            l1_chain_id: L1_CHAIN_ID,
+            gas_needed_post_auction_results: GAS_NEEDED_POST_AUCTION_RESULTS,
+        };
+        // the auction for block 101 is open until 4s after block 100
+        let l1 = Arc::new(MockL1 {
+            auction_guard: config.auction_guard,
+            latest: Mutex::new((100, now())),
+        });
+        let l1_server = RpcServer::spawn("127.0.0.1:0", l1.clone()).await.unwrap();
+        let builder = MockBuilder::spawn(MockBuilderConfig {
+            auction_guard: Some(config.auction_guard),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let kettle = MockKettle::spawn(config.clone(), Arc::new(HttpKettleHost::new()))
+            .await
+            .unwrap();
+
+        let mut accounts = AccountRegistry::default();
+        accounts
+            .insert("bidder".to_string(), AccountRole::Bidder, bidder.clone())
+            .unwrap();
+        let suapp = AuctionSuapp::new(
+            config.suapp,
+            config.auction_deposits,
+            token_0,
+            token_1,
+            Address::repeat_byte(7),
+            config.kettle_address,
+            kettle.url(),
+            l1_server.url(),
+            builder.url(),
+            accounts,
+            owner,
+            suapp_signer.clone(),
+            Arc::new(NonceManager::new()),
+        )
+        .await
+        .unwrap();
+
+        for ccr_hash in [
+            suapp.set_l1_url().await.unwrap(),
+            suapp.set_bundle_url().await.unwrap(),
+            suapp.set_signing_key().await.unwrap(),
+            suapp.initialize_l1_block().await.unwrap(),
+        ] {
+            suapp.wait_for_ccr(ccr_hash).await.unwrap();
+        }
+        assert_eq!(
+            kettle.model().await.state.signing_pub_key,
+            suapp_signer.address()
+        );
+
+        // newBid
+        let bid = suapp
+            .new_bid(
+                &"bidder".to_string(),
+                101,
+                1000,
+                Swap::exact_input_single(token_0, token_1, U256::from(10_000), U256::ZERO),
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(bid.report.is_empty());
+        let outcome = suapp.wait_for_ccr(bid.ccr_hash).await.unwrap();
+        let bid_id = outcome.bid_id().unwrap();
+        assert_eq!(
+            kettle.model().await.state.block_bids[&U256::from(101)],
+            vec![bid_id]
+        );
+
+        // runAuction once the auction's duration passed
+        l1.latest.lock().await.1 -= 10;
+        let ccr_hash = suapp.trigger_auction().await.unwrap();
+        suapp.wait_for_ccr(ccr_hash).await.unwrap();
+
+        // a single bid pays its own price
+        let stats = suapp.get_auction_stats().await.unwrap();
+        assert_eq!(stats.last_auction_processed_l1_block, 101);
+        assert_eq!(stats.winning_bid_amount, U256::from(1000));
+
+        let bundle = builder.last_bundle().await.unwrap();
+        assert_eq!(bundle.block_number, 101);
+        assert_eq!(bundle.txs.len(), 2);
+        assert_eq!(
+            bundle.txs[0].kind,
+            BundleTxKind::Payment {
+                bidder: bidder.address(),
+                valid_block: U256::from(101),
+                price: U256::from(1000),
+                auction: true,
+            }
+        );
+        assert_eq!(
+            bundle.txs[0].tx.as_ref().unwrap().from,
+            suapp_signer.address()
+        );
+        assert_eq!(bundle.txs[1].kind, BundleTxKind::WinnerSwap);
+        assert!(bundle.reverting_tx_hashes.is_empty());
+
+        // the suapp only runs one auction per block
+        let e = suapp.trigger_auction().await.unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<SuappRevert>(),
+            Some(&SuappRevert::AuctionAlreadyRan)
+        );
+        assert_eq!(kettle.sent_bundles().await.len(), 1);
+    }
+}
@@ -0,0 +1,908 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{
+    eips::eip2718::Encodable2718,
+    network::{EthereumSigner, TransactionBuilder},
+    rpc::types::eth::TransactionRequest,
+    signers::wallet::LocalWallet,
+    sol,
+};
+use alloy_primitives::{hex, keccak256, Address, Bytes, FixedBytes, LogData, B256, U256, U64};
+use alloy_sol_types::{Panic, PanicKind, SolCall, SolError, SolEvent, SolValue};
+use async_trait::async_trait;
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use reqwest::Client as ReqwestClient;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::amm_auction::{IAuctionDeposits, IAuctionGuard, IAuctionSuapp};
+
+sol! {
+    interface IAuctionSuappCallbacks {
+        function callbackNewPendingTxn(address sender, bytes16 txnId) external;
+        function callbackNewBid(bytes16 bidId, uint256 blockNum, bytes32 saltedReturn) external;
+        function callbackRunAuction(
+            uint256 notLandedButSent,
+            uint256 landed,
+            uint256 nonceUsed_,
+            uint256 auctioned_block,
+            uint256 nonBidTxnsCount_,
+            uint256 secondPrice_
+        ) external;
+        function callbackSetSigningKey(address signingPubKey_, bytes16 signingKeyBid_) external;
+        function callbackSetL1Url(bytes16 L1KeyId) external;
+        function callbackSetBundleUrl(bytes16 bundleKeyId) external;
+        function callbackInitLastL1Block(bytes16 lastL1BlockKeyId) external;
+        function callbackResetSwaps() external;
+    }
+}
+
+use IAuctionSuappCallbacks::*;
+
+/// Id of a confidential store record, Suave's `DataId`.
+pub type DataId = FixedBytes<16>;
+
+/// Confidential store keys and namespaces `AuctionSuapp.sol` uses.
+const KEY_PRIVATE_KEY: &str = "KEY";
+const KEY_L1_URL: &str = "L1_URL";
+const KEY_BUNDLE_URL: &str = "BUNDLE_URL";
+const KEY_LAST_BLOCK_PROCESSED: &str = "LAST_BLOCK";
+const NON_BID_TXN_NAMESPACE: &str = "non_bid_txns";
+const BID_NAMESPACE: &str = "bid_namespace";
+
+/// Added to the L1 base fee for the `postAuctionResults()` transaction's gas price.
+const POST_AUCTION_GAS_PRICE_PREMIUM: u128 = 8_000_000_000_000;
+
+/// What a kettle reaches outside of Suave: `Suave.doHTTPRequest()` and the time API the
+/// suapp reads the current time from.
+#[async_trait]
+pub trait KettleHost: Send + Sync {
+    /// Posts a JSON-RPC request to `url`, returning the response's `result`.
+    async fn json_rpc(&self, url: &str, method: &str, params: Value) -> eyre::Result<Value>;
+
+    /// Unix time in seconds `_getCurrentTime()` returns.
+    fn unix_time(&self) -> u64;
+}
+
+/// Sends the suapp's requests over HTTP and reads the time from the system clock.
+///
+/// Bundles are sent without the `X-Flashbots-Signature` header a real kettle adds.
+#[derive(Debug, Clone, Default)]
+pub struct HttpKettleHost {
+    client: ReqwestClient,
+    time_offset_secs: i64,
+}
+
+impl HttpKettleHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shifts the clock, e.g. forward to end auctions without waiting on them.
+    pub fn with_time_offset(mut self, time_offset_secs: i64) -> Self {
+        self.time_offset_secs = time_offset_secs;
+        self
+    }
+}
+
+#[async_trait]
+impl KettleHost for HttpKettleHost {
+    async fn json_rpc(&self, url: &str, method: &str, params: Value) -> eyre::Result<Value> {
+        let mut response: Value = self
+            .client
+            .post(url)
+            .json(&json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 }))
+            .send()
+            .await
+            .context(format!("failed to send {} to {}", method, url))?
+            .json()
+            .await
+            .context(format!("failed to parse {} response", method))?;
+        if let Some(error) = response.get("error") {
+            eyre::bail!("{} failed: {}", method, error);
+        }
+        Ok(response["result"].take())
+    }
+
+    fn unix_time(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+        now.saturating_add_signed(self.time_offset_secs)
+    }
+}
+
+/// A confidential execution reverting with `data`, what the suapp's `revert`s and
+/// `require`s turn into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelRevert(pub Bytes);
+
+impl ModelRevert {
+    fn error<E: SolError>(error: E) -> eyre::Report {
+        eyre::Report::new(ModelRevert(error.abi_encode().into()))
+    }
+
+    fn panic(kind: PanicKind) -> eyre::Report {
+        eyre::Report::new(ModelRevert(Panic::from(kind).abi_encode().into()))
+    }
+}
+
+impl std::fmt::Display for ModelRevert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "execution reverted: {}", self.0)
+    }
+}
+
+impl std::error::Error for ModelRevert {}
+
+/// A bundle `runAuction()` sent to the stored bundle url.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SentBundle {
+    pub block_number: u64,
+    /// The signed `postAuctionResults()` transaction, the winner's swap if the auction had a
+    /// winner, then the non-bid swaps
+    pub txs: Vec<Bytes>,
+    pub reverting_tx_hashes: Vec<B256>,
+}
+
+/// The suapp's on-chain state, laid out like `AuctionSuapp.sol`'s storage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SuappState {
+    pub last_auction_processed_l1_block: U256,
+    pub nonce_used: U256,
+    pub included_txns: U256,
+    pub not_landed_but_sent: U256,
+    pub landed: U256,
+    pub winning_bid_amount: U256,
+    pub target_deposit_contract: Address,
+    pub target_auction_guard: Address,
+    pub owner: Address,
+    pub signing_pub_key: Address,
+    pub signing_key_record: DataId,
+    pub eth_l1_url_record: DataId,
+    pub bundle_url_record: DataId,
+    pub last_block_processed_record: DataId,
+    pub chain_id: U256,
+    pub gas_needed_post_auction_results: U256,
+    pub auction_duration: U256,
+    /// Bid records per L1 block
+    pub block_bids: BTreeMap<U256, Vec<DataId>>,
+    /// Non-bid swap records in the order they were sent
+    pub non_bid_txns: Vec<DataId>,
+}
+
+impl SuappState {
+    /// The value `eth_getStorageAt` returns for `slot`. Only the value types in slots 0 to 14
+    /// are laid out, other slots read as zero.
+    pub fn storage_at(&self, slot: U256) -> U256 {
+        let Ok(slot) = u64::try_from(slot) else {
+            return U256::ZERO;
+        };
+        match slot {
+            0 => self.last_auction_processed_l1_block,
+            1 => self.nonce_used,
+            2 => self.included_txns,
+            3 => self.not_landed_but_sent,
+            4 => self.landed,
+            5 => self.winning_bid_amount,
+            6 => address_word(self.target_deposit_contract),
+            7 => address_word(self.target_auction_guard),
+            8 => address_word(self.owner),
+            9 => address_word(self.signing_pub_key),
+            // two `DataId`s share a slot, the first declared in the low-order bytes
+            10 => packed_data_ids(self.signing_key_record, self.eth_l1_url_record),
+            11 => packed_data_ids(self.bundle_url_record, self.last_block_processed_record),
+            12 => self.chain_id,
+            13 => self.gas_needed_post_auction_results,
+            14 => self.auction_duration,
+            _ => U256::ZERO,
+        }
+    }
+}
+
+fn address_word(address: Address) -> U256 {
+    U256::from_be_slice(address.as_slice())
+}
+
+fn packed_data_ids(low: DataId, high: DataId) -> U256 {
+    (U256::from_be_slice(high.as_slice()) << 128) | U256::from_be_slice(low.as_slice())
+}
+
+/// The last L1 block as `getLastL1Block()` reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct L1Block {
+    number: U256,
+    timestamp: U256,
+    base_fee_per_gas: U256,
+}
+
+/// Rust model of `AuctionSuapp.sol`, executing CCRs the way a kettle would.
+///
+/// A CCR runs the suapp's confidential function against the confidential store, then the
+/// callback calldata it returns is applied to the on-chain state, emitting the suapp's events.
+/// Bids are only checked against the bidder's deposit, like the contract does.
+#[derive(Debug, Clone)]
+pub struct SuappModel {
+    pub state: SuappState,
+    confidential_store: HashMap<(DataId, String), Bytes>,
+    records_created: u64,
+    sent_bundles: Vec<SentBundle>,
+}
+
+impl SuappModel {
+    /// Mirrors the contract's constructor, deployed by `owner`.
+    pub fn new(
+        owner: Address,
+        target_deposit_contract: Address,
+        target_auction_guard: Address,
+        chain_id: u64,
+        gas_needed_post_auction_results: u64,
+    ) -> Self {
+        Self {
+            state: SuappState {
+                owner,
+                target_deposit_contract,
+                target_auction_guard,
+                chain_id: U256::from(chain_id),
+                gas_needed_post_auction_results: U256::from(gas_needed_post_auction_results),
+                auction_duration: U256::from(4),
+                ..Default::default()
+            },
+            confidential_store: HashMap::new(),
+            records_created: 0,
+            sent_bundles: Vec::new(),
+        }
+    }
+
+    /// Every bundle `runAuction()` sent, oldest first.
+    pub fn sent_bundles(&self) -> &[SentBundle] {
+        &self.sent_bundles
+    }
+
+    /// A record in the confidential store.
+    pub fn confidential_retrieve(&self, record: DataId, key: &str) -> Option<&Bytes> {
+        self.confidential_store.get(&(record, key.to_string()))
+    }
+
+    /// Runs a CCR from `sender` and applies its callback, returning the events emitted.
+    ///
+    /// A revert leaves the state untouched and errors with a `ModelRevert`.
+    pub async fn execute(
+        &mut self,
+        host: &dyn KettleHost,
+        sender: Address,
+        calldata: &[u8],
+        confidential_inputs: &[u8],
+    ) -> eyre::Result<Vec<LogData>> {
+        // confidential execution only commits if the ccr goes through
+        let mut confidential = self.clone();
+        let callback = confidential
+            .confidential_call(host, sender, calldata, confidential_inputs)
+            .await?;
+        let logs = confidential.apply_callback(&callback)?;
+        *self = confidential;
+        Ok(logs)
+    }
+
+    async fn confidential_call(
+        &mut self,
+        host: &dyn KettleHost,
+        sender: Address,
+        calldata: &[u8],
+        confidential_inputs: &[u8],
+    ) -> eyre::Result<Bytes> {
+        let selector = calldata.get(..4).unwrap_or_default();
+        let callback = if selector == IAuctionSuapp::newPendingTxnCall::SELECTOR {
+            self.new_pending_txn(sender, confidential_inputs)
+        } else if selector == IAuctionSuapp::newBidCall::SELECTOR {
+            let call = IAuctionSuapp::newBidCall::abi_decode(calldata, true)
+                .map_err(|_| eyre::Report::new(ModelRevert(Bytes::new())))?;
+            self.new_bid(host, sender, &call.salt, confidential_inputs)
+                .await?
+        } else if selector == IAuctionSuapp::runAuctionCall::SELECTOR {
+            self.run_auction(host).await?
+        } else if selector == IAuctionSuapp::setSigningKeyCall::SELECTOR {
+            let call = IAuctionSuapp::setSigningKeyCall::abi_decode(calldata, true)
+                .map_err(|_| eyre::Report::new(ModelRevert(Bytes::new())))?;
+            self.only_owner(sender)?;
+            let record = self.new_data_record();
+            self.confidential_store(record, KEY_PRIVATE_KEY, confidential_inputs);
+            callbackSetSigningKeyCall {
+                signingPubKey_: call.pubKey,
+                signingKeyBid_: record,
+            }
+            .abi_encode()
+        } else if selector == IAuctionSuapp::setL1UrlCall::SELECTOR {
+            self.only_owner(sender)?;
+            let record = self.new_data_record();
+            self.confidential_store(record, KEY_L1_URL, confidential_inputs);
+            callbackSetL1UrlCall { L1KeyId: record }.abi_encode()
+        } else if selector == IAuctionSuapp::setBundleUrlCall::SELECTOR {
+            self.only_owner(sender)?;
+            let record = self.new_data_record();
+            self.confidential_store(record, KEY_BUNDLE_URL, confidential_inputs);
+            callbackSetBundleUrlCall {
+                bundleKeyId: record,
+            }
+            .abi_encode()
+        } else if selector == IAuctionSuapp::initLastL1BlockCall::SELECTOR {
+            self.only_owner(sender)?;
+            let record = self.new_data_record();
+            self.confidential_store(record, KEY_LAST_BLOCK_PROCESSED, &U256::ZERO.abi_encode());
+            callbackInitLastL1BlockCall {
+                lastL1BlockKeyId: record,
+            }
+            .abi_encode()
+        } else if selector == IAuctionSuapp::_resetSwapsCall::SELECTOR {
+            self.only_owner(sender)?;
+            callbackResetSwapsCall {}.abi_encode()
+        } else {
+            // no fallback function
+            return Err(eyre::Report::new(ModelRevert(Bytes::new())));
+        };
+        Ok(callback.into())
+    }
+
+    /// Runs a callback on the suapp's on-chain state.
+    fn apply_callback(&mut self, calldata: &[u8]) -> eyre::Result<Vec<LogData>> {
+        let selector = calldata.get(..4).unwrap_or_default();
+        let mut logs = Vec::new();
+        if selector == callbackNewPendingTxnCall::SELECTOR {
+            let call = callbackNewPendingTxnCall::abi_decode(calldata, true)?;
+            self.state.non_bid_txns.push(call.txnId);
+            logs.push(
+                IAuctionSuapp::NonBidTxnId {
+                    sender: call.sender,
+                    txnId: call.txnId,
+                }
+                .encode_log_data(),
+            );
+        } else if selector == callbackNewBidCall::SELECTOR {
+            let call = callbackNewBidCall::abi_decode(calldata, true)?;
+            self.state
+                .block_bids
+                .entry(call.blockNum)
+                .or_default()
+                .push(call.bidId);
+            logs.push(
+                IAuctionSuapp::NewBid {
+                    saltedReturn: call.saltedReturn,
+                    bidId: call.bidId,
+                }
+                .encode_log_data(),
+            );
+        } else if selector == callbackRunAuctionCall::SELECTOR {
+            let call = callbackRunAuctionCall::abi_decode(calldata, true)?;
+            self.state.not_landed_but_sent = call.notLandedButSent;
+            self.state.landed = call.landed;
+            self.state.included_txns = call.nonBidTxnsCount_;
+            self.state.nonce_used = call.nonceUsed_;
+            self.state.last_auction_processed_l1_block = call.auctioned_block;
+            self.state.winning_bid_amount = call.secondPrice_;
+        } else if selector == callbackSetSigningKeyCall::SELECTOR {
+            let call = callbackSetSigningKeyCall::abi_decode(calldata, true)?;
+            self.state.signing_pub_key = call.signingPubKey_;
+            self.state.signing_key_record = call.signingKeyBid_;
+            logs.push(
+                IAuctionSuapp::UpdateKey {
+                    newKey: call.signingKeyBid_,
+                }
+                .encode_log_data(),
+            );
+        } else if selector == callbackSetL1UrlCall::SELECTOR {
+            self.state.eth_l1_url_record =
+                callbackSetL1UrlCall::abi_decode(calldata, true)?.L1KeyId;
+        } else if selector == callbackSetBundleUrlCall::SELECTOR {
+            self.state.bundle_url_record =
+                callbackSetBundleUrlCall::abi_decode(calldata, true)?.bundleKeyId;
+        } else if selector == callbackInitLastL1BlockCall::SELECTOR {
+            self.state.last_block_processed_record =
+                callbackInitLastL1BlockCall::abi_decode(calldata, true)?.lastL1BlockKeyId;
+        } else if selector == callbackResetSwapsCall::SELECTOR {
+            self.state.non_bid_txns.clear();
+            self.state.landed = U256::ZERO;
+            self.state.not_landed_but_sent = U256::ZERO;
+        } else {
+            eyre::bail!("unknown callback {}", hex::encode_prefixed(selector));
+        }
+        Ok(logs)
+    }
+
+    fn only_owner(&self, sender: Address) -> eyre::Result<()> {
+        if sender != self.state.owner {
+            return Err(ModelRevert::error(IAuctionSuapp::OnlyOwner {}));
+        }
+        Ok(())
+    }
+
+    /// `Suave.newDataRecord()`, ids are derived from a counter so runs are reproducible.
+    fn new_data_record(&mut self) -> DataId {
+        self.records_created += 1;
+        DataId::from_slice(&keccak256(self.records_created.to_be_bytes())[..16])
+    }
+
+    fn confidential_store(&mut self, record: DataId, key: &str, value: &[u8]) {
+        self.confidential_store
+            .insert((record, key.to_string()), Bytes::copy_from_slice(value));
+    }
+
+    /// `Suave.confidentialRetrieve()`, which fails the execution for missing records.
+    fn retrieve(&self, record: DataId, key: &str) -> eyre::Result<Bytes> {
+        self.confidential_retrieve(record, key)
+            .cloned()
+            .context(format!("confidential record {} has no {}", record, key))
+    }
+
+    fn retrieve_string(&self, record: DataId, key: &str) -> eyre::Result<String> {
+        String::from_utf8(self.retrieve(record, key)?.to_vec())
+            .context(format!("confidential record {} is not a string", record))
+    }
+
+    fn new_pending_txn(&mut self, sender: Address, confidential_inputs: &[u8]) -> Vec<u8> {
+        let record = self.new_data_record();
+        self.confidential_store(record, NON_BID_TXN_NAMESPACE, confidential_inputs);
+        callbackNewPendingTxnCall {
+            sender,
+            txnId: record,
+        }
+        .abi_encode()
+    }
+
+    async fn new_bid(
+        &mut self,
+        host: &dyn KettleHost,
+        sender: Address,
+        salt: &str,
+        confidential_inputs: &[u8],
+    ) -> eyre::Result<Vec<u8>> {
+        let bid = IAuctionSuapp::Bid::abi_decode(confidential_inputs, true)
+            .map_err(|_| eyre::Report::new(ModelRevert(Bytes::new())))?;
+
+        let http_url = self.retrieve_string(self.state.eth_l1_url_record, KEY_L1_URL)?;
+        let last_l1_block = last_l1_block(host, &http_url).await?;
+        let current_time = U256::from(host.unix_time());
+        if bid.blockNumber <= last_l1_block.number
+            || current_time > last_l1_block.timestamp + self.state.auction_duration
+        {
+            return Err(ModelRevert::error(IAuctionSuapp::StaleBid {}));
+        }
+
+        let record = self.new_data_record();
+        self.confidential_store(record, BID_NAMESPACE, &bid.abi_encode());
+        let salted_return = keccak256((sender, salt.to_string()).abi_encode_params());
+
+        Ok(callbackNewBidCall {
+            bidId: record,
+            blockNum: bid.blockNumber,
+            saltedReturn: salted_return,
+        }
+        .abi_encode())
+    }
+
+    async fn run_auction(&mut self, host: &dyn KettleHost) -> eyre::Result<Vec<u8>> {
+        let http_url = self.retrieve_string(self.state.eth_l1_url_record, KEY_L1_URL)?;
+        let last_l1_block = last_l1_block(host, &http_url).await?;
+
+        let last_block_auctioned = word(&self.retrieve(
+            self.state.last_block_processed_record,
+            KEY_LAST_BLOCK_PROCESSED,
+        )?);
+        let nonce = signing_key_nonce(host, &http_url, self.state.signing_pub_key).await?;
+
+        // bundles aren't guaranteed to land, send from the last included index
+        let txs_to_send_index =
+            if self.state.nonce_used != U256::ZERO && nonce > self.state.nonce_used {
+                self.state.not_landed_but_sent
+            } else {
+                self.state.landed
+            };
+
+        let current_time = U256::from(host.unix_time());
+        if last_block_auctioned >= last_l1_block.number + U256::from(1) {
+            return Err(ModelRevert::error(IAuctionSuapp::AuctionAlreadyRan {}));
+        }
+        if current_time < last_l1_block.timestamp + self.state.auction_duration {
+            return Err(ModelRevert::error(IAuctionSuapp::AuctionNotEnded {}));
+        }
+        let current_block = last_l1_block.number + U256::from(1);
+
+        let (winning_bid, second_price) = self
+            .find_auction_winner(host, &http_url, current_block)
+            .await?;
+
+        let first_non_bid = usize::try_from(txs_to_send_index)
+            .ok()
+            .filter(|index| *index <= self.state.non_bid_txns.len())
+            .ok_or_else(|| ModelRevert::panic(PanicKind::UnderOverflow))?;
+        let non_bid_txns_count = self.state.non_bid_txns.len() - first_non_bid;
+
+        let mut txs = vec![
+            self.create_post_auction_transaction(
+                &winning_bid,
+                &last_l1_block,
+                second_price != U256::ZERO,
+                nonce,
+            )
+            .await?,
+        ];
+        if second_price != U256::ZERO {
+            txs.push(winning_bid.swapTxn.clone());
+        }
+        let mut reverting_tx_hashes = Vec::with_capacity(non_bid_txns_count);
+        for record in &self.state.non_bid_txns[first_non_bid..] {
+            let non_bid_txn = self.retrieve(*record, NON_BID_TXN_NAMESPACE)?;
+            reverting_tx_hashes.push(keccak256(&non_bid_txn));
+            txs.push(non_bid_txn);
+        }
+
+        let bundle_url = self.retrieve_string(self.state.bundle_url_record, KEY_BUNDLE_URL)?;
+        let bundle = SentBundle {
+            block_number: u64::try_from(current_block)
+                .context("auctioned block does not fit in u64")?,
+            txs,
+            reverting_tx_hashes,
+        };
+        send_bundle(host, &bundle_url, &bundle).await?;
+        self.sent_bundles.push(bundle);
+
+        self.confidential_store(
+            self.state.last_block_processed_record,
+            KEY_LAST_BLOCK_PROCESSED,
+            &current_block.to_be_bytes::<32>(),
+        );
+
+        Ok(callbackRunAuctionCall {
+            notLandedButSent: txs_to_send_index + U256::from(non_bid_txns_count),
+            landed: txs_to_send_index,
+            nonceUsed_: nonce,
+            auctioned_block: current_block,
+            nonBidTxnsCount_: U256::from(non_bid_txns_count),
+            secondPrice_: second_price,
+        }
+        .abi_encode())
+    }
+
+    /// Highest bid with a deposit covering it, and the second price it pays.
+    async fn find_auction_winner(
+        &self,
+        host: &dyn KettleHost,
+        http_url: &str,
+        block_number: U256,
+    ) -> eyre::Result<(IAuctionSuapp::Bid, U256)> {
+        let mut best_price = U256::ZERO;
+        let mut second_price = U256::ZERO;
+        let mut best_bid = empty_bid();
+
+        for record in self
+            .state
+            .block_bids
+            .get(&block_number)
+            .into_iter()
+            .flatten()
+        {
+            let bid = IAuctionSuapp::Bid::abi_decode(&self.retrieve(*record, BID_NAMESPACE)?, true)
+                .context("failed to decode stored bid")?;
+            let deposit = deposit_balance(
+                host,
+                http_url,
+                self.state.target_deposit_contract,
+                bid.bidder,
+            )
+            .await?;
+            if deposit < bid.amount {
+                continue;
+            }
+            if bid.amount > best_price {
+                second_price = best_price;
+                best_price = bid.amount;
+                best_bid = bid;
+            } else if bid.amount > second_price {
+                second_price = bid.amount;
+            }
+        }
+        // a single bid pays its own price
+        if second_price == U256::ZERO {
+            second_price = best_price;
+        }
+
+        Ok((best_bid, second_price))
+    }
+
+    /// Signs the `postAuctionResults()` transaction with the stored signing key.
+    async fn create_post_auction_transaction(
+        &self,
+        bid: &IAuctionSuapp::Bid,
+        block: &L1Block,
+        auction_has_winner: bool,
+        nonce: U256,
+    ) -> eyre::Result<Bytes> {
+        let input = IAuctionGuard::postAuctionResultsCall {
+            bidder: bid.bidder,
+            validBlock: bid.blockNumber,
+            price: bid.amount,
+            auction: auction_has_winner,
+            v: bid.v,
+            r: bid.r,
+            s: bid.s,
+        }
+        .abi_encode();
+
+        let signing_key = word(&self.retrieve(self.state.signing_key_record, KEY_PRIVATE_KEY)?);
+        let wallet: LocalWallet = hex::encode(signing_key.to_be_bytes::<32>())
+            .parse()
+            .context("stored signing key is not a valid key")?;
+
+        let tx = TransactionRequest::default()
+            .from(wallet.address())
+            .to(self.state.target_auction_guard)
+            .with_gas_price(
+                u128::try_from(block.base_fee_per_gas).context("base fee does not fit in u128")?
+                    + POST_AUCTION_GAS_PRICE_PREMIUM,
+            )
+            .with_chain_id(
+                u64::try_from(self.state.chain_id).context("chain id does not fit in u64")?,
+            )
+            .with_nonce(u64::try_from(nonce).context("nonce does not fit in u64")?)
+            .input(Bytes::from(input).into())
+            .gas_limit(
+                u128::try_from(self.state.gas_needed_post_auction_results)
+                    .context("post auction gas does not fit in u128")?,
+            );
+        let mut signed = Vec::new();
+        tx.build(&EthereumSigner::from(wallet))
+            .await
+            .context("failed to sign postAuctionResults() transaction")?
+            .encode_2718(&mut signed);
+        Ok(signed.into())
+    }
+}
+
+/// `Bid memory bestBid` before any bid is seen.
+fn empty_bid() -> IAuctionSuapp::Bid {
+    IAuctionSuapp::Bid {
+        bidder: Address::ZERO,
+        blockNumber: U256::ZERO,
+        amount: U256::ZERO,
+        swapTxn: Bytes::new(),
+        v: 0,
+        r: B256::ZERO,
+        s: B256::ZERO,
+    }
+}
+
+/// `uint256(bytes32(data))`, right padding data shorter than a word.
+fn word(data: &[u8]) -> U256 {
+    let mut word = [0u8; 32];
+    let len = data.len().min(32);
+    word[..len].copy_from_slice(&data[..len]);
+    U256::from_be_bytes(word)
+}
+
+fn hex_result(value: Value, method: &str) -> eyre::Result<U256> {
+    serde_json::from_value::<U256>(value).context(format!("{} result is not a hex number", method))
+}
+
+async fn last_l1_block(host: &dyn KettleHost, http_url: &str) -> eyre::Result<L1Block> {
+    let number = hex_result(
+        host.json_rpc(http_url, "eth_blockNumber", json!([]))
+            .await?,
+        "eth_blockNumber",
+    )?;
+    let block = host
+        .json_rpc(http_url, "eth_getBlockByNumber", json!([number, false]))
+        .await?;
+    Ok(L1Block {
+        number,
+        timestamp: hex_result(block["timestamp"].clone(), "eth_getBlockByNumber")?,
+        base_fee_per_gas: hex_result(block["baseFeePerGas"].clone(), "eth_getBlockByNumber")?,
+    })
+}
+
+async fn signing_key_nonce(
+    host: &dyn KettleHost,
+    http_url: &str,
+    signing_pub_key: Address,
+) -> eyre::Result<U256> {
+    hex_result(
+        host.json_rpc(
+            http_url,
+            "eth_getTransactionCount",
+            json!([signing_pub_key, "latest"]),
+        )
+        .await?,
+        "eth_getTransactionCount",
+    )
+}
+
+async fn deposit_balance(
+    host: &dyn KettleHost,
+    http_url: &str,
+    deposits: Address,
+    bidder: Address,
+) -> eyre::Result<U256> {
+    let data = IAuctionDeposits::balanceOfCall { bidder }.abi_encode();
+    let result = host
+        .json_rpc(
+            http_url,
+            "eth_call",
+            json!([{ "to": deposits, "data": Bytes::from(data) }, "latest"]),
+        )
+        .await?;
+    hex_result(result, "eth_call")
+}
+
+/// Sends the bundle the way `Bundle.sendBundle()` encodes it.
+async fn send_bundle(
+    host: &dyn KettleHost,
+    bundle_url: &str,
+    bundle: &SentBundle,
+) -> eyre::Result<()> {
+    let mut params = json!({
+        "blockNumber": U64::from(bundle.block_number),
+        "txs": bundle.txs,
+    });
+    if !bundle.reverting_tx_hashes.is_empty() {
+        params["revertingTxHashes"] = json!(bundle.reverting_tx_hashes);
+    }
+    host.json_rpc(bundle_url, "eth_sendBundle", json!([params]))
+        .await
+        .context("failed to send bundle")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+    use crate::raw_tx::RawTransaction;
+
+    const L1_URL: &str = "http://l1";
+    const BUNDLE_URL: &str = "http://builder";
+    /// Anvil's first dev key, the suapp's signing key.
+    const SIGNING_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    /// An L1 at block 100 and a builder taking any bundle, answered in process.
+    struct Host {
+        deposits: HashMap<Address, U256>,
+        unix_time: AtomicU64,
+    }
+
+    #[async_trait]
+    impl KettleHost for Host {
+        async fn json_rpc(&self, url: &str, method: &str, params: Value) -> eyre::Result<Value> {
+            match (url, method) {
+                (L1_URL, "eth_blockNumber") => Ok(json!(U64::from(100))),
+                (L1_URL, "eth_getBlockByNumber") => Ok(json!({
+                    "timestamp": U64::from(1000),
+                    "baseFeePerGas": U64::from(1_000_000_000),
+                })),
+                (L1_URL, "eth_getTransactionCount") => Ok(json!(U64::ZERO)),
+                (L1_URL, "eth_call") => {
+                    let data: Bytes = serde_json::from_value(params[0]["data"].clone())?;
+                    let bidder = IAuctionDeposits::balanceOfCall::abi_decode(&data, true)?.bidder;
+                    let deposit = self.deposits.get(&bidder).copied().unwrap_or_default();
+                    Ok(json!(Bytes::from(deposit.to_be_bytes_vec())))
+                }
+                (BUNDLE_URL, "eth_sendBundle") => Ok(Value::Null),
+                (url, method) => eyre::bail!("unexpected {} to {}", method, url),
+            }
+        }
+
+        fn unix_time(&self) -> u64 {
+            self.unix_time.load(Ordering::SeqCst)
+        }
+    }
+
+    fn bid(bidder: Address, amount: u64) -> IAuctionSuapp::Bid {
+        IAuctionSuapp::Bid {
+            bidder,
+            blockNumber: U256::from(101),
+            amount: U256::from(amount),
+            swapTxn: Bytes::from(bidder.to_vec()),
+            v: 27,
+            r: B256::repeat_byte(1),
+            s: B256::repeat_byte(2),
+        }
+    }
+
+    #[tokio::test]
+    async fn underfunded_bids_are_skipped_and_the_winner_pays_the_second_price() {
+        let owner = Address::repeat_byte(1);
+        let guard = Address::repeat_byte(3);
+        let (underfunded, winner, runner_up) = (
+            Address::repeat_byte(0xa),
+            Address::repeat_byte(0xb),
+            Address::repeat_byte(0xc),
+        );
+        let host = Host {
+            deposits: HashMap::from([
+                (underfunded, U256::from(1000)),
+                (winner, U256::from(5000)),
+                (runner_up, U256::from(5000)),
+            ]),
+            unix_time: AtomicU64::new(1002),
+        };
+        let mut model = SuappModel::new(owner, Address::repeat_byte(2), guard, 17000, 300_000);
+
+        let signing_key: LocalWallet = SIGNING_KEY.parse().unwrap();
+        let setup: [(Vec<u8>, Vec<u8>); 4] = [
+            (
+                IAuctionSuapp::setL1UrlCall {}.abi_encode(),
+                L1_URL.as_bytes().to_vec(),
+            ),
+            (
+                IAuctionSuapp::setBundleUrlCall {}.abi_encode(),
+                BUNDLE_URL.as_bytes().to_vec(),
+            ),
+            (
+                IAuctionSuapp::setSigningKeyCall {
+                    pubKey: signing_key.address(),
+                }
+                .abi_encode(),
+                hex::decode(SIGNING_KEY).unwrap(),
+            ),
+            (
+                IAuctionSuapp::initLastL1BlockCall {}.abi_encode(),
+                Vec::new(),
+            ),
+        ];
+        for (calldata, confidential_inputs) in setup {
+            model
+                .execute(&host, owner, &calldata, &confidential_inputs)
+                .await
+                .unwrap();
+        }
+
+        // the highest bid isn't covered by its bidder's deposit
+        let bids = [
+            bid(underfunded, 3000),
+            bid(winner, 2000),
+            bid(runner_up, 500),
+        ];
+        let new_bid = IAuctionSuapp::newBidCall {
+            salt: "111".to_string(),
+        }
+        .abi_encode();
+        for bid in &bids {
+            model
+                .execute(&host, bid.bidder, &new_bid, &bid.abi_encode())
+                .await
+                .unwrap();
+        }
+        assert_eq!(model.state.block_bids[&U256::from(101)].len(), 3);
+
+        // the auction for block 101 ends 4s after block 100
+        host.unix_time.store(1010, Ordering::SeqCst);
+        model
+            .execute(
+                &host,
+                owner,
+                &IAuctionSuapp::runAuctionCall {}.abi_encode(),
+                &[],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(model.state.winning_bid_amount, U256::from(500));
+        assert_eq!(model.state.last_auction_processed_l1_block, U256::from(101));
+
+        let bundle = &model.sent_bundles()[0];
+        assert_eq!(bundle.block_number, 101);
+        assert_eq!(bundle.txs.len(), 2);
+        let payment = RawTransaction::decode(&bundle.txs[0]).unwrap();
+        assert_eq!(payment.from, signing_key.address());
+        assert_eq!(payment.to, Some(guard));
+        let results =
+            IAuctionGuard::postAuctionResultsCall::abi_decode(&payment.input, true).unwrap();
+        assert_eq!(results.bidder, winner);
+        assert_eq!(results.price, U256::from(2000));
+        assert!(results.auction);
+        assert_eq!(bundle.txs[1], bids[1].swapTxn);
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use alloy_primitives::Address;
+use color_eyre::{eyre, eyre::Context};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs,
+    sync::{Mutex, OwnedMutexGuard},
+};
+
+/// The kind of nonce error a node returned for a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceErrorKind {
+    TooLow,
+    TooHigh,
+}
+
+impl NonceErrorKind {
+    /// Checks an error chain for a node's "nonce too low"/"nonce too high" response.
+    pub fn classify(err: &eyre::Report) -> Option<Self> {
+        err.chain().find_map(|cause| {
+            let message = cause.to_string().to_lowercase();
+            if message.contains("nonce too low") {
+                Some(NonceErrorKind::TooLow)
+            } else if message.contains("nonce too high") {
+                Some(NonceErrorKind::TooHigh)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// On-disk representation of a single tracked signer.
+#[derive(Serialize, Deserialize)]
+struct NonceEntry {
+    chain_id: u64,
+    address: Address,
+    next_nonce: u64,
+    /// Nonces below `next_nonce` whose transactions were never sent
+    #[serde(default)]
+    released: Vec<u64>,
+}
+
+/// Nonces tracked for a single (chain, signer).
+#[derive(Debug, Default)]
+struct SignerNonces {
+    next: u64,
+    /// Reserved nonces given back by failed sends, handed out again before `next`
+    released: BTreeSet<u64>,
+}
+
+/// Hands out sequential nonces per (chain, signer) so multiple transactions can be signed
+/// before any of them land.
+///
+/// Nonces are reconciled with the chain's pending transaction count on every request and
+/// can optionally be persisted to disk so a restarted process doesn't reuse nonces of its
+/// own in-flight transactions. A nonce whose transaction couldn't be sent is released and
+/// handed out again, so a failed send doesn't leave a gap.
+///
+/// Senders that resync on nonce errors should hold `lock_signer()` from reserving a nonce
+/// until the node took or rejected its transaction, a resync while another send is in
+/// flight hands its nonce out again.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    signers: Mutex<HashMap<(u64, Address), SignerNonces>>,
+    /// Held by the signer's send in flight
+    send_locks: Mutex<HashMap<Address, Arc<Mutex<()>>>>,
+    persist_path: Option<PathBuf>,
+}
+
+impl NonceManager {
+    /// Creates an in-memory nonce manager.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a nonce manager persisted to `path`, loading any previously stored nonces.
+    pub async fn with_persistence(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut signers = HashMap::new();
+        if fs::try_exists(&path).await.unwrap_or(false) {
+            let contents = fs::read_to_string(&path)
+                .await
+                .context(format!("failed to read nonce store {}", path.display()))?;
+            let entries: Vec<NonceEntry> =
+                serde_json::from_str(&contents).context("failed to parse nonce store")?;
+            for entry in entries {
+                signers.insert(
+                    (entry.chain_id, entry.address),
+                    SignerNonces {
+                        next: entry.next_nonce,
+                        released: entry.released.into_iter().collect(),
+                    },
+                );
+            }
+        }
+
+        Ok(Self {
+            signers: Mutex::new(signers),
+            send_locks: Mutex::default(),
+            persist_path: Some(path),
+        })
+    }
+
+    /// Waits for `address`'s other sends to finish, the next send is the caller's until the
+    /// guard is dropped.
+    pub async fn lock_signer(&self, address: Address) -> OwnedMutexGuard<()> {
+        let lock = self
+            .send_locks
+            .lock()
+            .await
+            .entry(address)
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    /// Reserves the next nonce for `address` on `chain_id`.
+    ///
+    /// `pending_count` is the chain's pending transaction count for the address, the
+    /// tracked nonce is moved up to it if other transactions from the address landed.
+    /// Released nonces are handed out first, lowest first.
+    pub async fn next_nonce(
+        &self,
+        chain_id: u64,
+        address: Address,
+        pending_count: u64,
+    ) -> eyre::Result<u64> {
+        let mut signers = self.signers.lock().await;
+        let nonces = signers
+            .entry((chain_id, address))
+            .or_insert_with(|| SignerNonces {
+                next: pending_count,
+                released: BTreeSet::new(),
+            });
+        if nonces.next < pending_count {
+            nonces.next = pending_count;
+        }
+        // released nonces the chain has moved past were used by someone else
+        nonces.released = nonces.released.split_off(&pending_count);
+        let nonce = match nonces.released.pop_first() {
+            Some(nonce) => nonce,
+            None => {
+                let nonce = nonces.next;
+                nonces.next += 1;
+                nonce
+            }
+        };
+
+        self.persist(&signers).await?;
+        Ok(nonce)
+    }
+
+    /// Gives back a nonce reserved with `next_nonce()` whose transaction was never sent.
+    pub async fn release(&self, chain_id: u64, address: Address, nonce: u64) -> eyre::Result<()> {
+        let mut signers = self.signers.lock().await;
+        let Some(nonces) = signers.get_mut(&(chain_id, address)) else {
+            return Ok(());
+        };
+        if nonce >= nonces.next {
+            return Ok(());
+        }
+        nonces.released.insert(nonce);
+        // released nonces at the top are simply not handed out yet
+        while nonces
+            .released
+            .last()
+            .is_some_and(|released| *released + 1 == nonces.next)
+        {
+            nonces.released.pop_last();
+            nonces.next -= 1;
+        }
+        self.persist(&signers).await
+    }
+
+    /// Resets the tracked nonce to the chain's pending count, used to recover after a node
+    /// rejected a transaction with a nonce error, or once reserved transactions are known
+    /// to never land.
+    pub async fn resync(
+        &self,
+        chain_id: u64,
+        address: Address,
+        pending_count: u64,
+    ) -> eyre::Result<()> {
+        let mut signers = self.signers.lock().await;
+        signers.insert(
+            (chain_id, address),
+            SignerNonces {
+                next: pending_count,
+                released: BTreeSet::new(),
+            },
+        );
+        self.persist(&signers).await
+    }
+
+    async fn persist(&self, signers: &HashMap<(u64, Address), SignerNonces>) -> eyre::Result<()> {
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+
+        let entries: Vec<NonceEntry> = signers
+            .iter()
+            .map(|((chain_id, address), nonces)| NonceEntry {
+                chain_id: *chain_id,
+                address: *address,
+                next_nonce: nonces.next,
+                released: nonces.released.iter().copied().collect(),
+            })
+            .collect();
+        let contents =
+            serde_json::to_string_pretty(&entries).context("failed to serialize nonce store")?;
+
+        // write then rename so a crash mid-write doesn't corrupt the store
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents).await.context(format!(
+            "failed to write nonce store {}",
+            tmp_path.display()
+        ))?;
+        fs::rename(&tmp_path, path)
+            .await
+            .context(format!("failed to replace nonce store {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn locked_sends_survive_resyncs() {
+        let nonce_manager = Arc::new(NonceManager::new());
+        let address = Address::repeat_byte(1);
+        // the chain's pending count, bumped when a send lands
+        let pending = Arc::new(AtomicU64::new(0));
+
+        let sends = (0..8).map(|i| {
+            let nonce_manager = nonce_manager.clone();
+            let pending = pending.clone();
+            tokio::spawn(async move {
+                let _send_lock = nonce_manager.lock_signer(address).await;
+                let nonce = nonce_manager
+                    .next_nonce(1, address, pending.load(Ordering::SeqCst))
+                    .await
+                    .unwrap();
+                tokio::task::yield_now().await;
+                // every other send is rejected once and resynced like `send_suapp_ccr()` does
+                if i % 2 == 0 {
+                    nonce_manager
+                        .resync(1, address, pending.load(Ordering::SeqCst))
+                        .await
+                        .unwrap();
+                    let nonce = nonce_manager
+                        .next_nonce(1, address, pending.load(Ordering::SeqCst))
+                        .await
+                        .unwrap();
+                    pending.fetch_add(1, Ordering::SeqCst);
+                    return nonce;
+                }
+                pending.fetch_add(1, Ordering::SeqCst);
+                nonce
+            })
+        });
+        let mut nonces = Vec::new();
+        for send in sends {
+            nonces.push(send.await.unwrap());
+        }
+        nonces.sort();
+        assert_eq!(nonces, (0..8).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn released_nonces_are_reused() {
+        let nonce_manager = NonceManager::new();
+        let address = Address::repeat_byte(1);
+        for expected in 0..3 {
+            assert_eq!(
+                nonce_manager.next_nonce(1, address, 0).await.unwrap(),
+                expected
+            );
+        }
+        nonce_manager.release(1, address, 1).await.unwrap();
+        assert_eq!(nonce_manager.next_nonce(1, address, 0).await.unwrap(), 1);
+        assert_eq!(nonce_manager.next_nonce(1, address, 0).await.unwrap(), 3);
+        // the chain moving past a nonce discards it
+        nonce_manager.release(1, address, 2).await.unwrap();
+        assert_eq!(nonce_manager.next_nonce(1, address, 3).await.unwrap(), 4);
+    }
+}
@@ -0,0 +1,485 @@
+use std::{collections::BTreeMap, fmt};
+
+use alloy::{
+    eips::BlockId,
+    providers::{Provider, RootProvider},
+    sol,
+    transports::http::Http,
+};
+use alloy_primitives::{Address, U256};
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use reqwest::Client as ReqwestClient;
+use serde::Serialize;
+
+use crate::{
+    amm_auction::{http_provider, view},
+    amm_auction_config::AmmAuctionConfig,
+    swap::DEFAULT_FEE_TIER,
+};
+
+sol! {
+    interface IUniswapV3FactoryAuctioned {
+        function createPool(address tokenA, address tokenB, uint24 fee, address auction) external returns (address pool);
+        function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address pool);
+    }
+
+    interface IUniswapV3PoolAuctioned {
+        function initialize(uint160 sqrtPriceX96) external;
+        function increaseObservationCardinalityNext(uint16 observationCardinalityNext) external;
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function fee() external view returns (uint24);
+        function tickSpacing() external view returns (int24);
+        function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked);
+        function liquidity() external view returns (uint128);
+        function feeGrowthGlobal0X128() external view returns (uint256);
+        function feeGrowthGlobal1X128() external view returns (uint256);
+        function tickBitmap(int16 wordPosition) external view returns (uint256);
+        function ticks(int24 tick) external view returns (uint128 liquidityGross, int128 liquidityNet, uint256 feeGrowthOutside0X128, uint256 feeGrowthOutside1X128, int56 tickCumulativeOutside, uint160 secondsPerLiquidityOutsideX128, uint32 secondsOutside, bool initialized);
+    }
+
+    interface IERC20Metadata {
+        function symbol() external view returns (string memory);
+        function decimals() external view returns (uint8);
+    }
+}
+
+/// One of the pool's tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PoolToken {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// The pool's current price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Slot0 {
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    /// If a swap or liquidity change is in progress the pool is locked
+    pub unlocked: bool,
+}
+
+/// A word of the pool's tick bitmap, one bit per tick spacing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TickBitmapWord {
+    pub word_position: i16,
+    pub bitmap: U256,
+    /// Ticks with liquidity starting or ending at them
+    pub initialized_ticks: Vec<i32>,
+}
+
+/// Snapshot of the pool's state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoolState {
+    pub pool: Address,
+    /// Block every field was read at
+    pub block_number: u64,
+    pub token_0: PoolToken,
+    pub token_1: PoolToken,
+    pub fee: u32,
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    /// In range liquidity
+    pub liquidity: u128,
+    pub fee_growth_global_0_x128: U256,
+    pub fee_growth_global_1_x128: U256,
+    /// Token 1 per token 0, adjusted for decimals
+    pub price_0_in_1: f64,
+    /// Token 0 per token 1, adjusted for decimals
+    pub price_1_in_0: f64,
+}
+
+impl fmt::Display for PoolState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pool State")?;
+        writeln!(f, "  pool                 : {}", self.pool)?;
+        writeln!(f, "  block                : {}", self.block_number)?;
+        writeln!(f, "  fee                  : {}", self.fee)?;
+        writeln!(f, "  sqrt price x96       : {}", self.sqrt_price_x96)?;
+        writeln!(f, "  tick                 : {}", self.tick)?;
+        writeln!(f, "  liquidity            : {}", self.liquidity)?;
+        writeln!(
+            f,
+            "  fee growth 0 x128    : {}",
+            self.fee_growth_global_0_x128
+        )?;
+        writeln!(
+            f,
+            "  fee growth 1 x128    : {}",
+            self.fee_growth_global_1_x128
+        )?;
+        writeln!(
+            f,
+            "  price                : {} {} per {}",
+            self.price_0_in_1, self.token_1.symbol, self.token_0.symbol
+        )?;
+        write!(
+            f,
+            "  price                : {} {} per {}",
+            self.price_1_in_0, self.token_0.symbol, self.token_1.symbol
+        )
+    }
+}
+
+/// Reads the state of a `UniswapV3PoolAuctioned.sol` pool.
+pub struct PoolClient {
+    provider: RootProvider<Http<ReqwestClient>>,
+    pool: Address,
+    token_0: PoolToken,
+    token_1: PoolToken,
+    fee: u32,
+    tick_spacing: i32,
+}
+
+impl PoolClient {
+    /// Builds a client for the pool at `pool`, reading its immutable parameters.
+    pub async fn new(l1_rpc: &str, pool: Address) -> eyre::Result<Self> {
+        let provider = http_provider(l1_rpc)?;
+
+        let token_0 = view(
+            &provider,
+            pool,
+            IUniswapV3PoolAuctioned::token0Call {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0;
+        let token_1 = view(
+            &provider,
+            pool,
+            IUniswapV3PoolAuctioned::token1Call {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0;
+        let fee = view(
+            &provider,
+            pool,
+            IUniswapV3PoolAuctioned::feeCall {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0;
+        let tick_spacing = view(
+            &provider,
+            pool,
+            IUniswapV3PoolAuctioned::tickSpacingCall {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0;
+
+        Ok(Self {
+            token_0: pool_token(&provider, token_0).await?,
+            token_1: pool_token(&provider, token_1).await?,
+            provider,
+            pool,
+            fee,
+            tick_spacing,
+        })
+    }
+
+    /// Builds a client for the factory's pool of two tokens, in either order, and a fee tier.
+    pub async fn from_factory(
+        l1_rpc: &str,
+        factory: Address,
+        token_a: Address,
+        token_b: Address,
+        fee: u32,
+    ) -> eyre::Result<Self> {
+        let provider = http_provider(l1_rpc)?;
+        let pool = view(
+            &provider,
+            factory,
+            IUniswapV3FactoryAuctioned::getPoolCall {
+                tokenA: token_a,
+                tokenB: token_b,
+                fee,
+            },
+            BlockId::latest(),
+        )
+        .await?
+        .pool;
+        eyre::ensure!(
+            !pool.is_zero(),
+            "factory {} has no pool for {} and {} with fee {}",
+            factory,
+            token_a,
+            token_b,
+            fee
+        );
+        Self::new(l1_rpc, pool).await
+    }
+
+    /// Builds a client for the configured `POOL`, or the configured factory's pool of
+    /// `TOKEN_0` and `TOKEN_1` if it isn't set.
+    pub async fn from_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        if let Some(pool) = config.pool {
+            return Self::new(&config.rpc_url_l1, pool).await;
+        }
+        Self::from_factory(
+            &config.rpc_url_l1,
+            config.factory.context("factory not set")?,
+            config.token_0.context("token 0 not set")?,
+            config.token_1.context("token 1 not set")?,
+            config.swap_fee_tier.unwrap_or(DEFAULT_FEE_TIER),
+        )
+        .await
+    }
+
+    pub fn address(&self) -> Address {
+        self.pool
+    }
+
+    pub fn token_0(&self) -> &PoolToken {
+        &self.token_0
+    }
+
+    pub fn token_1(&self) -> &PoolToken {
+        &self.token_1
+    }
+
+    pub fn fee(&self) -> u32 {
+        self.fee
+    }
+
+    pub fn tick_spacing(&self) -> i32 {
+        self.tick_spacing
+    }
+
+    /// Latest block number, to pin a set of reads to one block.
+    pub async fn latest_block(&self) -> eyre::Result<u64> {
+        self.provider
+            .get_block_number()
+            .await
+            .context("failed to get latest L1 block number")
+    }
+
+    pub async fn slot0(&self, block_number: u64) -> eyre::Result<Slot0> {
+        let slot0 = view(
+            &self.provider,
+            self.pool,
+            IUniswapV3PoolAuctioned::slot0Call {},
+            BlockId::number(block_number),
+        )
+        .await?;
+        Ok(Slot0 {
+            sqrt_price_x96: slot0.sqrtPriceX96,
+            tick: slot0.tick,
+            unlocked: slot0.unlocked,
+        })
+    }
+
+    pub async fn liquidity(&self, block_number: u64) -> eyre::Result<u128> {
+        Ok(view(
+            &self.provider,
+            self.pool,
+            IUniswapV3PoolAuctioned::liquidityCall {},
+            BlockId::number(block_number),
+        )
+        .await?
+        ._0)
+    }
+
+    /// Fee growth per unit of liquidity of token 0 and token 1, as Q128.128.
+    pub async fn fee_growth_global(&self, block_number: u64) -> eyre::Result<(U256, U256)> {
+        let fee_growth_0 = view(
+            &self.provider,
+            self.pool,
+            IUniswapV3PoolAuctioned::feeGrowthGlobal0X128Call {},
+            BlockId::number(block_number),
+        )
+        .await?
+        ._0;
+        let fee_growth_1 = view(
+            &self.provider,
+            self.pool,
+            IUniswapV3PoolAuctioned::feeGrowthGlobal1X128Call {},
+            BlockId::number(block_number),
+        )
+        .await?
+        ._0;
+        Ok((fee_growth_0, fee_growth_1))
+    }
+
+    pub async fn tick_bitmap_word(
+        &self,
+        word_position: i16,
+        block_number: u64,
+    ) -> eyre::Result<TickBitmapWord> {
+        let bitmap = view(
+            &self.provider,
+            self.pool,
+            IUniswapV3PoolAuctioned::tickBitmapCall {
+                wordPosition: word_position,
+            },
+            BlockId::number(block_number),
+        )
+        .await?
+        ._0;
+        let initialized_ticks = (0..256)
+            .filter(|bit| bitmap.bit(*bit))
+            .map(|bit| (i32::from(word_position) * 256 + bit as i32) * self.tick_spacing)
+            .collect();
+        Ok(TickBitmapWord {
+            word_position,
+            bitmap,
+            initialized_ticks,
+        })
+    }
+
+    /// The bitmap words within `radius` words of the one holding `tick`.
+    pub async fn tick_bitmap_words_around(
+        &self,
+        tick: i32,
+        radius: i16,
+        block_number: u64,
+    ) -> eyre::Result<Vec<TickBitmapWord>> {
+        let word_position = tick_word_position(tick, self.tick_spacing);
+        let mut words = Vec::new();
+        for offset in -radius..=radius {
+            if let Some(position) = word_position.checked_add(offset) {
+                words.push(self.tick_bitmap_word(position, block_number).await?);
+            }
+        }
+        Ok(words)
+    }
+
+    /// Liquidity added (or removed, if negative) when the price crosses `tick` upwards.
+    pub async fn liquidity_net(&self, tick: i32, block_number: u64) -> eyre::Result<i128> {
+        Ok(view(
+            &self.provider,
+            self.pool,
+            IUniswapV3PoolAuctioned::ticksCall { tick },
+            BlockId::number(block_number),
+        )
+        .await?
+        .liquidityNet)
+    }
+
+    /// Reads the price, in range liquidity, and the net liquidity of every initialized tick
+    /// within `word_radius` bitmap words of the current tick at the latest block, for
+    /// simulating swaps.
+    pub async fn tick_liquidity(&self, word_radius: i16) -> eyre::Result<TickLiquidity> {
+        self.tick_liquidity_at(word_radius, self.latest_block().await?)
+            .await
+    }
+
+    /// `tick_liquidity()` with every read at `block_number`.
+    pub async fn tick_liquidity_at(
+        &self,
+        word_radius: i16,
+        block_number: u64,
+    ) -> eyre::Result<TickLiquidity> {
+        let slot0 = self.slot0(block_number).await?;
+        let words = self
+            .tick_bitmap_words_around(slot0.tick, word_radius, block_number)
+            .await?;
+        let mut ticks = BTreeMap::new();
+        for tick in words.iter().flat_map(|word| &word.initialized_ticks) {
+            ticks.insert(*tick, self.liquidity_net(*tick, block_number).await?);
+        }
+        Ok(TickLiquidity {
+            sqrt_price_x96: slot0.sqrt_price_x96,
+            tick: slot0.tick,
+            liquidity: self.liquidity(block_number).await?,
+            fee: self.fee,
+            tick_spacing: self.tick_spacing,
+            ticks,
+            words: (
+                words
+                    .first()
+                    .context("no tick bitmap words read")?
+                    .word_position,
+                words
+                    .last()
+                    .context("no tick bitmap words read")?
+                    .word_position,
+            ),
+        })
+    }
+
+    /// Reads the pool's price, liquidity and fee growth at the latest block.
+    pub async fn state(&self) -> eyre::Result<PoolState> {
+        self.state_at(self.latest_block().await?).await
+    }
+
+    /// `state()` with every read at `block_number`, so they can't straddle a new block.
+    pub async fn state_at(&self, block_number: u64) -> eyre::Result<PoolState> {
+        let slot0 = self.slot0(block_number).await?;
+        let (fee_growth_global_0_x128, fee_growth_global_1_x128) =
+            self.fee_growth_global(block_number).await?;
+        let price_0_in_1 = sqrt_price_x96_to_price(
+            slot0.sqrt_price_x96,
+            self.token_0.decimals,
+            self.token_1.decimals,
+        );
+        Ok(PoolState {
+            pool: self.pool,
+            block_number,
+            token_0: self.token_0.clone(),
+            token_1: self.token_1.clone(),
+            fee: self.fee,
+            sqrt_price_x96: slot0.sqrt_price_x96,
+            tick: slot0.tick,
+            liquidity: self.liquidity(block_number).await?,
+            fee_growth_global_0_x128,
+            fee_growth_global_1_x128,
+            price_0_in_1,
+            price_1_in_0: if price_0_in_1 == 0.0 {
+                0.0
+            } else {
+                1.0 / price_0_in_1
+            },
+        })
+    }
+}
+
+/// Token 1 per token 0 at `sqrt_price_x96`, adjusted for the tokens' decimals.
+pub fn sqrt_price_x96_to_price(sqrt_price_x96: U256, decimals_0: u8, decimals_1: u8) -> f64 {
+    let sqrt_price = u256_to_f64(sqrt_price_x96) / 2f64.powi(96);
+    sqrt_price * sqrt_price * 10f64.powi(i32::from(decimals_0) - i32::from(decimals_1))
+}
+
+/// Position of the bitmap word holding `tick`'s bit.
+pub fn tick_word_position(tick: i32, tick_spacing: i32) -> i16 {
+    // compressed ticks round towards negative infinity
+    let compressed = tick.div_euclid(tick_spacing);
+    (compressed >> 8) as i16
+}
+
+pub(crate) fn u256_to_f64(value: U256) -> f64 {
+    value
+        .as_limbs()
+        .iter()
+        .rev()
+        .fold(0.0, |acc, limb| acc * 2f64.powi(64) + *limb as f64)
+}
+
+async fn pool_token(
+    provider: &RootProvider<Http<ReqwestClient>>,
+    token: Address,
+) -> eyre::Result<PoolToken> {
+    Ok(PoolToken {
+        address: token,
+        symbol: view(
+            provider,
+            token,
+            IERC20Metadata::symbolCall {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0,
+        decimals: view(
+            provider,
+            token,
+            IERC20Metadata::decimalsCall {},
+            BlockId::latest(),
+        )
+        .await?
+        ._0,
+    })
+}
@@ -0,0 +1,539 @@
+use std::{collections::BTreeMap, fmt};
+
+use alloy::{
+    providers::{Provider, RootProvider},
+    transports::http::Http,
+};
+use alloy_primitives::{Address, Bytes, B256, U64};
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use reqwest::Client as ReqwestClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    amm_auction::http_provider,
+    amm_auction_config::AmmAuctionConfig,
+    guard::{GuardBlockState, GuardClient, GuardRevert},
+    pool::PoolClient,
+};
+
+/// Why no one but the auction winner could swap through the pool in a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockReason {
+    /// No auction results landed, guarded swaps revert with `WrongValidWinnerBlock`
+    NoResults,
+    /// The winner was posted but never swapped, other swaps revert with `WrongWinner`
+    WinnerNeverSwapped,
+}
+
+/// A transaction to the pool or swap router that reverted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RevertedSwap {
+    pub block_number: u64,
+    pub hash: B256,
+    pub from: Address,
+    pub to: Address,
+    /// The guard error it reverted with, `None` if the node can't trace transactions
+    pub revert: Option<GuardRevert>,
+}
+
+impl fmt::Display for RevertedSwap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} from {} in block {}",
+            self.hash, self.from, self.block_number
+        )?;
+        match &self.revert {
+            Some(GuardRevert::Unknown(data)) if data.is_empty() => write!(f, ", no revert data"),
+            Some(GuardRevert::Unknown(_)) => write!(f, ", not a guard revert"),
+            Some(revert) => write!(f, ", {:?}", revert),
+            None => write!(f, ", revert reason unknown"),
+        }
+    }
+}
+
+/// Whether the pool was locked in a block and the swaps that tried it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockLockStatus {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub guard: GuardBlockState,
+    pub lock: Option<LockReason>,
+    /// Transactions sent to the pool or swap router
+    pub swap_attempts: u64,
+    pub reverted_swaps: Vec<RevertedSwap>,
+}
+
+/// Consecutive blocks the pool was locked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LockedPeriod {
+    pub start_block: u64,
+    pub end_block: u64,
+    /// From the first locked block's timestamp to the unlocked block after the period, or to
+    /// the last locked block's if the pool is still locked
+    pub duration_secs: u64,
+    /// If the last block checked was still locked
+    pub ongoing: bool,
+}
+
+impl LockedPeriod {
+    pub fn blocks(&self) -> u64 {
+        self.end_block - self.start_block + 1
+    }
+}
+
+/// Locked blocks found over a range of blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PoolLockReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub locked_blocks: Vec<u64>,
+    pub locked_periods: Vec<LockedPeriod>,
+    pub locked_secs: u64,
+    pub swap_attempts: u64,
+    /// Reverted swaps of every block checked, not only the locked ones
+    pub reverted_swaps: Vec<RevertedSwap>,
+}
+
+impl fmt::Display for PoolLockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pool Locks")?;
+        writeln!(
+            f,
+            "  blocks               : {} - {}",
+            self.from_block, self.to_block
+        )?;
+        writeln!(f, "  locked blocks        : {}", self.locked_blocks.len())?;
+        writeln!(f, "  locked for           : {}s", self.locked_secs)?;
+        writeln!(f, "  swap attempts        : {}", self.swap_attempts)?;
+        write!(f, "  reverted swaps       : {}", self.reverted_swaps.len())?;
+        for period in &self.locked_periods {
+            write!(
+                f,
+                "\n  - locked {} - {} ({} blocks, {}s{})",
+                period.start_block,
+                period.end_block,
+                period.blocks(),
+                period.duration_secs,
+                if period.ongoing { ", ongoing" } else { "" }
+            )?;
+        }
+        for swap in &self.reverted_swaps {
+            write!(f, "\n  - reverted {}", swap)?;
+        }
+        Ok(())
+    }
+}
+
+/// Folds blocks' lock statuses into periods and totals.
+///
+/// Statuses are kept per block number, so pushing a block again replaces it, and blocks
+/// replaced by a reorg can be rolled back before their new versions are pushed.
+#[derive(Debug, Clone, Default)]
+pub struct PoolLockTracker {
+    blocks: BTreeMap<u64, BlockLockStatus>,
+    /// Timestamp of the unlocked block after the last period, if it was ended with
+    /// `end_period()`
+    closing_timestamp: Option<u64>,
+}
+
+impl PoolLockTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a block's status, replacing the block's earlier one, returning the period it
+    /// ended if the pool was locked up to the block before and it's unlocked.
+    pub fn push(&mut self, status: &BlockLockStatus) -> Option<LockedPeriod> {
+        let ongoing = self.ongoing_period();
+        self.blocks.insert(status.block_number, status.clone());
+        self.closing_timestamp = None;
+        let ongoing = ongoing?;
+        self.locked_periods()
+            .into_iter()
+            .find(|period| period.start_block == ongoing.start_block && !period.ongoing)
+    }
+
+    /// Forgets `from_block` and every block after it, once a reorg replaced them.
+    pub fn rollback(&mut self, from_block: u64) {
+        self.blocks.split_off(&from_block);
+        self.closing_timestamp = None;
+    }
+
+    /// Ends the open period at `timestamp`, the unlocked block after it, without adding that
+    /// block to the tracked range.
+    pub fn end_period(&mut self, timestamp: u64) -> Option<LockedPeriod> {
+        let ongoing = self.ongoing_period()?;
+        self.closing_timestamp = Some(timestamp);
+        Some(LockedPeriod {
+            duration_secs: timestamp.saturating_sub(self.blocks[&ongoing.start_block].timestamp),
+            ongoing: false,
+            ..ongoing
+        })
+    }
+
+    /// Blocks locked in a row up to the last one pushed, 0 if it's unlocked.
+    pub fn current_streak(&self) -> u64 {
+        self.ongoing_period()
+            .map(|period| period.blocks())
+            .unwrap_or(0)
+    }
+
+    pub fn locked_block_count(&self) -> u64 {
+        self.blocks
+            .values()
+            .filter(|status| status.lock.is_some())
+            .count() as u64
+    }
+
+    /// Seconds locked so far, counting the ongoing period up to the last block pushed.
+    pub fn locked_secs(&self) -> u64 {
+        self.locked_periods()
+            .iter()
+            .map(|period| period.duration_secs)
+            .sum()
+    }
+
+    pub fn report(&self) -> PoolLockReport {
+        PoolLockReport {
+            from_block: self.blocks.keys().next().copied().unwrap_or_default(),
+            to_block: self.blocks.keys().next_back().copied().unwrap_or_default(),
+            locked_blocks: self
+                .blocks
+                .values()
+                .filter(|status| status.lock.is_some())
+                .map(|status| status.block_number)
+                .collect(),
+            locked_periods: self.locked_periods(),
+            locked_secs: self.locked_secs(),
+            swap_attempts: self
+                .blocks
+                .values()
+                .map(|status| status.swap_attempts)
+                .sum(),
+            reverted_swaps: self
+                .blocks
+                .values()
+                .flat_map(|status| status.reverted_swaps.iter().cloned())
+                .collect(),
+        }
+    }
+
+    /// The period the last block pushed is in, if it's locked.
+    fn ongoing_period(&self) -> Option<LockedPeriod> {
+        self.locked_periods().pop().filter(|period| period.ongoing)
+    }
+
+    /// Runs of consecutive locked blocks, in block order.
+    fn locked_periods(&self) -> Vec<LockedPeriod> {
+        let mut periods = Vec::new();
+        // first and last block of the run, with their timestamps
+        let mut open: Option<((u64, u64), (u64, u64))> = None;
+        for status in self.blocks.values() {
+            let consecutive = |(last, _): (u64, u64)| status.block_number == last + 1;
+            match open {
+                Some((first, last)) if status.lock.is_some() && consecutive(last) => {
+                    open = Some((first, (status.block_number, status.timestamp)));
+                    continue;
+                }
+                // a gap in the blocks ends the period, its length is unknown past the last
+                // block seen
+                Some(((start, start_timestamp), last)) => {
+                    let end_timestamp = if consecutive(last) {
+                        status.timestamp
+                    } else {
+                        last.1
+                    };
+                    periods.push(LockedPeriod {
+                        start_block: start,
+                        end_block: last.0,
+                        duration_secs: end_timestamp.saturating_sub(start_timestamp),
+                        ongoing: false,
+                    });
+                    open = None;
+                }
+                None => {}
+            }
+            if status.lock.is_some() {
+                let block = (status.block_number, status.timestamp);
+                open = Some((block, block));
+            }
+        }
+
+        if let Some(((start, start_timestamp), (end, end_timestamp))) = open {
+            periods.push(LockedPeriod {
+                start_block: start,
+                end_block: end,
+                duration_secs: self
+                    .closing_timestamp
+                    .unwrap_or(end_timestamp)
+                    .saturating_sub(start_timestamp),
+                ongoing: self.closing_timestamp.is_none(),
+            });
+        }
+        periods
+    }
+}
+
+/// Subset of a block's transaction.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockTx {
+    hash: B256,
+    from: Address,
+    to: Option<Address>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockFields {
+    timestamp: U64,
+    transactions: Vec<BlockTx>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReceiptStatus {
+    status: Option<U64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallFrame {
+    output: Option<Bytes>,
+}
+
+/// Finds blocks the pool was locked in because their auction didn't conclude.
+///
+/// While auctions are enabled every guarded swap reverts until the block's
+/// `postAuctionResults()` lands, and with a winner until the winner swaps. Each block's guard
+/// state is read at the end of the block, and its transactions to the pool or swap router are
+/// the swap attempts. Swaps through other contracts aren't seen.
+pub struct PoolLockDetector {
+    provider: RootProvider<Http<ReqwestClient>>,
+    guard: GuardClient,
+    pool: Address,
+    swap_router: Address,
+}
+
+impl PoolLockDetector {
+    pub fn new(
+        l1_rpc: &str,
+        auction_guard: Address,
+        pool: Address,
+        swap_router: Address,
+    ) -> eyre::Result<Self> {
+        let provider = http_provider(l1_rpc)?;
+
+        Ok(Self {
+            guard: GuardClient::from_provider(provider.clone(), auction_guard),
+            provider,
+            pool,
+            swap_router,
+        })
+    }
+
+    /// Uses the config's pool, or resolves it from the factory if it isn't set.
+    pub async fn from_config(config: &AmmAuctionConfig) -> eyre::Result<Self> {
+        let pool = match config.pool {
+            Some(pool) => pool,
+            None => PoolClient::from_config(config)
+                .await
+                .context("pool not set and failed to resolve it")?
+                .address(),
+        };
+        Self::new(
+            &config.rpc_url_l1,
+            config.auction_guard.context("auction guard not set")?,
+            pool,
+            config.swap_router.context("swap router not set")?,
+        )
+    }
+
+    pub async fn latest_block(&self) -> eyre::Result<u64> {
+        self.provider
+            .get_block_number()
+            .await
+            .context("failed to get latest block number")
+    }
+
+    /// Checks if the pool was locked in `block_number` and which swaps reverted in it.
+    pub async fn check_block(&self, block_number: u64) -> eyre::Result<BlockLockStatus> {
+        let block: Option<BlockFields> = self
+            .provider
+            .client()
+            .request("eth_getBlockByNumber", (U64::from(block_number), true))
+            .await
+            .context(format!("failed to get block {}", block_number))?;
+        let block = block.context(format!("block {} not produced yet", block_number))?;
+        let guard = self.guard.block_state(block_number).await.context(format!(
+            "failed to read guard state at block {}",
+            block_number
+        ))?;
+        let lock = if guard.no_results() {
+            Some(LockReason::NoResults)
+        } else if guard.winner_never_swapped() {
+            Some(LockReason::WinnerNeverSwapped)
+        } else {
+            None
+        };
+
+        let mut swap_attempts = 0;
+        let mut reverted_swaps = Vec::new();
+        for tx in &block.transactions {
+            let Some(to) = tx
+                .to
+                .filter(|to| *to == self.pool || *to == self.swap_router)
+            else {
+                continue;
+            };
+            swap_attempts += 1;
+            if self.succeeded(tx.hash).await? {
+                continue;
+            }
+            reverted_swaps.push(RevertedSwap {
+                block_number,
+                hash: tx.hash,
+                from: tx.from,
+                to,
+                revert: self
+                    .revert_data(tx.hash)
+                    .await
+                    .map(|data| GuardRevert::decode(&data)),
+            });
+        }
+
+        Ok(BlockLockStatus {
+            block_number,
+            timestamp: block.timestamp.to::<u64>(),
+            guard,
+            lock,
+            swap_attempts,
+            reverted_swaps,
+        })
+    }
+
+    /// Checks every block from `from_block` to `to_block`, inclusive.
+    pub async fn scan(&self, from_block: u64, to_block: u64) -> eyre::Result<PoolLockReport> {
+        eyre::ensure!(
+            from_block <= to_block,
+            "from block {} is after to block {}",
+            from_block,
+            to_block
+        );
+        let mut tracker = PoolLockTracker::new();
+        for block_number in from_block..=to_block {
+            tracker.push(&self.check_block(block_number).await?);
+        }
+        // a period running into the last block lasts until the next block, if it's unlocked
+        if tracker.current_streak() > 0 {
+            if let Ok(next) = self.check_block(to_block + 1).await {
+                if next.lock.is_none() {
+                    tracker.end_period(next.timestamp);
+                }
+            }
+        }
+        Ok(tracker.report())
+    }
+
+    async fn succeeded(&self, tx_hash: B256) -> eyre::Result<bool> {
+        let receipt: Option<ReceiptStatus> = self
+            .provider
+            .client()
+            .request("eth_getTransactionReceipt", (tx_hash,))
+            .await
+            .context(format!("failed to get receipt of {}", tx_hash))?;
+        let status = receipt
+            .context(format!("no receipt for {}", tx_hash))?
+            .status
+            .context(format!("receipt of {} has no status", tx_hash))?;
+        Ok(status == U64::from(1))
+    }
+
+    /// Revert data of a mined transaction from `debug_traceTransaction`, which not every node
+    /// serves.
+    async fn revert_data(&self, tx_hash: B256) -> Option<Bytes> {
+        let frame: CallFrame = self
+            .provider
+            .client()
+            .request(
+                "debug_traceTransaction",
+                (tx_hash, json!({ "tracer": "callTracer" })),
+            )
+            .await
+            .ok()?;
+        Some(frame.output.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(block_number: u64, locked: bool) -> BlockLockStatus {
+        BlockLockStatus {
+            block_number,
+            timestamp: block_number * 12,
+            guard: GuardBlockState {
+                block_number,
+                auctions_enabled: true,
+                last_concluded_block: if locked { 0 } else { block_number },
+                winner_valid_block: 0,
+            },
+            lock: locked.then_some(LockReason::NoResults),
+            swap_attempts: 1,
+            reverted_swaps: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn periods_end_on_unlocked_block() {
+        let mut tracker = PoolLockTracker::new();
+        assert_eq!(tracker.push(&status(10, false)), None);
+        assert_eq!(tracker.push(&status(11, true)), None);
+        assert_eq!(tracker.push(&status(12, true)), None);
+        assert_eq!(tracker.current_streak(), 2);
+        let period = tracker.push(&status(13, false)).unwrap();
+        assert_eq!((period.start_block, period.end_block), (11, 12));
+        assert_eq!(period.duration_secs, 24);
+        assert!(!period.ongoing);
+        assert_eq!(tracker.current_streak(), 0);
+        assert_eq!(tracker.locked_secs(), 24);
+    }
+
+    #[test]
+    fn pushing_a_block_again_replaces_it() {
+        let mut tracker = PoolLockTracker::new();
+        for block_number in 10..=12 {
+            tracker.push(&status(block_number, true));
+        }
+        tracker.push(&status(12, true));
+        tracker.push(&status(11, true));
+
+        let report = tracker.report();
+        assert_eq!(report.locked_blocks, vec![10, 11, 12]);
+        assert_eq!(report.swap_attempts, 3);
+        assert_eq!(tracker.current_streak(), 3);
+        assert_eq!(tracker.locked_secs(), 24);
+    }
+
+    #[test]
+    fn rollback_forgets_reorged_blocks() {
+        let mut tracker = PoolLockTracker::new();
+        tracker.push(&status(10, false));
+        tracker.push(&status(11, true));
+        tracker.push(&status(12, true));
+
+        // the reorg replaced 11 and 12 with unlocked blocks
+        tracker.rollback(11);
+        assert_eq!(tracker.current_streak(), 0);
+        assert_eq!(tracker.push(&status(11, false)), None);
+        assert_eq!(tracker.push(&status(12, false)), None);
+
+        let report = tracker.report();
+        assert_eq!((report.from_block, report.to_block), (10, 12));
+        assert!(report.locked_blocks.is_empty());
+        assert!(report.locked_periods.is_empty());
+        assert_eq!(report.swap_attempts, 3);
+    }
+}
@@ -0,0 +1,640 @@
+use std::{
+    fmt, fs,
+    path::Path,
+    sync::{Arc, RwLock},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::{eyre, eyre::Context};
+use eyre::ContextCompat;
+use futures_util::StreamExt;
+use reqwest::Client as ReqwestClient;
+use serde::Deserialize;
+use tokio::time::{sleep, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+use crate::amm_auction_config::{AmmAuctionConfig, PriceFeedKind};
+
+/// Default age after which a quote is stale.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(30);
+/// Default interval between HTTP ticker polls.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Delay before reconnecting to a dropped websocket ticker.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A reference price for the pool's pair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceQuote {
+    /// Token 1 per token 0, adjusted for decimals like `PoolState::price_0_in_1`
+    pub price: f64,
+    /// Unix time in seconds the price is from
+    pub timestamp: u64,
+}
+
+/// A feed's latest quote and how old it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceReading {
+    pub quote: PriceQuote,
+    pub age: Duration,
+    /// If the quote is older than the feed's max age
+    pub stale: bool,
+}
+
+impl fmt::Display for PriceReading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}s old{})",
+            self.quote.price,
+            self.age.as_secs(),
+            if self.stale { ", stale" } else { "" }
+        )
+    }
+}
+
+/// A source of reference prices to bid against.
+///
+/// Feeds that stream prices update in the background, reading them never blocks.
+pub trait PriceFeed: Send + Sync {
+    /// Where the prices come from, for logging.
+    fn source(&self) -> String;
+
+    /// The latest quote, `None` until the first one arrives.
+    fn latest(&self) -> Option<PriceQuote>;
+
+    /// How old a quote can get before it's stale.
+    fn max_age(&self) -> Duration;
+
+    /// The latest quote with its staleness.
+    fn reading(&self) -> Option<PriceReading> {
+        let quote = self.latest()?;
+        let age = Duration::from_secs(unix_now().saturating_sub(quote.timestamp));
+        Some(PriceReading {
+            quote,
+            age,
+            stale: age > self.max_age(),
+        })
+    }
+}
+
+/// Builds the feed selected by the config's `PRICE_FEED`, starting its background task if it
+/// has one. Must be called from within a tokio runtime.
+pub fn price_feed_from_config(config: &AmmAuctionConfig) -> eyre::Result<Arc<dyn PriceFeed>> {
+    let max_age = config
+        .price_feed_max_age_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_MAX_AGE);
+    let source = || {
+        config
+            .price_feed_source
+            .clone()
+            .context("PRICE_FEED_SOURCE not set")
+    };
+
+    Ok(match config.price_feed {
+        PriceFeedKind::Static => Arc::new(StaticPriceFeed::new(
+            config
+                .bid_strategy_reference_price
+                .context("BID_STRATEGY_REFERENCE_PRICE not set for static price feed")?,
+        )),
+        PriceFeedKind::Replay => Arc::new(ReplayPriceFeed::from_file(source()?, max_age)?),
+        PriceFeedKind::Http => {
+            let poll_interval = config
+                .price_feed_poll_interval_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_POLL_INTERVAL);
+            Arc::new(TickerPriceFeed::http(&source()?, poll_interval, max_age)?)
+        }
+        PriceFeedKind::Websocket => Arc::new(TickerPriceFeed::websocket(&source()?, max_age)?),
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+/// A fixed price that never goes stale.
+pub struct StaticPriceFeed {
+    price: f64,
+}
+
+impl StaticPriceFeed {
+    pub fn new(price: f64) -> Self {
+        Self { price }
+    }
+}
+
+impl PriceFeed for StaticPriceFeed {
+    fn source(&self) -> String {
+        "static".to_string()
+    }
+
+    fn latest(&self) -> Option<PriceQuote> {
+        Some(PriceQuote {
+            price: self.price,
+            timestamp: unix_now(),
+        })
+    }
+
+    fn max_age(&self) -> Duration {
+        Duration::MAX
+    }
+}
+
+/// Replays recorded quotes in real time, starting from the first one when the feed is made.
+///
+/// Quotes are read from a CSV file of `timestamp,price` rows (an optional header is skipped)
+/// or, for `.jsonl` files, from ticker objects one per line. Replayed quotes are shifted to
+/// the current time, so after the last quote the feed goes stale.
+pub struct ReplayPriceFeed {
+    source: String,
+    quotes: Vec<PriceQuote>,
+    started: Instant,
+    started_at: u64,
+    max_age: Duration,
+}
+
+impl ReplayPriceFeed {
+    pub fn new(
+        source: String,
+        mut quotes: Vec<PriceQuote>,
+        max_age: Duration,
+    ) -> eyre::Result<Self> {
+        eyre::ensure!(!quotes.is_empty(), "no quotes to replay in {}", source);
+        quotes.sort_by_key(|quote| quote.timestamp);
+        Ok(Self {
+            source,
+            quotes,
+            started: Instant::now(),
+            started_at: unix_now(),
+            max_age,
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>, max_age: Duration) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let contents =
+            fs::read_to_string(path).wrap_err(format!("failed to read {}", path.display()))?;
+        let is_jsonl = path
+            .extension()
+            .is_some_and(|extension| extension == "jsonl");
+
+        let mut quotes = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let quote = if is_jsonl {
+                serde_json::from_str::<Ticker>(line)
+                    .ok()
+                    .filter(|ticker| ticker.timestamp.is_some())
+                    .and_then(Ticker::quote)
+            } else {
+                parse_csv_row(line)
+            };
+            match quote {
+                Some(quote) => quotes.push(quote),
+                // csv header
+                None if index == 0 && !is_jsonl => continue,
+                None => eyre::bail!(
+                    "malformed quote on line {} of {}",
+                    index + 1,
+                    path.display()
+                ),
+            }
+        }
+
+        Self::new(path.display().to_string(), quotes, max_age)
+    }
+}
+
+fn parse_csv_row(line: &str) -> Option<PriceQuote> {
+    let (timestamp, price) = line.split_once(',')?;
+    Some(PriceQuote {
+        price: price.trim().parse().ok()?,
+        timestamp: timestamp.trim().parse().ok()?,
+    })
+}
+
+impl PriceFeed for ReplayPriceFeed {
+    fn source(&self) -> String {
+        format!("replay of {}", self.source)
+    }
+
+    fn latest(&self) -> Option<PriceQuote> {
+        let first = self.quotes[0].timestamp;
+        let replay_time = first + self.started.elapsed().as_secs();
+        let quote = self
+            .quotes
+            .iter()
+            .take_while(|quote| quote.timestamp <= replay_time)
+            .last()?;
+        Some(PriceQuote {
+            price: quote.price,
+            timestamp: self.started_at + (quote.timestamp - first),
+        })
+    }
+
+    fn max_age(&self) -> Duration {
+        self.max_age
+    }
+}
+
+/// Ticker message schema of HTTP and websocket feeds, `{"price": 1.5, "timestamp": 1700000000}`.
+///
+/// `timestamp` is optional and defaults to when the message was received.
+#[derive(Debug, Deserialize)]
+struct Ticker {
+    price: f64,
+    timestamp: Option<u64>,
+}
+
+impl Ticker {
+    fn quote(self) -> Option<PriceQuote> {
+        (self.price.is_finite() && self.price > 0.0).then(|| PriceQuote {
+            price: self.price,
+            timestamp: self.timestamp.unwrap_or_else(unix_now),
+        })
+    }
+}
+
+/// Latest quote of a ticker polled over HTTP or streamed over a websocket.
+///
+/// The connection runs in a background task that's stopped when the feed is dropped.
+pub struct TickerPriceFeed {
+    source: String,
+    latest: Arc<RwLock<Option<PriceQuote>>>,
+    max_age: Duration,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl TickerPriceFeed {
+    /// Polls `url` for a ticker every `poll_interval`.
+    pub fn http(url: &str, poll_interval: Duration, max_age: Duration) -> eyre::Result<Self> {
+        let url = url::Url::parse(url).context("failed to parse price feed url")?;
+        let latest = Arc::new(RwLock::new(None));
+
+        let task_latest = latest.clone();
+        let task_url = url.clone();
+        let task = tokio::spawn(async move {
+            let client = ReqwestClient::new();
+            loop {
+                match fetch_ticker(&client, task_url.clone()).await {
+                    Ok(quote) => *task_latest.write().expect("price lock poisoned") = Some(quote),
+                    Err(e) => println!("price feed {}: {:#}", task_url, e),
+                }
+                sleep(poll_interval).await;
+            }
+        });
+
+        Ok(Self {
+            source: url.to_string(),
+            latest,
+            max_age,
+            task,
+        })
+    }
+
+    /// Reads a ticker from every text message `url` sends, reconnecting if it drops.
+    pub fn websocket(url: &str, max_age: Duration) -> eyre::Result<Self> {
+        Self::websocket_with_reconnect_delay(url, max_age, RECONNECT_DELAY)
+    }
+
+    /// `websocket()` waiting `reconnect_delay` before reconnecting.
+    pub fn websocket_with_reconnect_delay(
+        url: &str,
+        max_age: Duration,
+        reconnect_delay: Duration,
+    ) -> eyre::Result<Self> {
+        let url = url::Url::parse(url).context("failed to parse price feed url")?;
+        let latest = Arc::new(RwLock::new(None));
+
+        let task_latest = latest.clone();
+        let task_url = url.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match connect_async(task_url.clone()).await {
+                    Ok((mut ws_stream, _)) => {
+                        while let Some(message) = ws_stream.next().await {
+                            match message {
+                                Ok(Message::Text(text)) => {
+                                    match serde_json::from_str::<Ticker>(&text)
+                                        .ok()
+                                        .and_then(Ticker::quote)
+                                    {
+                                        Some(quote) => {
+                                            *task_latest.write().expect("price lock poisoned") =
+                                                Some(quote)
+                                        }
+                                        None => println!(
+                                            "price feed {}: ignoring message {}",
+                                            task_url, text
+                                        ),
+                                    }
+                                }
+                                Ok(_) => (),
+                                Err(e) => {
+                                    println!("price feed {}: {}", task_url, e);
+                                    break;
+                                }
+                            }
+                        }
+                        println!("price feed {}: disconnected", task_url);
+                    }
+                    Err(e) => println!("price feed {}: failed to connect: {}", task_url, e),
+                }
+                sleep(reconnect_delay).await;
+            }
+        });
+
+        Ok(Self {
+            source: url.to_string(),
+            latest,
+            max_age,
+            task,
+        })
+    }
+}
+
+async fn fetch_ticker(client: &ReqwestClient, url: url::Url) -> eyre::Result<PriceQuote> {
+    let ticker: Ticker = client
+        .get(url)
+        .send()
+        .await
+        .context("failed to request ticker")?
+        .error_for_status()
+        .context("ticker request failed")?
+        .json()
+        .await
+        .context("failed to parse ticker")?;
+    ticker.quote().context("ticker price is not positive")
+}
+
+impl PriceFeed for TickerPriceFeed {
+    fn source(&self) -> String {
+        self.source.clone()
+    }
+
+    fn latest(&self) -> Option<PriceQuote> {
+        *self.latest.read().expect("price lock poisoned")
+    }
+
+    fn max_age(&self) -> Duration {
+        self.max_age
+    }
+}
+
+impl Drop for TickerPriceFeed {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ticker_server::MockTicker;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// Writes a replay file named `name` to the temp dir.
+    fn replay_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Pretends the feed was made `secs` ago.
+    fn rewind(feed: &mut ReplayPriceFeed, secs: u64) {
+        feed.started = Instant::now()
+            .checked_sub(Duration::from_secs(secs))
+            .unwrap();
+        feed.started_at = unix_now() - secs;
+    }
+
+    #[test]
+    fn replay_reads_csv_with_or_without_header() {
+        let rows = "1714000012,1.012\n1714000000,1.000\n\n1714000024, 1.025\n";
+        let with_header = replay_file("header.csv", &format!("timestamp,price\n{}", rows));
+        let without_header = replay_file("no-header.csv", rows);
+
+        for path in [with_header, without_header] {
+            let feed = ReplayPriceFeed::from_file(&path, DEFAULT_MAX_AGE).unwrap();
+            // sorted by timestamp
+            assert_eq!(
+                feed.quotes,
+                vec![
+                    PriceQuote {
+                        price: 1.0,
+                        timestamp: 1714000000
+                    },
+                    PriceQuote {
+                        price: 1.012,
+                        timestamp: 1714000012
+                    },
+                    PriceQuote {
+                        price: 1.025,
+                        timestamp: 1714000024
+                    },
+                ]
+            );
+            assert_eq!(feed.latest().unwrap().price, 1.0);
+            fs::remove_file(path).unwrap();
+        }
+
+        let sample = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../solidity_code/sample_prices.csv"
+        );
+        let feed = ReplayPriceFeed::from_file(sample, DEFAULT_MAX_AGE).unwrap();
+        assert_eq!(feed.quotes.len(), 6);
+        assert_eq!(feed.quotes[0].timestamp, 1714000000);
+    }
+
+    #[test]
+    fn replay_reads_jsonl() {
+        let path = replay_file(
+            "tickers.jsonl",
+            "{\"price\": 1.5, \"timestamp\": 100}\n\n{\"price\": 2.0, \"timestamp\": 110}\n",
+        );
+        let feed = ReplayPriceFeed::from_file(&path, DEFAULT_MAX_AGE).unwrap();
+        assert_eq!(
+            feed.quotes,
+            vec![
+                PriceQuote {
+                    price: 1.5,
+                    timestamp: 100
+                },
+                PriceQuote {
+                    price: 2.0,
+                    timestamp: 110
+                },
+            ]
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn replay_rejects_malformed_lines() {
+        let cases = [
+            ("bad-row.csv", "100,1.5\n110,1.6\n120\n", "line 3"),
+            ("bad-price.csv", "100,1.5\n110,abc\n", "line 2"),
+            // only the first csv line can be a header
+            ("late-header.csv", "100,1.5\ntimestamp,price\n", "line 2"),
+            // jsonl files have no header
+            (
+                "header.jsonl",
+                "timestamp,price\n{\"price\": 1.5, \"timestamp\": 100}\n",
+                "line 1",
+            ),
+            // replayed tickers need their timestamp
+            ("no-timestamp.jsonl", "{\"price\": 1.5}\n", "line 1"),
+            (
+                "zero-price.jsonl",
+                "{\"price\": 0.0, \"timestamp\": 100}\n",
+                "line 1",
+            ),
+            ("empty.csv", "timestamp,price\n", "no quotes"),
+        ];
+        for (name, contents, error) in cases {
+            let path = replay_file(name, contents);
+            let e = ReplayPriceFeed::from_file(&path, DEFAULT_MAX_AGE)
+                .err()
+                .unwrap_or_else(|| panic!("{} was read", name));
+            assert!(
+                e.to_string().contains(error),
+                "{}: unexpected error {}",
+                name,
+                e
+            );
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn replay_goes_stale_after_last_quote() {
+        let quotes = [(1000, 1.0), (1010, 1.1), (1020, 1.2)]
+            .map(|(timestamp, price)| PriceQuote { price, timestamp })
+            .to_vec();
+        let mut feed =
+            ReplayPriceFeed::new("test".to_string(), quotes, Duration::from_secs(30)).unwrap();
+
+        rewind(&mut feed, 15);
+        let reading = feed.reading().unwrap();
+        assert_eq!(reading.quote.price, 1.1);
+        assert_eq!(reading.quote.timestamp, feed.started_at + 10);
+        assert!(!reading.stale);
+
+        // the last quote keeps being served, aging past max age
+        rewind(&mut feed, 100);
+        let reading = feed.reading().unwrap();
+        assert_eq!(reading.quote.price, 1.2);
+        assert_eq!(reading.quote.timestamp, feed.started_at + 20);
+        assert!(reading.stale);
+    }
+
+    /// Waits up to 5s for the feed's latest price to be `price`.
+    async fn wait_for_price(feed: &TickerPriceFeed, price: f64) {
+        for _ in 0..100 {
+            if feed.latest().is_some_and(|quote| quote.price == price) {
+                return;
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+        panic!(
+            "feed never read price {}, latest {:?}",
+            price,
+            feed.latest()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_feed_polls_ticker() {
+        let ticker = MockTicker::spawn().await.unwrap();
+        ticker.set_price(1.5, None);
+        let feed =
+            TickerPriceFeed::http(&ticker.http_url(), POLL_INTERVAL, DEFAULT_MAX_AGE).unwrap();
+
+        wait_for_price(&feed, 1.5).await;
+        ticker.set_price(2.0, None);
+        wait_for_price(&feed, 2.0).await;
+        assert!(ticker.requests() >= 2);
+        assert!(!feed.reading().unwrap().stale);
+    }
+
+    #[tokio::test]
+    async fn http_feed_keeps_last_quote_while_ticker_is_down() {
+        let ticker = MockTicker::spawn().await.unwrap();
+        let feed =
+            TickerPriceFeed::http(&ticker.http_url(), POLL_INTERVAL, DEFAULT_MAX_AGE).unwrap();
+
+        // no price yet, the ticker answers 503
+        sleep(POLL_INTERVAL * 3).await;
+        assert!(ticker.requests() >= 1);
+        assert_eq!(feed.latest(), None);
+
+        ticker.set_price(1.5, None);
+        wait_for_price(&feed, 1.5).await;
+        // a non-positive price is rejected and the last good one kept
+        ticker.set_price(0.0, None);
+        sleep(POLL_INTERVAL * 3).await;
+        assert_eq!(feed.latest().unwrap().price, 1.5);
+    }
+
+    #[tokio::test]
+    async fn reading_is_stale_past_max_age() {
+        let ticker = MockTicker::spawn().await.unwrap();
+        let max_age = Duration::from_secs(30);
+        ticker.set_price(1.5, Some(unix_now() - 60));
+        let feed = TickerPriceFeed::http(&ticker.http_url(), POLL_INTERVAL, max_age).unwrap();
+
+        wait_for_price(&feed, 1.5).await;
+        let reading = feed.reading().unwrap();
+        assert!(reading.stale);
+        assert!(reading.age >= Duration::from_secs(60));
+
+        ticker.set_price(2.0, Some(unix_now() - 10));
+        wait_for_price(&feed, 2.0).await;
+        assert!(!feed.reading().unwrap().stale);
+    }
+
+    #[tokio::test]
+    async fn websocket_feed_streams_ticker() {
+        let ticker = MockTicker::spawn().await.unwrap();
+        ticker.set_price(1.5, None);
+        let feed = TickerPriceFeed::websocket(&ticker.ws_url(), DEFAULT_MAX_AGE).unwrap();
+
+        wait_for_price(&feed, 1.5).await;
+        ticker.set_price(2.5, None);
+        wait_for_price(&feed, 2.5).await;
+        assert_eq!(ticker.connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn websocket_feed_reconnects() {
+        let ticker = MockTicker::spawn().await.unwrap();
+        ticker.set_price(1.5, None);
+        let feed = TickerPriceFeed::websocket_with_reconnect_delay(
+            &ticker.ws_url(),
+            DEFAULT_MAX_AGE,
+            POLL_INTERVAL,
+        )
+        .unwrap();
+        wait_for_price(&feed, 1.5).await;
+
+        ticker.drop_connections();
+        for _ in 0..100 {
+            if ticker.connections() == 2 {
+                break;
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+        assert_eq!(ticker.connections(), 2);
+        ticker.set_price(3.0, None);
+        wait_for_price(&feed, 3.0).await;
+    }
+}
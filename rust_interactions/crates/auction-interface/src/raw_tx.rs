@@ -0,0 +1,81 @@
+use alloy::{
+    consensus::{Transaction, TxEnvelope},
+    eips::eip2718::Decodable2718,
+};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use color_eyre::{eyre, eyre::Context};
+use serde::Serialize;
+
+/// A signed L1 transaction decoded from its EIP-2718 encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RawTransaction {
+    pub hash: B256,
+    /// 0 for legacy transactions
+    pub tx_type: u8,
+    pub from: Address,
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+}
+
+impl RawTransaction {
+    /// Decodes a legacy or typed transaction and recovers its sender.
+    pub fn decode(raw: &[u8]) -> eyre::Result<Self> {
+        let mut encoded = raw;
+        let tx = TxEnvelope::decode_2718(&mut encoded)
+            .map_err(|e| eyre::eyre!("malformed transaction: {}", e))?;
+        eyre::ensure!(
+            encoded.is_empty(),
+            "{} bytes past the end of the transaction",
+            encoded.len()
+        );
+        let from = tx
+            .recover_signer()
+            .map_err(|e| eyre::eyre!("failed to recover sender: {}", e))?;
+
+        Ok(Self {
+            hash: *tx.tx_hash(),
+            tx_type: tx.tx_type() as u8,
+            from,
+            chain_id: tx.chain_id(),
+            nonce: tx.nonce(),
+            gas_limit: u64::try_from(tx.gas_limit()).context("gas limit does not fit in u64")?,
+            to: tx.to().to().copied(),
+            value: tx.value(),
+            input: Bytes::copy_from_slice(tx.input()),
+        })
+    }
+}
+
+/// An item of an rlp list, `raw` including its header.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RlpItem<'a> {
+    pub(crate) list: bool,
+    pub(crate) raw: &'a [u8],
+    pub(crate) payload: &'a [u8],
+}
+
+/// Splits concatenated rlp items.
+pub(crate) fn rlp_items(mut encoded: &[u8]) -> eyre::Result<Vec<RlpItem<'_>>> {
+    let mut items = Vec::new();
+    while !encoded.is_empty() {
+        let start = encoded;
+        let header = alloy_rlp::Header::decode(&mut encoded)
+            .map_err(|e| eyre::eyre!("malformed rlp: {}", e))?;
+        let header_len = start.len() - encoded.len();
+        eyre::ensure!(
+            encoded.len() >= header.payload_length,
+            "rlp item longer than its input"
+        );
+        items.push(RlpItem {
+            list: header.list,
+            raw: &start[..header_len + header.payload_length],
+            payload: &encoded[..header.payload_length],
+        });
+        encoded = &encoded[header.payload_length..];
+    }
+    Ok(items)
+}
@@ -0,0 +1,339 @@
+use std::{fmt, net::SocketAddr, sync::Arc};
+
+use async_trait::async_trait;
+use color_eyre::{eyre, eyre::Context};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// Largest request head read before the connection is dropped.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn method_not_found(method: &str) -> Self {
+        Self {
+            code: -32601,
+            message: format!("the method {} does not exist/is not available", method),
+            data: None,
+        }
+    }
+
+    pub fn invalid_params(message: impl fmt::Display) -> Self {
+        Self {
+            code: -32602,
+            message: format!("invalid params: {}", message),
+            data: None,
+        }
+    }
+
+    /// A server-side failure, how nodes report rejected transactions and reverts.
+    pub fn server(message: impl fmt::Display) -> Self {
+        Self {
+            code: -32000,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+/// Answers the calls of a `RpcServer`.
+#[async_trait]
+pub trait RpcHandler: Send + Sync + 'static {
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError>;
+}
+
+/// A minimal JSON-RPC over HTTP server for local mocks of the nodes the crates talk to.
+///
+/// Every POST body is handled as a single or batched JSON-RPC request, the path and headers
+/// other than `Content-Length` are ignored. The server stops when it's dropped.
+pub struct RpcServer {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl RpcServer {
+    /// Serves `handler` on `addr`, pass port 0 to pick a free port.
+    pub async fn spawn(addr: &str, handler: Arc<dyn RpcHandler>) -> eyre::Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .context(format!("failed to bind rpc server to {}", addr))?;
+        let addr = listener
+            .local_addr()
+            .context("failed to get rpc server address")?;
+
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        println!("rpc server {}: failed to accept connection: {}", addr, e);
+                        continue;
+                    }
+                };
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_connection(stream, handler).await {
+                        println!("rpc server {}: {:#}", addr, e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, task })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// `http://` url of the server.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for RpcServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Handles requests on a kept-alive connection until the client closes it.
+async fn serve_connection(mut stream: TcpStream, handler: Arc<dyn RpcHandler>) -> eyre::Result<()> {
+    let mut buffer = Vec::new();
+    while let Some(body) = read_request(&mut stream, &mut buffer).await? {
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(Value::Array(requests)) => {
+                let mut responses = Vec::with_capacity(requests.len());
+                for request in requests {
+                    responses.push(handle_request(handler.as_ref(), request).await);
+                }
+                Value::Array(responses)
+            }
+            Ok(request) => handle_request(handler.as_ref(), request).await,
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "error": { "code": -32700, "message": format!("parse error: {}", e) },
+            }),
+        };
+        let response = serde_json::to_vec(&response).context("failed to serialize response")?;
+        let head = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+            response.len()
+        );
+        stream
+            .write_all(head.as_bytes())
+            .await
+            .context("failed to write response")?;
+        stream
+            .write_all(&response)
+            .await
+            .context("failed to write response")?;
+    }
+    Ok(())
+}
+
+/// Reads the next HTTP request on `stream` and returns its body, `None` if the client closed
+/// the connection before sending one.
+///
+/// `buffer` holds what was read past the request, it's kept between calls so pipelined
+/// requests on a kept-alive connection aren't lost.
+pub(crate) async fn read_request(
+    stream: &mut TcpStream,
+    buffer: &mut Vec<u8>,
+) -> eyre::Result<Option<Vec<u8>>> {
+    // read the request head
+    let head_end = loop {
+        if let Some(end) = find_head_end(buffer) {
+            break end;
+        }
+        eyre::ensure!(buffer.len() <= MAX_HEAD_BYTES, "request head too large");
+        let mut chunk = [0u8; 4096];
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .context("failed to read request")?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse::<usize>())
+        .transpose()
+        .context("malformed content length")?
+        .unwrap_or(0);
+
+    // read the body
+    let body_start = head_end + 4;
+    while buffer.len() < body_start + content_length {
+        let mut chunk = [0u8; 4096];
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .context("failed to read request body")?;
+        eyre::ensure!(read != 0, "connection closed mid request");
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body = buffer[body_start..body_start + content_length].to_vec();
+    buffer.drain(..body_start + content_length);
+    Ok(Some(body))
+}
+
+fn find_head_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|window| window == b"\r\n\r\n")
+}
+
+async fn handle_request(handler: &dyn RpcHandler, request: Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32600, "message": "invalid request" },
+        });
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    match handler.handle(method, params).await {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+/// Positional param `index` of a JSON-RPC request.
+pub fn param<T: serde::de::DeserializeOwned>(params: &Value, index: usize) -> Result<T, RpcError> {
+    let value = params
+        .get(index)
+        .cloned()
+        .ok_or_else(|| RpcError::invalid_params(format!("missing param {}", index)))?;
+    serde_json::from_value(value)
+        .map_err(|e| RpcError::invalid_params(format!("param {}: {}", index, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Client as ReqwestClient;
+
+    use super::*;
+
+    /// Echoes `echo`'s params back and errors on anything else.
+    struct Echo;
+
+    #[async_trait]
+    impl RpcHandler for Echo {
+        async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+            match method {
+                "echo" => Ok(params),
+                method => Err(RpcError::method_not_found(method)),
+            }
+        }
+    }
+
+    async fn post(client: &ReqwestClient, server: &RpcServer, body: Value) -> Value {
+        client
+            .post(server.url())
+            .json(&body)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let server = RpcServer::spawn("127.0.0.1:0", Arc::new(Echo))
+            .await
+            .unwrap();
+        let client = ReqwestClient::new();
+
+        // both requests go over the same kept-alive connection
+        for id in 1..=2 {
+            let response = post(
+                &client,
+                &server,
+                json!({ "jsonrpc": "2.0", "id": id, "method": "echo", "params": ["0x01", id] }),
+            )
+            .await;
+            assert_eq!(
+                response,
+                json!({ "jsonrpc": "2.0", "id": id, "result": ["0x01", id] })
+            );
+        }
+
+        let response = post(
+            &client,
+            &server,
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "eth_chainId", "params": [] }),
+        )
+        .await;
+        assert_eq!(response["id"], json!(3));
+        assert_eq!(response["error"]["code"], json!(-32601));
+        assert!(response.get("result").is_none());
+    }
+
+    #[tokio::test]
+    async fn batch_request() {
+        let server = RpcServer::spawn("127.0.0.1:0", Arc::new(Echo))
+            .await
+            .unwrap();
+        let response = post(
+            &ReqwestClient::new(),
+            &server,
+            json!([
+                { "jsonrpc": "2.0", "id": 1, "method": "echo", "params": [1] },
+                { "jsonrpc": "2.0", "id": 2, "method": "missing", "params": [] },
+                { "jsonrpc": "2.0", "id": 3 },
+                { "jsonrpc": "2.0", "id": "four", "method": "echo", "params": [4] },
+            ]),
+        )
+        .await;
+
+        // answered in order, each with its own id
+        let responses = response.as_array().unwrap();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(
+            responses[0],
+            json!({ "jsonrpc": "2.0", "id": 1, "result": [1] })
+        );
+        assert_eq!(responses[1]["id"], json!(2));
+        assert_eq!(responses[1]["error"]["code"], json!(-32601));
+        assert_eq!(responses[2]["id"], json!(3));
+        assert_eq!(responses[2]["error"]["code"], json!(-32600));
+        assert_eq!(
+            responses[3],
+            json!({ "jsonrpc": "2.0", "id": "four", "result": [4] })
+        );
+    }
+}
@@ -0,0 +1,347 @@
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolCall;
+use color_eyre::eyre;
+
+use crate::amm_auction::ISwapRouter;
+
+/// Fee tier used when none is configured, 0.3%
+pub const DEFAULT_FEE_TIER: u32 = 3000;
+/// Seconds past the target block's timestamp a swap stays valid when none is configured
+pub const DEFAULT_DEADLINE_OFFSET_SECS: u64 = 120;
+
+/// Which side of a swap is fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapAmount {
+    /// Swap exactly `amount_in`, reverting if less than `amount_out_minimum` comes out
+    ExactInput {
+        amount_in: U256,
+        amount_out_minimum: U256,
+    },
+    /// Receive exactly `amount_out`, reverting if more than `amount_in_maximum` goes in
+    ExactOutput {
+        amount_out: U256,
+        amount_in_maximum: U256,
+    },
+}
+
+/// A swap through one or more pools.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Swap {
+    /// Tokens in the order they're swapped through, starting with the token going in
+    pub path: Vec<Address>,
+    /// Fee tier of each hop's pool, the configured fee tier is used for every hop if empty
+    pub fees: Vec<u32>,
+    pub amount: SwapAmount,
+    /// Price limit for single pool swaps, 0 for no limit
+    pub sqrt_price_limit_x96: U256,
+}
+
+impl Swap {
+    pub fn exact_input_single(
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        amount_out_minimum: U256,
+    ) -> Self {
+        Self::exact_input(vec![token_in, token_out], amount_in, amount_out_minimum)
+    }
+
+    pub fn exact_output_single(
+        token_in: Address,
+        token_out: Address,
+        amount_out: U256,
+        amount_in_maximum: U256,
+    ) -> Self {
+        Self::exact_output(vec![token_in, token_out], amount_out, amount_in_maximum)
+    }
+
+    pub fn exact_input(path: Vec<Address>, amount_in: U256, amount_out_minimum: U256) -> Self {
+        Self {
+            path,
+            fees: Vec::new(),
+            amount: SwapAmount::ExactInput {
+                amount_in,
+                amount_out_minimum,
+            },
+            sqrt_price_limit_x96: U256::ZERO,
+        }
+    }
+
+    pub fn exact_output(path: Vec<Address>, amount_out: U256, amount_in_maximum: U256) -> Self {
+        Self {
+            path,
+            fees: Vec::new(),
+            amount: SwapAmount::ExactOutput {
+                amount_out,
+                amount_in_maximum,
+            },
+            sqrt_price_limit_x96: U256::ZERO,
+        }
+    }
+
+    /// Sets the fee tier of each hop's pool.
+    pub fn with_fees(mut self, fees: Vec<u32>) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Sets the price limit, only valid for single pool swaps.
+    pub fn with_sqrt_price_limit_x96(mut self, sqrt_price_limit_x96: U256) -> Self {
+        self.sqrt_price_limit_x96 = sqrt_price_limit_x96;
+        self
+    }
+
+    /// Number of pools the swap goes through.
+    pub fn hops(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+}
+
+/// Encodes swaps as Uniswap V3 `SwapRouter` calls.
+#[derive(Debug, Clone)]
+pub struct SwapBuilder {
+    /// Fee tier used for hops without an explicit one
+    fee_tier: u32,
+    /// Seconds past the target block's timestamp the swap stays valid
+    deadline_offset_secs: u64,
+}
+
+impl Default for SwapBuilder {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEE_TIER, DEFAULT_DEADLINE_OFFSET_SECS)
+    }
+}
+
+impl SwapBuilder {
+    pub fn new(fee_tier: u32, deadline_offset_secs: u64) -> Self {
+        Self {
+            fee_tier,
+            deadline_offset_secs,
+        }
+    }
+
+    /// Deadline for a swap meant to land in the block with `target_block_timestamp`.
+    pub fn deadline(&self, target_block_timestamp: u64) -> U256 {
+        U256::from(target_block_timestamp.saturating_add(self.deadline_offset_secs))
+    }
+
+    /// Encodes the router call for `swap`, sending the output to `recipient`.
+    ///
+    /// Single pool swaps use `exactInputSingle`/`exactOutputSingle`, longer paths use
+    /// `exactInput`/`exactOutput`.
+    pub fn calldata(
+        &self,
+        swap: &Swap,
+        recipient: Address,
+        target_block_timestamp: u64,
+    ) -> eyre::Result<Vec<u8>> {
+        eyre::ensure!(swap.path.len() >= 2, "swap path needs at least two tokens");
+        let fees = if swap.fees.is_empty() {
+            vec![self.fee_tier; swap.hops()]
+        } else {
+            swap.fees.clone()
+        };
+        eyre::ensure!(
+            fees.len() == swap.hops(),
+            "swap path has {} hops but {} fee tiers were given",
+            swap.hops(),
+            fees.len()
+        );
+        let deadline = self.deadline(target_block_timestamp);
+
+        if swap.hops() == 1 {
+            eyre::ensure!(
+                swap.sqrt_price_limit_x96.bit_len() <= 160,
+                "sqrtPriceLimitX96 does not fit in uint160"
+            );
+            let sqrt_price_limit_x96 = swap.sqrt_price_limit_x96;
+            let fee = fee_tier(fees[0])?;
+            let calldata = match swap.amount {
+                SwapAmount::ExactInput {
+                    amount_in,
+                    amount_out_minimum,
+                } => ISwapRouter::exactInputSingleCall {
+                    params: ISwapRouter::ExactInputSingleParams {
+                        tokenIn: swap.path[0],
+                        tokenOut: swap.path[1],
+                        fee,
+                        recipient,
+                        deadline,
+                        amountIn: amount_in,
+                        amountOutMinimum: amount_out_minimum,
+                        sqrtPriceLimitX96: sqrt_price_limit_x96,
+                    },
+                }
+                .abi_encode(),
+                SwapAmount::ExactOutput {
+                    amount_out,
+                    amount_in_maximum,
+                } => ISwapRouter::exactOutputSingleCall {
+                    params: ISwapRouter::ExactOutputSingleParams {
+                        tokenIn: swap.path[0],
+                        tokenOut: swap.path[1],
+                        fee,
+                        recipient,
+                        deadline,
+                        amountOut: amount_out,
+                        amountInMaximum: amount_in_maximum,
+                        sqrtPriceLimitX96: sqrt_price_limit_x96,
+                    },
+                }
+                .abi_encode(),
+            };
+            return Ok(calldata);
+        }
+
+        // the router only takes a price limit for single pool swaps
+        eyre::ensure!(
+            swap.sqrt_price_limit_x96.is_zero(),
+            "sqrtPriceLimitX96 is only supported for single pool swaps"
+        );
+        let calldata = match swap.amount {
+            SwapAmount::ExactInput {
+                amount_in,
+                amount_out_minimum,
+            } => ISwapRouter::exactInputCall {
+                params: ISwapRouter::ExactInputParams {
+                    path: encode_path(&swap.path, &fees)?.into(),
+                    recipient,
+                    deadline,
+                    amountIn: amount_in,
+                    amountOutMinimum: amount_out_minimum,
+                },
+            }
+            .abi_encode(),
+            SwapAmount::ExactOutput {
+                amount_out,
+                amount_in_maximum,
+            } => {
+                // exact output paths are encoded starting from the output token
+                let path: Vec<Address> = swap.path.iter().rev().copied().collect();
+                let fees: Vec<u32> = fees.iter().rev().copied().collect();
+                ISwapRouter::exactOutputCall {
+                    params: ISwapRouter::ExactOutputParams {
+                        path: encode_path(&path, &fees)?.into(),
+                        recipient,
+                        deadline,
+                        amountOut: amount_out,
+                        amountInMaximum: amount_in_maximum,
+                    },
+                }
+                .abi_encode()
+            }
+        };
+        Ok(calldata)
+    }
+}
+
+fn fee_tier(fee: u32) -> eyre::Result<u32> {
+    eyre::ensure!(fee < 1 << 24, "fee tier {} does not fit in uint24", fee);
+    Ok(fee)
+}
+
+/// Packs a multi-hop path as `token (fee token)*` with 3 byte fees.
+fn encode_path(tokens: &[Address], fees: &[u32]) -> eyre::Result<Vec<u8>> {
+    let mut path = Vec::with_capacity(tokens.len() * 20 + fees.len() * 3);
+    path.extend_from_slice(tokens[0].as_slice());
+    for (fee, token) in fees.iter().zip(&tokens[1..]) {
+        path.extend_from_slice(&fee_tier(*fee)?.to_be_bytes()[1..]);
+        path.extend_from_slice(token.as_slice());
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::address;
+    use hex_literal::hex;
+
+    use super::*;
+
+    const DAI: Address = address!("6B175474E89094C44Da98b954EedeAC495271d0F");
+    const USDC: Address = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+    const WETH: Address = address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+    const RECIPIENT: Address = Address::repeat_byte(0x11);
+
+    /// `abi.encodePacked(DAI, uint24(3000), USDC, uint24(500), WETH)`, the router's
+    /// DAI -> USDC -> WETH path.
+    const DAI_USDC_WETH: [u8; 66] = hex!(
+        "6b175474e89094c44da98b954eedeac495271d0f000bb8a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        "0001f4c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+    );
+    /// The same pools from WETH back to DAI, how an exact output DAI -> WETH swap is encoded.
+    const WETH_USDC_DAI: [u8; 66] = hex!(
+        "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20001f4a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        "000bb86b175474e89094c44da98b954eedeac495271d0f"
+    );
+
+    #[test]
+    fn multi_hop_path_is_packed() {
+        assert_eq!(
+            encode_path(&[DAI, USDC, WETH], &[3000, 500]).unwrap(),
+            DAI_USDC_WETH
+        );
+        assert!(encode_path(&[DAI, USDC], &[1 << 24]).is_err());
+    }
+
+    #[test]
+    fn exact_input_encodes_path_in_swap_order() {
+        let swap = Swap::exact_input(vec![DAI, USDC, WETH], U256::from(1000), U256::from(1))
+            .with_fees(vec![3000, 500]);
+        let calldata = SwapBuilder::default()
+            .calldata(&swap, RECIPIENT, 1_700_000_000)
+            .unwrap();
+
+        let params = ISwapRouter::exactInputCall::abi_decode(&calldata, true)
+            .unwrap()
+            .params;
+        assert_eq!(&params.path[..], DAI_USDC_WETH);
+        assert_eq!(params.recipient, RECIPIENT);
+        assert_eq!(params.deadline, U256::from(1_700_000_120));
+        assert_eq!(params.amountIn, U256::from(1000));
+        assert_eq!(params.amountOutMinimum, U256::from(1));
+    }
+
+    #[test]
+    fn exact_output_encodes_path_reversed() {
+        let swap = Swap::exact_output(vec![DAI, USDC, WETH], U256::from(1000), U256::from(2000))
+            .with_fees(vec![3000, 500]);
+        let calldata = SwapBuilder::default()
+            .calldata(&swap, RECIPIENT, 1_700_000_000)
+            .unwrap();
+
+        let params = ISwapRouter::exactOutputCall::abi_decode(&calldata, true)
+            .unwrap()
+            .params;
+        assert_eq!(&params.path[..], WETH_USDC_DAI);
+        assert_eq!(params.amountOut, U256::from(1000));
+        assert_eq!(params.amountInMaximum, U256::from(2000));
+    }
+
+    #[test]
+    fn multi_hop_fees_default_to_the_fee_tier() {
+        let swap = Swap::exact_input(vec![DAI, USDC, WETH], U256::from(1000), U256::ZERO);
+        let calldata = SwapBuilder::new(500, DEFAULT_DEADLINE_OFFSET_SECS)
+            .calldata(&swap, RECIPIENT, 0)
+            .unwrap();
+        let params = ISwapRouter::exactInputCall::abi_decode(&calldata, true)
+            .unwrap()
+            .params;
+        assert_eq!(
+            &params.path[..],
+            encode_path(&[DAI, USDC, WETH], &[500, 500]).unwrap()
+        );
+
+        // one fee tier per hop
+        let swap = swap.with_fees(vec![3000]);
+        assert!(SwapBuilder::default()
+            .calldata(&swap, RECIPIENT, 0)
+            .is_err());
+        // no price limit through several pools
+        let swap = Swap::exact_input(vec![DAI, USDC, WETH], U256::from(1000), U256::ZERO)
+            .with_sqrt_price_limit_x96(U256::from(1));
+        assert!(SwapBuilder::default()
+            .calldata(&swap, RECIPIENT, 0)
+            .is_err());
+    }
+}
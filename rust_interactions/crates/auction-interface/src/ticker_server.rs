@@ -0,0 +1,211 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use color_eyre::{eyre, eyre::Context};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+    sync::watch,
+    task::JoinHandle,
+};
+use tokio_tungstenite::{accept_async, tungstenite::protocol::Message};
+
+use crate::rpc_server::read_request;
+
+/// Shared between the servers and their connections.
+struct TickerState {
+    /// Current ticker message, `None` until a price is set
+    ticker: watch::Sender<Option<String>>,
+    /// Bumped to close every open websocket connection
+    generation: watch::Sender<u64>,
+    requests: AtomicUsize,
+    connections: AtomicUsize,
+}
+
+/// A local stand-in for the price ticker the HTTP and websocket price feeds read, for running
+/// them without a live exchange.
+///
+/// Every HTTP request is answered with the current ticker, and every websocket connection is
+/// sent it on connecting and whenever it changes. The servers stop when it's dropped.
+pub struct MockTicker {
+    http_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    state: Arc<TickerState>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MockTicker {
+    /// Serves the ticker over HTTP and websocket on free local ports.
+    pub async fn spawn() -> eyre::Result<Self> {
+        let state = Arc::new(TickerState {
+            ticker: watch::channel(None).0,
+            generation: watch::channel(0).0,
+            requests: AtomicUsize::new(0),
+            connections: AtomicUsize::new(0),
+        });
+
+        let http_listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("failed to bind ticker http server")?;
+        let http_addr = http_listener
+            .local_addr()
+            .context("failed to get ticker http server address")?;
+        let ws_listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("failed to bind ticker websocket server")?;
+        let ws_addr = ws_listener
+            .local_addr()
+            .context("failed to get ticker websocket server address")?;
+
+        let http_state = state.clone();
+        let http_task = tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = http_listener.accept().await else {
+                    continue;
+                };
+                let state = http_state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_http(stream, &state).await {
+                        println!("ticker {}: {:#}", http_addr, e);
+                    }
+                });
+            }
+        });
+        let ws_state = state.clone();
+        let ws_task = tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = ws_listener.accept().await else {
+                    continue;
+                };
+                let state = ws_state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_websocket(stream, &state).await {
+                        println!("ticker {}: {:#}", ws_addr, e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            http_addr,
+            ws_addr,
+            state,
+            tasks: vec![http_task, ws_task],
+        })
+    }
+
+    /// `http://` url the ticker is polled at.
+    pub fn http_url(&self) -> String {
+        format!("http://{}", self.http_addr)
+    }
+
+    /// `ws://` url the ticker is streamed at.
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.ws_addr)
+    }
+
+    /// Sets the ticker's price, sent without a timestamp if `timestamp` is `None`, and pushes
+    /// it to every open websocket connection.
+    pub fn set_price(&self, price: f64, timestamp: Option<u64>) {
+        let ticker = match timestamp {
+            Some(timestamp) => json!({ "price": price, "timestamp": timestamp }),
+            None => json!({ "price": price }),
+        };
+        self.state.ticker.send_replace(Some(ticker.to_string()));
+    }
+
+    /// Closes every open websocket connection, as a dropped feed would be.
+    pub fn drop_connections(&self) {
+        self.state
+            .generation
+            .send_modify(|generation| *generation += 1);
+    }
+
+    /// Number of HTTP requests answered.
+    pub fn requests(&self) -> usize {
+        self.state.requests.load(Ordering::SeqCst)
+    }
+
+    /// Number of websocket connections accepted.
+    pub fn connections(&self) -> usize {
+        self.state.connections.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for MockTicker {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Answers one HTTP request with the current ticker, 503 if no price is set.
+async fn serve_http(mut stream: TcpStream, state: &TickerState) -> eyre::Result<()> {
+    if read_request(&mut stream, &mut Vec::new()).await?.is_none() {
+        return Ok(());
+    }
+
+    let ticker = state.ticker.borrow().clone();
+    let (status, body) = match ticker {
+        Some(ticker) => ("200 OK", ticker),
+        None => ("503 Service Unavailable", String::new()),
+    };
+    state.requests.fetch_add(1, Ordering::SeqCst);
+    let response = format!(
+        "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .context("failed to write response")
+}
+
+/// Streams the ticker to one websocket client until it leaves or connections are dropped.
+async fn serve_websocket(stream: TcpStream, state: &TickerState) -> eyre::Result<()> {
+    let mut ws_stream = accept_async(stream)
+        .await
+        .context("websocket handshake failed")?;
+    state.connections.fetch_add(1, Ordering::SeqCst);
+    let mut ticker = state.ticker.subscribe();
+    let mut generation = state.generation.subscribe();
+
+    let current = ticker.borrow_and_update().clone();
+    if let Some(message) = current {
+        ws_stream
+            .send(Message::Text(message))
+            .await
+            .context("failed to send ticker")?;
+    }
+    loop {
+        tokio::select! {
+            _ = ticker.changed() => {
+                let current = ticker.borrow_and_update().clone();
+                if let Some(message) = current {
+                    ws_stream
+                        .send(Message::Text(message))
+                        .await
+                        .context("failed to send ticker")?;
+                }
+            }
+            _ = generation.changed() => break,
+            // read what the client sends so a close is noticed
+            message = ws_stream.next() => match message {
+                Some(Ok(_)) => (),
+                Some(Err(_)) | None => return Ok(()),
+            },
+        }
+    }
+    ws_stream.close(None).await.ok();
+    Ok(())
+}
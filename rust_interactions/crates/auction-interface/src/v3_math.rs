@@ -0,0 +1,605 @@
+//! Ports of Uniswap V3's `TickMath`, `SqrtPriceMath` and `SwapMath`, and the pool's swap
+//! loop, rounding exactly like the contracts so simulated swaps match on-chain ones.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::{uint, U256, U512};
+use color_eyre::eyre;
+use eyre::ContextCompat;
+
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = 887272;
+/// `get_sqrt_ratio_at_tick(MIN_TICK)`
+pub const MIN_SQRT_RATIO: U256 = uint!(4295128739_U256);
+/// `get_sqrt_ratio_at_tick(MAX_TICK)`
+pub const MAX_SQRT_RATIO: U256 = uint!(1461446703485210103287273052203988822378723970342_U256);
+/// 2^96, prices are Q64.96
+pub const Q96: U256 = uint!(0x1000000000000000000000000_U256);
+/// Fees are in hundredths of a bip
+const FEE_DENOMINATOR: u32 = 1_000_000;
+
+/// `floor(a * b / denominator)` without intermediate overflow, `None` if the result overflows.
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let quotient = U512::from(a) * U512::from(b) / U512::from(denominator);
+    U256::checked_from(quotient)
+}
+
+/// `ceil(a * b / denominator)` without intermediate overflow, `None` if the result overflows.
+pub fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let product = U512::from(a) * U512::from(b);
+    let denominator = U512::from(denominator);
+    let mut quotient = product / denominator;
+    if !(product % denominator).is_zero() {
+        quotient += U512::from(1);
+    }
+    U256::checked_from(quotient)
+}
+
+fn div_rounding_up(a: U256, b: U256) -> U256 {
+    let quotient = a / b;
+    if (a % b).is_zero() {
+        quotient
+    } else {
+        quotient + U256::from(1)
+    }
+}
+
+/// `sqrt(1.0001^tick) * 2^96`, `TickMath.getSqrtRatioAtTick`.
+pub fn get_sqrt_ratio_at_tick(tick: i32) -> eyre::Result<U256> {
+    let abs_tick = tick.unsigned_abs();
+    eyre::ensure!(abs_tick <= MAX_TICK as u32, "tick {} out of range", tick);
+
+    // sqrt(1.0001^-(2^i)) as Q128.128 for each bit i of the tick
+    const RATIOS: [U256; 19] = [
+        uint!(0xfff97272373d413259a46990580e213a_U256),
+        uint!(0xfff2e50f5f656932ef12357cf3c7fdcc_U256),
+        uint!(0xffe5caca7e10e4e61c3624eaa0941cd0_U256),
+        uint!(0xffcb9843d60f6159c9db58835c926644_U256),
+        uint!(0xff973b41fa98c081472e6896dfb254c0_U256),
+        uint!(0xff2ea16466c96a3843ec78b326b52861_U256),
+        uint!(0xfe5dee046a99a2a811c461f1969c3053_U256),
+        uint!(0xfcbe86c7900a88aedcffc83b479aa3a4_U256),
+        uint!(0xf987a7253ac413176f2b074cf7815e54_U256),
+        uint!(0xf3392b0822b70005940c7a398e4b70f3_U256),
+        uint!(0xe7159475a2c29b7443b29c7fa6e889d9_U256),
+        uint!(0xd097f3bdfd2022b8845ad8f792aa5825_U256),
+        uint!(0xa9f746462d870fdf8a65dc1f90e061e5_U256),
+        uint!(0x70d869a156d2a1b890bb3df62baf32f7_U256),
+        uint!(0x31be135f97d08fd981231505542fcfa6_U256),
+        uint!(0x9aa508b5b7a84e1c677de54f3e99bc9_U256),
+        uint!(0x5d6af8dedb81196699c329225ee604_U256),
+        uint!(0x2216e584f5fa1ea926041bedfe98_U256),
+        uint!(0x48a170391f7dc42444e8fa2_U256),
+    ];
+
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        uint!(0xfffcb933bd6fad37aa2d162d1a594001_U256)
+    } else {
+        U256::from(1) << 128
+    };
+    for (bit, bit_ratio) in RATIOS.iter().enumerate() {
+        if abs_tick & (0x2 << bit) != 0 {
+            ratio = (ratio * *bit_ratio) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 to Q64.96, rounding up
+    let remainder = ratio % (U256::from(1) << 32);
+    Ok((ratio >> 32) + U256::from(u8::from(!remainder.is_zero())))
+}
+
+/// The greatest tick whose sqrt ratio is at most `sqrt_price_x96`,
+/// `TickMath.getTickAtSqrtRatio`.
+pub fn get_tick_at_sqrt_ratio(sqrt_price_x96: U256) -> eyre::Result<i32> {
+    eyre::ensure!(
+        sqrt_price_x96 >= MIN_SQRT_RATIO && sqrt_price_x96 < MAX_SQRT_RATIO,
+        "sqrt price {} out of range",
+        sqrt_price_x96
+    );
+    // sqrt ratios grow with the tick, so binary search for the last tick at or below the price
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if get_sqrt_ratio_at_tick(mid)? <= sqrt_price_x96 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+/// Token 0 between two prices for `liquidity`, `SqrtPriceMath.getAmount0Delta`.
+pub fn get_amount_0_delta(
+    sqrt_ratio_a_x96: U256,
+    sqrt_ratio_b_x96: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> eyre::Result<U256> {
+    let (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
+        (sqrt_ratio_b_x96, sqrt_ratio_a_x96)
+    } else {
+        (sqrt_ratio_a_x96, sqrt_ratio_b_x96)
+    };
+    eyre::ensure!(!sqrt_ratio_a_x96.is_zero(), "sqrt price is zero");
+
+    let numerator_1 = U256::from(liquidity) << 96;
+    let numerator_2 = sqrt_ratio_b_x96 - sqrt_ratio_a_x96;
+    if round_up {
+        let amount = mul_div_rounding_up(numerator_1, numerator_2, sqrt_ratio_b_x96)
+            .context("amount 0 delta overflows")?;
+        Ok(div_rounding_up(amount, sqrt_ratio_a_x96))
+    } else {
+        let amount = mul_div(numerator_1, numerator_2, sqrt_ratio_b_x96)
+            .context("amount 0 delta overflows")?;
+        Ok(amount / sqrt_ratio_a_x96)
+    }
+}
+
+/// Token 1 between two prices for `liquidity`, `SqrtPriceMath.getAmount1Delta`.
+pub fn get_amount_1_delta(
+    sqrt_ratio_a_x96: U256,
+    sqrt_ratio_b_x96: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> eyre::Result<U256> {
+    let difference = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
+        sqrt_ratio_a_x96 - sqrt_ratio_b_x96
+    } else {
+        sqrt_ratio_b_x96 - sqrt_ratio_a_x96
+    };
+    let amount = if round_up {
+        mul_div_rounding_up(U256::from(liquidity), difference, Q96)
+    } else {
+        mul_div(U256::from(liquidity), difference, Q96)
+    };
+    amount.context("amount 1 delta overflows")
+}
+
+/// Price after adding `amount_in` of the input token, `SqrtPriceMath.getNextSqrtPriceFromInput`.
+pub fn get_next_sqrt_price_from_input(
+    sqrt_price_x96: U256,
+    liquidity: u128,
+    amount_in: U256,
+    zero_for_one: bool,
+) -> eyre::Result<U256> {
+    eyre::ensure!(!sqrt_price_x96.is_zero(), "sqrt price is zero");
+    eyre::ensure!(liquidity != 0, "no liquidity");
+
+    if zero_for_one {
+        // getNextSqrtPriceFromAmount0RoundingUp, adding
+        if amount_in.is_zero() {
+            return Ok(sqrt_price_x96);
+        }
+        let numerator_1 = U256::from(liquidity) << 96;
+        if let Some(product) = amount_in.checked_mul(sqrt_price_x96) {
+            if let Some(denominator) = numerator_1.checked_add(product) {
+                return mul_div_rounding_up(numerator_1, sqrt_price_x96, denominator)
+                    .context("next sqrt price overflows");
+            }
+        }
+        let denominator = (numerator_1 / sqrt_price_x96)
+            .checked_add(amount_in)
+            .context("next sqrt price overflows")?;
+        Ok(div_rounding_up(numerator_1, denominator))
+    } else {
+        // getNextSqrtPriceFromAmount1RoundingDown, adding
+        let quotient = if amount_in <= (U256::from(1) << 160) - U256::from(1) {
+            (amount_in << 96) / U256::from(liquidity)
+        } else {
+            mul_div(amount_in, Q96, U256::from(liquidity)).context("next sqrt price overflows")?
+        };
+        let next = sqrt_price_x96
+            .checked_add(quotient)
+            .context("next sqrt price overflows")?;
+        eyre::ensure!(
+            next < U256::from(1) << 160,
+            "next sqrt price overflows uint160"
+        );
+        Ok(next)
+    }
+}
+
+/// One exact input swap step within a single liquidity range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next_x96: U256,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub fee_amount: U256,
+}
+
+/// Swaps up to `amount_remaining` in from `sqrt_price_current_x96` towards
+/// `sqrt_price_target_x96`, `SwapMath.computeSwapStep` for exact input swaps.
+pub fn compute_swap_step(
+    sqrt_price_current_x96: U256,
+    sqrt_price_target_x96: U256,
+    liquidity: u128,
+    amount_remaining: U256,
+    fee_pips: u32,
+) -> eyre::Result<SwapStep> {
+    let zero_for_one = sqrt_price_current_x96 >= sqrt_price_target_x96;
+    let fee_complement = U256::from(FEE_DENOMINATOR - fee_pips);
+
+    let amount_remaining_less_fee = mul_div(
+        amount_remaining,
+        fee_complement,
+        U256::from(FEE_DENOMINATOR),
+    )
+    .context("amount remaining overflows")?;
+    let amount_in_to_target = if zero_for_one {
+        get_amount_0_delta(
+            sqrt_price_target_x96,
+            sqrt_price_current_x96,
+            liquidity,
+            true,
+        )?
+    } else {
+        get_amount_1_delta(
+            sqrt_price_current_x96,
+            sqrt_price_target_x96,
+            liquidity,
+            true,
+        )?
+    };
+
+    let sqrt_price_next_x96 = if amount_remaining_less_fee >= amount_in_to_target {
+        sqrt_price_target_x96
+    } else {
+        get_next_sqrt_price_from_input(
+            sqrt_price_current_x96,
+            liquidity,
+            amount_remaining_less_fee,
+            zero_for_one,
+        )?
+    };
+    let reached_target = sqrt_price_next_x96 == sqrt_price_target_x96;
+
+    let (amount_in, amount_out) = if zero_for_one {
+        (
+            if reached_target {
+                amount_in_to_target
+            } else {
+                get_amount_0_delta(sqrt_price_next_x96, sqrt_price_current_x96, liquidity, true)?
+            },
+            get_amount_1_delta(
+                sqrt_price_next_x96,
+                sqrt_price_current_x96,
+                liquidity,
+                false,
+            )?,
+        )
+    } else {
+        (
+            if reached_target {
+                amount_in_to_target
+            } else {
+                get_amount_1_delta(sqrt_price_current_x96, sqrt_price_next_x96, liquidity, true)?
+            },
+            get_amount_0_delta(
+                sqrt_price_current_x96,
+                sqrt_price_next_x96,
+                liquidity,
+                false,
+            )?,
+        )
+    };
+
+    let fee_amount = if reached_target {
+        mul_div_rounding_up(amount_in, U256::from(fee_pips), fee_complement)
+            .context("fee amount overflows")?
+    } else {
+        // the remainder goes to the fee
+        amount_remaining - amount_in
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next_x96,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+/// The liquidity a swap can see: the pool's price and in range liquidity, and the net
+/// liquidity of the initialized ticks in a range of tick bitmap words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickLiquidity {
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    pub liquidity: u128,
+    /// Fee in hundredths of a bip
+    pub fee: u32,
+    pub tick_spacing: i32,
+    /// `liquidityNet` of each initialized tick in the loaded words
+    pub ticks: BTreeMap<i32, i128>,
+    /// Lowest and highest loaded tick bitmap word
+    pub words: (i16, i16),
+}
+
+impl TickLiquidity {
+    /// The next initialized tick at or below (`lte`) or above `tick`, or the edge of its
+    /// bitmap word if there is none, `TickBitmap.nextInitializedTickWithinOneWord`.
+    fn next_initialized_tick_within_one_word(
+        &self,
+        tick: i32,
+        lte: bool,
+    ) -> eyre::Result<(i32, bool)> {
+        let compressed = tick.div_euclid(self.tick_spacing);
+        let (first, last) = if lte {
+            let word_start = (compressed >> 8) << 8;
+            (word_start, compressed)
+        } else {
+            let word_start = ((compressed + 1) >> 8) << 8;
+            (compressed + 1, word_start + 255)
+        };
+        let word_position = (first >> 8) as i16;
+        eyre::ensure!(
+            (self.words.0..=self.words.1).contains(&word_position),
+            "swap reaches tick bitmap word {} which wasn't loaded",
+            word_position
+        );
+
+        let range = first * self.tick_spacing..=last * self.tick_spacing;
+        let initialized = if lte {
+            self.ticks.range(range).next_back()
+        } else {
+            self.ticks.range(range).next()
+        };
+        Ok(match initialized {
+            Some((tick, _)) => (*tick, true),
+            None if lte => (first * self.tick_spacing, false),
+            None => (last * self.tick_spacing, false),
+        })
+    }
+
+    /// Lowest and highest price a swap can reach without leaving the loaded words.
+    fn loaded_sqrt_price_range(&self) -> eyre::Result<(U256, U256)> {
+        let lowest = (i32::from(self.words.0) << 8) * self.tick_spacing;
+        let highest = ((i32::from(self.words.1) << 8) + 255) * self.tick_spacing;
+        Ok((
+            get_sqrt_ratio_at_tick(lowest.max(MIN_TICK))?,
+            get_sqrt_ratio_at_tick(highest.min(MAX_TICK))?,
+        ))
+    }
+}
+
+/// Outcome of a simulated exact input swap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapResult {
+    /// Input used, including fees
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    pub liquidity: u128,
+    /// Initialized ticks crossed
+    pub ticks_crossed: Vec<i32>,
+}
+
+/// Simulates an exact input swap of up to `amount_in` until `sqrt_price_limit_x96`, the loop
+/// of `UniswapV3Pool.swap`.
+///
+/// The ticks past the loaded words aren't known, so a swap whose limit lies beyond them stops
+/// at their edge, using less than `amount_in`.
+pub fn simulate_exact_input(
+    pool: &TickLiquidity,
+    zero_for_one: bool,
+    amount_in: U256,
+    sqrt_price_limit_x96: U256,
+) -> eyre::Result<SwapResult> {
+    eyre::ensure!(!amount_in.is_zero(), "swap amount is zero");
+    if zero_for_one {
+        eyre::ensure!(
+            sqrt_price_limit_x96 < pool.sqrt_price_x96 && sqrt_price_limit_x96 > MIN_SQRT_RATIO,
+            "price limit {} not below the pool price",
+            sqrt_price_limit_x96
+        );
+    } else {
+        eyre::ensure!(
+            sqrt_price_limit_x96 > pool.sqrt_price_x96 && sqrt_price_limit_x96 < MAX_SQRT_RATIO,
+            "price limit {} not above the pool price",
+            sqrt_price_limit_x96
+        );
+    }
+
+    let (lowest_loaded, highest_loaded) = pool.loaded_sqrt_price_range()?;
+    let sqrt_price_limit_x96 = if zero_for_one {
+        sqrt_price_limit_x96.max(lowest_loaded)
+    } else {
+        sqrt_price_limit_x96.min(highest_loaded)
+    };
+
+    let mut amount_remaining = amount_in;
+    let mut result = SwapResult {
+        amount_in: U256::ZERO,
+        amount_out: U256::ZERO,
+        sqrt_price_x96: pool.sqrt_price_x96,
+        tick: pool.tick,
+        liquidity: pool.liquidity,
+        ticks_crossed: Vec::new(),
+    };
+
+    while !amount_remaining.is_zero() && result.sqrt_price_x96 != sqrt_price_limit_x96 {
+        let sqrt_price_start_x96 = result.sqrt_price_x96;
+        let (tick_next, initialized) =
+            pool.next_initialized_tick_within_one_word(result.tick, zero_for_one)?;
+        let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+        let sqrt_price_next_x96 = get_sqrt_ratio_at_tick(tick_next)?;
+
+        let sqrt_price_target_x96 = if (zero_for_one && sqrt_price_next_x96 < sqrt_price_limit_x96)
+            || (!zero_for_one && sqrt_price_next_x96 > sqrt_price_limit_x96)
+        {
+            sqrt_price_limit_x96
+        } else {
+            sqrt_price_next_x96
+        };
+
+        let step = compute_swap_step(
+            result.sqrt_price_x96,
+            sqrt_price_target_x96,
+            result.liquidity,
+            amount_remaining,
+            pool.fee,
+        )?;
+        result.sqrt_price_x96 = step.sqrt_price_next_x96;
+        amount_remaining -= step.amount_in + step.fee_amount;
+        result.amount_in += step.amount_in + step.fee_amount;
+        result.amount_out += step.amount_out;
+
+        if result.sqrt_price_x96 == sqrt_price_next_x96 {
+            if initialized {
+                let mut liquidity_net = pool.ticks[&tick_next];
+                if zero_for_one {
+                    liquidity_net = -liquidity_net;
+                }
+                result.liquidity = result
+                    .liquidity
+                    .checked_add_signed(liquidity_net)
+                    .context(format!("liquidity underflows at tick {}", tick_next))?;
+                result.ticks_crossed.push(tick_next);
+            }
+            result.tick = if zero_for_one {
+                tick_next - 1
+            } else {
+                tick_next
+            };
+        } else if result.sqrt_price_x96 != sqrt_price_start_x96 {
+            result.tick = get_tick_at_sqrt_ratio(result.sqrt_price_x96)?;
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A pool at tick 0 with a full range position and a concentrated one in [-60, 60],
+    /// 1e18 liquidity in range, 0.3% fee and the words around tick 0 loaded.
+    pub(crate) fn pool() -> TickLiquidity {
+        TickLiquidity {
+            sqrt_price_x96: Q96,
+            tick: 0,
+            liquidity: 1_000_000_000_000_000_000,
+            fee: 3000,
+            tick_spacing: 60,
+            ticks: BTreeMap::from([
+                (-60, 500_000_000_000_000_000),
+                (60, -500_000_000_000_000_000),
+            ]),
+            words: (-1, 0),
+        }
+    }
+
+    #[test]
+    fn sqrt_ratio_at_tick_bounds() {
+        assert_eq!(get_sqrt_ratio_at_tick(MIN_TICK).unwrap(), MIN_SQRT_RATIO);
+        assert_eq!(get_sqrt_ratio_at_tick(MAX_TICK).unwrap(), MAX_SQRT_RATIO);
+        assert_eq!(get_sqrt_ratio_at_tick(0).unwrap(), Q96);
+        // TickMath.spec.ts
+        assert_eq!(
+            get_sqrt_ratio_at_tick(MIN_TICK + 1).unwrap(),
+            uint!(4295343490_U256)
+        );
+        assert_eq!(
+            get_sqrt_ratio_at_tick(MAX_TICK - 1).unwrap(),
+            uint!(1461373636630004318706518188784493106690254656249_U256)
+        );
+        assert!(get_sqrt_ratio_at_tick(MIN_TICK - 1).is_err());
+        assert!(get_sqrt_ratio_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn tick_at_sqrt_ratio_round_trips() {
+        for tick in [MIN_TICK, -887_000, -60, -1, 0, 1, 60, 100_000, MAX_TICK - 1] {
+            let sqrt_ratio = get_sqrt_ratio_at_tick(tick).unwrap();
+            assert_eq!(get_tick_at_sqrt_ratio(sqrt_ratio).unwrap(), tick);
+            if sqrt_ratio > MIN_SQRT_RATIO {
+                assert_eq!(
+                    get_tick_at_sqrt_ratio(sqrt_ratio - U256::from(1)).unwrap(),
+                    tick - 1
+                );
+            }
+        }
+        assert_eq!(
+            get_tick_at_sqrt_ratio(MAX_SQRT_RATIO - U256::from(1)).unwrap(),
+            MAX_TICK - 1
+        );
+        assert!(get_tick_at_sqrt_ratio(MAX_SQRT_RATIO).is_err());
+    }
+
+    #[test]
+    fn swap_step_capped_at_target() {
+        // SwapMath.spec.ts, exact amount in that gets capped at the price target in one for zero
+        let target = uint!(79623317895830914510639640423_U256);
+        let step = compute_swap_step(
+            Q96,
+            target,
+            2_000_000_000_000_000_000,
+            U256::from(1_000_000_000_000_000_000u128),
+            600,
+        )
+        .unwrap();
+        assert_eq!(step.sqrt_price_next_x96, target);
+        assert_eq!(step.amount_in, uint!(9975124224178055_U256));
+        assert_eq!(step.amount_out, uint!(9925619580021728_U256));
+        assert_eq!(step.fee_amount, uint!(5988667735148_U256));
+    }
+
+    #[test]
+    fn swap_crosses_initialized_tick() {
+        // expected amounts from the v3-core TickMath, SqrtPriceMath and SwapMath formulas run
+        // step by step in exact integer arithmetic: 3004354062741926 + 9040182736436 in for
+        // 2995354955910780 out down to tick -60, then the rest with half the liquidity
+        let swap = simulate_exact_input(
+            &pool(),
+            true,
+            U256::from(10_000_000_000_000_000u128),
+            MIN_SQRT_RATIO + U256::from(1),
+        )
+        .unwrap();
+        assert_eq!(swap.amount_in, uint!(10000000000000000_U256));
+        assert_eq!(swap.amount_out, uint!(9824480656168965_U256));
+        assert_eq!(
+            swap.sqrt_price_x96,
+            uint!(77908727883408741693311100724_U256)
+        );
+        assert_eq!(swap.tick, -336);
+        assert_eq!(swap.liquidity, 500_000_000_000_000_000);
+        assert_eq!(swap.ticks_crossed, vec![-60]);
+    }
+
+    #[test]
+    fn swap_stops_at_loaded_words() {
+        let pool = TickLiquidity {
+            ticks: BTreeMap::new(),
+            ..pool()
+        };
+        let swap = simulate_exact_input(
+            &pool,
+            true,
+            U256::from(u128::MAX),
+            MIN_SQRT_RATIO + U256::from(1),
+        )
+        .unwrap();
+        // the bottom of word -1
+        assert_eq!(swap.sqrt_price_x96, get_sqrt_ratio_at_tick(-15360).unwrap());
+        assert_eq!(swap.tick, -15361);
+        assert_eq!(
+            swap.amount_in,
+            uint!(1155368275718226686_U256) + uint!(3476534430446019_U256)
+        );
+        assert_eq!(swap.amount_out, uint!(536042164457128282_U256));
+    }
+}
@@ -0,0 +1,55 @@
+use std::process::ExitCode;
+
+use auction_interface::{
+    amm_auction_config::AmmAuctionConfig,
+    mock_builder::{MockBuilder, MockBuilderConfig},
+};
+use clap::Parser;
+use color_eyre::eyre::{self, Context};
+
+/// A local eth_sendBundle server recording the bundles the suapp sends
+#[derive(Debug, Parser)]
+#[clap(name = "auction-mock-builder", version)]
+struct Args {
+    /// Address to serve eth_sendBundle on
+    #[clap(long, default_value = "127.0.0.1:8550")]
+    addr: String,
+    /// Rpc url of an anvil chain to apply the bundles to, started with `--order fifo`
+    #[clap(long, env = "MOCK_BUILDER_ANVIL_URL")]
+    anvil_url: Option<String>,
+}
+
+fn main() -> ExitCode {
+    if let Err(err) = run() {
+        eprintln!("{err:?}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn run() -> eyre::Result<()> {
+    let rt = tokio::runtime::Runtime::new().wrap_err("failed to create a new runtime")?;
+
+    rt.block_on(async_main())
+}
+
+async fn async_main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let config = AmmAuctionConfig::new("../solidity_code/.env")
+        .await
+        .context("failed to build auction amm config")?;
+
+    let mut builder_config = MockBuilderConfig::from_config(&config);
+    if let Some(anvil_url) = args.anvil_url {
+        builder_config = builder_config.with_anvil(anvil_url);
+    }
+    let builder = MockBuilder::spawn_on(&args.addr, builder_config).await?;
+    println!("mock builder listening on {}", builder.url());
+
+    tokio::signal::ctrl_c()
+        .await
+        .context("failed to wait for ctrl-c")?;
+    println!("received {} bundles", builder.bundles().await.len());
+    Ok(())
+}